@@ -0,0 +1,92 @@
+//! Compare memory use and access speed of dense and palette-compressed chunk storage on generated terrain.
+//!
+//! Run with `cargo bench --bench paletted`.
+
+use std::{hint::black_box, time::Instant};
+use voxy::PalettedVoxels;
+
+const SIZE: usize = 34;
+const CHUNKS: usize = 64;
+
+const AIR: u16 = 0;
+const STONE: u16 = 1;
+const DIRT: u16 = 2;
+const GRASS: u16 = 3;
+const WATER: u16 = 4;
+const ORES: [u16; 6] = [100, 101, 102, 103, 104, 105];
+
+fn terrain(seed: usize) -> Vec<u16> {
+    let mut voxels = vec![AIR; SIZE * SIZE * SIZE];
+    for z in 0..SIZE {
+        for x in 0..SIZE {
+            let fx = (x + seed * 7) as f32 * 0.15;
+            let fz = (z + seed * 13) as f32 * 0.11;
+            let height = (SIZE as f32 * 0.5 + fx.sin() * 5. + fz.cos() * 4.) as usize;
+
+            for y in 0..SIZE {
+                let idx = x + y * SIZE + z * SIZE * SIZE;
+                voxels[idx] = if y > height {
+                    if y < SIZE / 2 { WATER } else { AIR }
+                } else if y == height {
+                    GRASS
+                } else if y + 3 > height {
+                    DIRT
+                } else if (x * 31 + y * 17 + z * 7 + seed).is_multiple_of(53) {
+                    ORES[(x + y + z + seed) % ORES.len()]
+                } else {
+                    STONE
+                };
+            }
+        }
+    }
+    voxels
+}
+
+fn main() {
+    let dense: Vec<Vec<u16>> = (0..CHUNKS).map(terrain).collect();
+    let dense_bytes: usize = dense.iter().map(|v| v.capacity() * size_of::<u16>()).sum();
+
+    let start = Instant::now();
+    let paletted: Vec<PalettedVoxels<u16>> = dense
+        .iter()
+        .map(|v| PalettedVoxels::from_slice(v))
+        .collect();
+    let compress = start.elapsed();
+    let paletted_bytes: usize = paletted.iter().map(PalettedVoxels::heap_size).sum();
+
+    let start = Instant::now();
+    let mut sum = 0u64;
+    for chunk in &paletted {
+        for idx in 0..chunk.len() {
+            sum += chunk.get(idx) as u64;
+        }
+    }
+    black_box(sum);
+    let get = start.elapsed();
+
+    let start = Instant::now();
+    let mut buf = Vec::new();
+    for chunk in &paletted {
+        chunk.expand_into(&mut buf);
+        black_box(&buf);
+    }
+    let expand = start.elapsed();
+
+    let voxels = CHUNKS * SIZE * SIZE * SIZE;
+    println!("{CHUNKS} chunks of {SIZE}^3 voxels ({voxels} voxels)");
+    println!("dense:    {dense_bytes} bytes");
+    println!(
+        "paletted: {paletted_bytes} bytes ({:.1}% of dense, {} bits per index)",
+        paletted_bytes as f64 / dense_bytes as f64 * 100.,
+        paletted[0].bits_per_index()
+    );
+    println!("compress: {compress:?}");
+    println!(
+        "get:      {get:?} ({:.2} ns/voxel)",
+        get.as_nanos() as f64 / voxels as f64
+    );
+    println!(
+        "expand:   {expand:?} ({:.2} ns/voxel)",
+        expand.as_nanos() as f64 / voxels as f64
+    );
+}
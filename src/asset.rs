@@ -1,11 +1,12 @@
-use crate::{Chunk, VoxelMaterial};
+use crate::{BrickMap, Chunk, VoxelAttributes, VoxelMaterial};
 use bevy::{
     asset::{AssetLoader, LoadContext, io::Reader},
     prelude::*,
 };
 use block_mesh::{MergeVoxel, Voxel, VoxelVisibility};
-use dot_vox::{DotVoxData, SceneNode};
+use dot_vox::{Dict, DotVoxData, SceneNode};
 use ndshape::{RuntimeShape, Shape};
+use std::io::{self, Write};
 
 pub struct VoxFileAssetPlugin;
 
@@ -16,7 +17,7 @@ impl Plugin for VoxFileAssetPlugin {
     }
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct AssetVoxel {
     pub idx: u8,
 }
@@ -27,6 +28,13 @@ impl AsRef<u8> for AssetVoxel {
     }
 }
 
+impl VoxelAttributes for AssetVoxel {
+    fn attributes(&self) -> u32 {
+        // Index 0 is empty and never meshed, the rest map to palette colours 0..255.
+        (self.idx as u32).saturating_sub(1)
+    }
+}
+
 impl Voxel for AssetVoxel {
     fn get_visibility(&self) -> VoxelVisibility {
         if self.idx == 0 {
@@ -45,10 +53,100 @@ impl MergeVoxel for AssetVoxel {
     }
 }
 
+/// A padded chunk of voxels from a `.vox` model.
+pub type AssetVoxelChunk = Chunk<AssetVoxel, Vec<AssetVoxel>, RuntimeShape<u32, 3>>;
+
 pub struct AssetChunk {
-    pub chunk: Chunk<AssetVoxel, Vec<AssetVoxel>, RuntimeShape<u32, 3>>,
+    pub chunk: AssetVoxelChunk,
+    pub transform: Transform,
+    pub name: Option<String>,
+}
+
+/// A model from a `.vox` file, split into one or more chunks.
+pub struct AssetModel {
+    pub chunks: Vec<AssetChunk>,
     pub transform: Transform,
+    /// The name of the closest named node above this model.
     pub name: Option<String>,
+    /// The names of the named nodes from the scene root down to this model.
+    pub path: Vec<String>,
+}
+
+impl AssetModel {
+    /// Create a model from padded voxels, splitting it into chunks of at most `chunk_size`³ voxels like [`VoxFileAsset::models`].
+    ///
+    /// `shape` includes one voxel of padding on each side, which should be empty.
+    pub fn from_voxels(
+        voxels: Vec<AssetVoxel>,
+        shape: RuntimeShape<u32, 3>,
+        chunk_size: Option<u32>,
+        name: Option<String>,
+    ) -> Self {
+        let path = name.iter().cloned().collect();
+        let size = UVec3::from(shape.as_array()) - UVec3::splat(2);
+        let Some(chunk_size) = chunk_size else {
+            return AssetModel {
+                chunks: vec![AssetChunk {
+                    chunk: Chunk::new(voxels, shape, UVec3::ZERO, size + UVec3::ONE),
+                    transform: Transform::IDENTITY,
+                    name: name.clone(),
+                }],
+                transform: Transform::IDENTITY,
+                name,
+                path,
+            };
+        };
+        let chunk_size = UVec3::splat(chunk_size.max(1));
+
+        let mut chunks = Vec::new();
+        for cz in (0..size.z).step_by(chunk_size.z as usize) {
+            for cy in (0..size.y).step_by(chunk_size.y as usize) {
+                for cx in (0..size.x).step_by(chunk_size.x as usize) {
+                    let origin = UVec3::new(cx, cy, cz);
+                    let chunk_shape_size = chunk_size.min(size - origin) + UVec3::splat(2);
+                    let chunk_shape = RuntimeShape::<u32, 3>::new(chunk_shape_size.to_array());
+
+                    let mut is_empty = true;
+                    let chunk_voxels = (0..chunk_shape.size())
+                        .map(|idx| {
+                            let local = UVec3::from(chunk_shape.delinearize(idx));
+                            let voxel =
+                                voxels[shape.linearize((origin + local).to_array()) as usize];
+
+                            let is_interior = local.cmpgt(UVec3::ZERO).all()
+                                && local.cmplt(chunk_shape_size - UVec3::ONE).all();
+                            if is_interior && voxel.idx != 0 {
+                                is_empty = false;
+                            }
+                            voxel
+                        })
+                        .collect();
+
+                    if is_empty {
+                        continue;
+                    }
+
+                    chunks.push(AssetChunk {
+                        chunk: Chunk::new(
+                            chunk_voxels,
+                            chunk_shape,
+                            UVec3::ZERO,
+                            chunk_shape_size - UVec3::ONE,
+                        ),
+                        transform: Transform::from_translation(origin.as_vec3()),
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+
+        AssetModel {
+            chunks,
+            transform: Transform::IDENTITY,
+            name,
+            path,
+        }
+    }
 }
 
 #[derive(Debug, Asset, TypePath)]
@@ -92,46 +190,210 @@ impl VoxFileAsset {
         }
     }
 
+    /// Iterate over every model in this file as a single chunk, transformed into scene space.
+    ///
+    /// This is [`VoxFileAsset::models`] without a chunk size, flattened.
     pub fn chunks(&self) -> impl Iterator<Item = AssetChunk> + '_ {
+        self.models(None).flat_map(|model| {
+            model.chunks.into_iter().map(move |chunk| AssetChunk {
+                transform: model.transform * chunk.transform,
+                ..chunk
+            })
+        })
+    }
+
+    /// Iterate over the models in this file, splitting each model into chunks of at most `chunk_size`³ voxels.
+    ///
+    /// Each chunk is padded with one voxel from its neighbours so faces between chunks are culled,
+    /// and its transform is relative to the model. Chunks without any voxels are skipped.
+    /// If `chunk_size` is `None`, each model contains a single chunk.
+    pub fn models(&self, chunk_size: Option<u32>) -> impl Iterator<Item = AssetModel> + '_ {
+        let mut models = Vec::new();
+        visit_node(
+            &self.file,
+            &mut models,
+            &self.file.scenes[0],
+            Transform::default(),
+            Vec::new(),
+        );
+
+        models.into_iter().map(move |(model, transform, path)| {
+            let (voxels, shape) = model_voxels(model);
+            AssetModel {
+                transform,
+                path: path.clone(),
+                ..AssetModel::from_voxels(voxels, shape, chunk_size, path.last().cloned())
+            }
+        })
+    }
+
+    /// Merge every model in the file's scene into a single sparse [`BrickMap`].
+    ///
+    /// Voxels are placed where the meshes of [`VoxFileAsset::models`] put them in scene space,
+    /// including the one voxel of padding, so the brick meshes line up with the spawned chunks of the same file.
+    /// Models whose transform isn't aligned to the voxel grid are snapped to it.
+    pub fn brickmap(&self) -> BrickMap<AssetVoxel> {
         let mut models = Vec::new();
         visit_node(
             &self.file,
             &mut models,
             &self.file.scenes[0],
             Transform::default(),
-            None,
+            Vec::new(),
         );
 
-        models.into_iter().map(move |(model, transform, name)| {
-            let shape =
-                RuntimeShape::<u32, 3>::new([model.size.x + 2, model.size.z + 2, model.size.y + 2]);
+        let mut brickmap = BrickMap::new(AssetVoxel::default());
+        for (model, transform, _) in models {
+            brickmap.extend(model.voxels.iter().map(|voxel| {
+                let local = UVec3::new(voxel.x as _, voxel.z as _, voxel.y as _) + UVec3::ONE;
+                let center = transform.transform_point(local.as_vec3() + 0.5);
+                (center.floor().as_ivec3(), AssetVoxel { idx: voxel.i + 1 })
+            }));
+        }
+        brickmap
+    }
+
+    /// Remove models without any voxels, along with their references in the scene graph.
+    ///
+    /// Returns the number of removed models.
+    pub fn strip_empty_models(&mut self) -> usize {
+        let mut new_ids = Vec::with_capacity(self.file.models.len());
+        let mut next_id = 0;
+        for model in &self.file.models {
+            new_ids.push((!model.voxels.is_empty()).then(|| {
+                next_id += 1;
+                next_id - 1
+            }));
+        }
+
+        let removed = self.file.models.len() - next_id as usize;
+        if removed == 0 {
+            return 0;
+        }
 
-            let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
-            for voxel in &model.voxels {
-                voxels[shape.linearize([voxel.x as u32 + 1, voxel.z as u32 + 1, voxel.y as u32 + 1])
-                    as usize] = AssetVoxel { idx: voxel.i + 1 };
+        self.file.models.retain(|model| !model.voxels.is_empty());
+        for node in &mut self.file.scenes {
+            if let SceneNode::Shape { models, .. } = node {
+                models.retain_mut(|shape_model| {
+                    match new_ids
+                        .get(shape_model.model_id as usize)
+                        .copied()
+                        .flatten()
+                    {
+                        Some(id) => {
+                            shape_model.model_id = id;
+                            true
+                        }
+                        None => false,
+                    }
+                });
             }
+        }
+        removed
+    }
 
-            AssetChunk {
-                chunk: Chunk::new(
-                    voxels,
-                    shape,
-                    UVec3::ZERO,
-                    UVec3::new(model.size.x + 1, model.size.z + 1, model.size.y + 1),
-                ),
-                transform,
-                name,
+    /// Point voxels using a palette entry to the first entry with the same colour and material.
+    ///
+    /// The palette itself is unchanged, so duplicate entries are left unused.
+    /// Returns the number of palette entries that were merged into an earlier entry.
+    pub fn dedup_palette(&mut self) -> usize {
+        let material = |idx: usize| {
+            // Materials use 1-based palette indices.
+            self.file
+                .materials
+                .iter()
+                .find(|material| material.id as usize == idx + 1)
+                .map(|material| &material.properties)
+        };
+
+        let mut remap: Vec<u8> = (0..=255).collect();
+        let mut merged = 0;
+        for idx in 1..self.file.palette.len().min(256) {
+            if let Some(first) = (0..idx).find(|first| {
+                remap[*first] as usize == *first
+                    && self.file.palette[*first] == self.file.palette[idx]
+                    && material(*first) == material(idx)
+            }) {
+                remap[idx] = first as u8;
+                merged += 1;
             }
-        })
+        }
+
+        for model in &mut self.file.models {
+            for voxel in &mut model.voxels {
+                voxel.i = remap[voxel.i as usize];
+            }
+        }
+        merged
+    }
+
+    /// Write this file in the `.vox` format, including its materials and layers.
+    ///
+    /// [`DotVoxData::write_vox`] only writes the models, scene graph and palette,
+    /// so the `MATL` and `LAYR` chunks are appended to the children of its `MAIN` chunk.
+    pub fn write_vox(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        self.file.write_vox(&mut bytes)?;
+
+        let mut chunks = Vec::new();
+        for material in &self.file.materials {
+            let mut content = material.id.to_le_bytes().to_vec();
+            write_dict(&mut content, &material.properties);
+            write_chunk(&mut chunks, b"MATL", &content);
+        }
+        for (id, layer) in self.file.layers.iter().enumerate() {
+            let mut content = (id as u32).to_le_bytes().to_vec();
+            write_dict(&mut content, &layer.attributes);
+            // Reserved, always -1.
+            content.extend_from_slice(&(-1i32).to_le_bytes());
+            write_chunk(&mut chunks, b"LAYR", &content);
+        }
+
+        // The children size of `MAIN` follows the file header, the chunk id and its empty content size.
+        let children = &mut bytes[16..20];
+        let size = u32::from_le_bytes(children.try_into().unwrap()) + chunks.len() as u32;
+        children.copy_from_slice(&size.to_le_bytes());
+
+        writer.write_all(&bytes)?;
+        writer.write_all(&chunks)
+    }
+}
+
+fn write_chunk(buffer: &mut Vec<u8>, id: &[u8; 4], content: &[u8]) {
+    buffer.extend_from_slice(id);
+    buffer.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&0u32.to_le_bytes());
+    buffer.extend_from_slice(content);
+}
+
+fn write_dict(buffer: &mut Vec<u8>, dict: &Dict) {
+    buffer.extend_from_slice(&(dict.len() as u32).to_le_bytes());
+    for (key, value) in dict {
+        for string in [key, value] {
+            buffer.extend_from_slice(&(string.len() as u32).to_le_bytes());
+            buffer.extend_from_slice(string.as_bytes());
+        }
     }
 }
 
+fn model_voxels(model: &dot_vox::Model) -> (Vec<AssetVoxel>, RuntimeShape<u32, 3>) {
+    let shape = RuntimeShape::<u32, 3>::new([model.size.x + 2, model.size.z + 2, model.size.y + 2]);
+
+    let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+    for voxel in &model.voxels {
+        voxels[shape.linearize([voxel.x as u32 + 1, voxel.z as u32 + 1, voxel.y as u32 + 1])
+            as usize] = AssetVoxel { idx: voxel.i + 1 };
+    }
+
+    (voxels, shape)
+}
+
 fn visit_node<'a>(
     file: &'a DotVoxData,
-    models: &mut Vec<(&'a dot_vox::Model, Transform, Option<String>)>,
+    models: &mut Vec<(&'a dot_vox::Model, Transform, Vec<String>)>,
     node: &SceneNode,
     transform: Transform,
-    name: Option<String>,
+    mut path: Vec<String>,
 ) {
     match node {
         SceneNode::Transform {
@@ -144,14 +406,14 @@ fn visit_node<'a>(
                 .position()
                 .map(|t| Vec3::new(-t.x as _, t.z as _, t.y as _))
                 .unwrap_or_default();
-            let name = attributes.get("_name").cloned().or(name);
+            path.extend(attributes.get("_name").cloned());
 
             visit_node(
                 file,
                 models,
                 &file.scenes[*child as usize],
                 transform.with_translation(transform.translation + translation),
-                name,
+                path,
             );
         }
         SceneNode::Group { children, .. } => {
@@ -161,7 +423,7 @@ fn visit_node<'a>(
                     models,
                     &file.scenes[*child as usize],
                     transform,
-                    name.clone(),
+                    path.clone(),
                 );
             }
         }
@@ -180,7 +442,7 @@ fn visit_node<'a>(
                                 file.models[model.model_id as usize].size.y as _,
                             ) / 2.,
                     ),
-                    name.clone(),
+                    path.clone(),
                 ));
             }
         }
@@ -0,0 +1,341 @@
+//! Inspect and convert MagicaVoxel `.vox` files without running an app.
+//!
+//! Run `voxy help` for usage.
+
+use dot_vox::{DotVoxData, SceneNode};
+use std::{
+    collections::BTreeMap,
+    env,
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use voxy::{ExportMesh, VoxFileAsset, VoxelScene};
+
+const USAGE: &str = "\
+Usage: voxy <command> [options]
+
+Commands:
+  info <file.vox>                    Print the models, palette, materials and node graph of a file
+  mesh-stats <file.vox>              Mesh every model and print geometry statistics
+      --chunk-size <n>               Split models into chunks of at most n³ voxels
+      --packed                       Build packed meshes
+      --max-triangles <n>            Fail if the scene has more than n triangles
+  export <file.vox> <output>         Mesh the scene and write it as .glb, .gltf, .obj or .ply
+      --chunk-size <n>               Split models into chunks of at most n³ voxels
+  optimize <file.vox> <output.vox>   Remove empty models and point voxels using a duplicate palette entry
+                                     at the first identical one (the duplicates stay in the palette)
+  help                               Print this message";
+
+type Result<T = ()> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<ExitCode> {
+    let Some((command, args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return Ok(ExitCode::FAILURE);
+    };
+    let options = Options::parse(args)?;
+
+    match command.as_str() {
+        "info" => info(&options.input(0)?)?,
+        "mesh-stats" => return mesh_stats(&options),
+        "export" => export(&options)?,
+        "optimize" => optimize(&options)?,
+        "help" | "-h" | "--help" => println!("{USAGE}"),
+        _ => return Err(format!("unknown command `{command}`, run `voxy help` for usage").into()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Default)]
+struct Options {
+    paths: Vec<PathBuf>,
+    chunk_size: Option<u32>,
+    packed: bool,
+    max_triangles: Option<usize>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{name}`"))
+            };
+            match arg.as_str() {
+                "--chunk-size" => options.chunk_size = Some(value(arg)?.parse()?),
+                "--max-triangles" => options.max_triangles = Some(value(arg)?.parse()?),
+                "--packed" => options.packed = true,
+                _ if arg.starts_with("--") => return Err(format!("unknown option `{arg}`").into()),
+                _ => options.paths.push(arg.into()),
+            }
+        }
+        Ok(options)
+    }
+
+    fn input(&self, idx: usize) -> Result<PathBuf> {
+        self.paths
+            .get(idx)
+            .cloned()
+            .ok_or_else(|| "missing file argument, run `voxy help` for usage".into())
+    }
+
+    fn scene(&self, asset: &VoxFileAsset) -> VoxelScene {
+        VoxelScene::from_models(asset.models(self.chunk_size), asset.material(), self.packed)
+    }
+}
+
+fn load(path: &Path) -> Result<VoxFileAsset> {
+    let file = dot_vox::load(&path.to_string_lossy())
+        .map_err(|error| format!("failed to load {}: {error}", path.display()))?;
+    Ok(VoxFileAsset { file })
+}
+
+fn info(path: &Path) -> Result {
+    let asset = load(path)?;
+    let file = &asset.file;
+    println!(
+        "{}: version {}, {} models, {} nodes, {} layers",
+        path.display(),
+        file.version,
+        file.models.len(),
+        file.scenes.len(),
+        file.layers.len()
+    );
+
+    println!("\nmodels:");
+    for (idx, model) in file.models.iter().enumerate() {
+        println!(
+            "  {idx:>4}  {}x{}x{}  {} voxels",
+            model.size.x,
+            model.size.y,
+            model.size.z,
+            model.voxels.len()
+        );
+    }
+
+    println!("\nscene models:");
+    for model in asset.models(None) {
+        let name = if model.path.is_empty() {
+            String::from("<unnamed>")
+        } else {
+            model.path.join("/")
+        };
+        println!("  {name}  at {}", model.transform.translation);
+    }
+
+    println!("\nnode graph:");
+    if !file.scenes.is_empty() {
+        print_node(file, 0, 1);
+    }
+
+    let mut usage = BTreeMap::new();
+    for voxel in file.models.iter().flat_map(|model| &model.voxels) {
+        *usage.entry(voxel.i).or_insert(0usize) += 1;
+    }
+    println!(
+        "\npalette: {} of {} colours used",
+        usage.len(),
+        file.palette.len()
+    );
+    for (idx, count) in &usage {
+        let color = file.palette[*idx as usize];
+        println!(
+            "  {:>4}  #{:02x}{:02x}{:02x}{:02x}  {count} voxels",
+            idx + 1,
+            color.r,
+            color.g,
+            color.b,
+            color.a
+        );
+    }
+
+    println!("\nmaterials:");
+    for material in &file.materials {
+        if material.id == 0 || !usage.contains_key(&((material.id - 1) as u8)) {
+            continue;
+        }
+        let properties: Vec<_> = material
+            .properties
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+        println!("  {:>4}  {}", material.id, properties.join(" "));
+    }
+
+    Ok(())
+}
+
+fn print_node(file: &DotVoxData, idx: u32, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let Some(node) = file.scenes.get(idx as usize) else {
+        println!("{indent}<missing node {idx}>");
+        return;
+    };
+
+    match node {
+        SceneNode::Transform {
+            attributes,
+            frames,
+            child,
+            layer_id,
+        } => {
+            let name = attributes.get("_name").map_or("", String::as_str);
+            let translation = frames
+                .first()
+                .and_then(|frame| frame.position())
+                .map(|position| format!(" at ({}, {}, {})", position.x, position.y, position.z))
+                .unwrap_or_default();
+            println!("{indent}transform {idx} {name}{translation} layer {layer_id}");
+            print_node(file, *child, depth + 1);
+        }
+        SceneNode::Group { children, .. } => {
+            println!("{indent}group {idx}");
+            for child in children {
+                print_node(file, *child, depth + 1);
+            }
+        }
+        SceneNode::Shape { models, .. } => {
+            let ids: Vec<_> = models
+                .iter()
+                .map(|model| model.model_id.to_string())
+                .collect();
+            println!("{indent}shape {idx} models [{}]", ids.join(", "));
+        }
+    }
+}
+
+fn mesh_stats(options: &Options) -> Result<ExitCode> {
+    let asset = load(&options.input(0)?)?;
+    let scene = options.scene(&asset);
+    let stats = scene.stats();
+
+    println!(
+        "{:<32} {:>6} {:>8} {:>9} {:>9} {:>10} {:>8} {:>6}",
+        "model", "chunks", "quads", "vertices", "indices", "triangles", "emissive", "lights"
+    );
+    let count = |count: Option<usize>| count.map_or(String::from("-"), |count| count.to_string());
+    for (idx, model) in stats.models.iter().enumerate() {
+        let name = model.name.clone().unwrap_or_else(|| idx.to_string());
+        println!(
+            "{name:<32} {:>6} {:>8} {:>9} {:>9} {:>10} {:>8} {:>6}",
+            model.chunks,
+            model.mesh.quads,
+            model.mesh.vertices,
+            model.mesh.indices,
+            model.mesh.triangles,
+            count(model.emissive_voxels),
+            model.lights
+        );
+    }
+    println!(
+        "{:<32} {:>6} {:>8} {:>9} {:>9} {:>10} {:>8} {:>6}",
+        "total",
+        stats.models.iter().map(|model| model.chunks).sum::<usize>(),
+        stats.total.quads,
+        stats.total.vertices,
+        stats.total.indices,
+        stats.total.triangles,
+        count(stats.emissive_voxels),
+        stats.lights
+    );
+    if let Some((min, max)) = stats.total.bounds {
+        println!("bounds: {min} to {max}");
+    }
+
+    let mut failed = false;
+    for issue in scene.validate() {
+        eprintln!("warning: {issue}");
+    }
+    if let Some(max_triangles) = options.max_triangles
+        && stats.total.triangles > max_triangles
+    {
+        eprintln!(
+            "error: {} triangles is over the budget of {max_triangles}",
+            stats.total.triangles
+        );
+        failed = true;
+    }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn export(options: &Options) -> Result {
+    let input = options.input(0)?;
+    let output = options.input(1)?;
+    let asset = load(&input)?;
+    let scene = VoxelScene::from_models(asset.models(options.chunk_size), asset.material(), false);
+
+    let meshes = ExportMesh::from_scene(&scene);
+
+    let extension = output
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let mut writer = BufWriter::new(File::create(&output)?);
+    match extension.as_deref() {
+        Some("glb") => voxy::export::write_gltf(&meshes, true, &mut writer)?,
+        Some("gltf") => voxy::export::write_gltf(&meshes, false, &mut writer)?,
+        Some("obj") => {
+            // The palette is written next to the OBJ file.
+            let mtl_path = output.with_extension("mtl");
+            let mtl_file = mtl_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+            voxy::export::write_obj(&meshes, &scene.material, &mtl_file, &mut writer, &mut mtl)?;
+            mtl.flush()?;
+        }
+        Some("ply") => voxy::export::write_ply(&meshes, &mut writer)?,
+        _ => {
+            return Err(format!(
+                "unsupported output format for {}, expected .glb, .gltf, .obj or .ply",
+                output.display()
+            )
+            .into());
+        }
+    }
+    writer.flush()?;
+
+    println!("wrote {}", output.display());
+    Ok(())
+}
+
+fn optimize(options: &Options) -> Result {
+    let input = options.input(0)?;
+    let output = options.input(1)?;
+    let mut asset = load(&input)?;
+
+    let models = asset.strip_empty_models();
+    let colors = asset.dedup_palette();
+    println!(
+        "removed {models} empty models, remapped voxels of {colors} duplicate palette entries"
+    );
+
+    let mut writer = BufWriter::new(File::create(&output)?);
+    asset.write_vox(&mut writer)?;
+    writer.flush()?;
+
+    println!("wrote {}", output.display());
+    Ok(())
+}
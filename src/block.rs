@@ -0,0 +1,92 @@
+use crate::{Chunk, VoxelId, build_quads};
+use bevy::{
+    mesh::{MeshVertexAttribute, VertexAttributeValues},
+    prelude::*,
+    render::render_resource::VertexFormat,
+};
+use block_mesh::{MergeVoxel, RIGHT_HANDED_Y_UP_CONFIG};
+use ndshape::Shape;
+use std::collections::HashMap;
+
+/// Texture array layer for each vertex of a textured block mesh.
+pub const ATTRIBUTE_TILE_INDEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("TileIndex", 988940918, VertexFormat::Uint32);
+
+/// Atlas tiles for each face of a block, in the same order as [`RIGHT_HANDED_Y_UP_CONFIG`]:
+/// `-X`, `-Y`, `-Z`, `+X`, `+Y`, `+Z`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockTextures {
+    pub faces: [u32; 6],
+}
+
+impl BlockTextures {
+    /// Use the same tile for every face.
+    pub fn all(tile: u32) -> Self {
+        Self { faces: [tile; 6] }
+    }
+
+    /// Use separate tiles for the top, sides, and bottom of the block (e.g. grass).
+    pub fn top_side_bottom(top: u32, side: u32, bottom: u32) -> Self {
+        Self {
+            faces: [side, bottom, side, side, top, side],
+        }
+    }
+}
+
+/// Registry mapping voxel IDs to per-face atlas tiles, used to build meshes for a [`TexturedVoxelMaterial`](crate::TexturedVoxelMaterial).
+#[derive(Clone, Debug, Default, Resource)]
+pub struct BlockRegistry {
+    blocks: HashMap<VoxelId, BlockTextures>,
+}
+
+impl BlockRegistry {
+    /// Register the textures for voxel `id`, replacing any previous textures.
+    pub fn insert(&mut self, id: VoxelId, textures: BlockTextures) -> &mut Self {
+        self.blocks.insert(id, textures);
+        self
+    }
+
+    /// Returns the textures for voxel `id`.
+    pub fn get(&self, id: VoxelId) -> Option<&BlockTextures> {
+        self.blocks.get(&id)
+    }
+}
+
+impl<V, VS, S> Chunk<V, VS, S>
+where
+    VS: AsRef<[V]>,
+    V: MergeVoxel + Copy + Into<VoxelId>,
+    S: Shape<3, Coord = u32>,
+{
+    /// Build a textured mesh for this chunk, looking up each voxel's face tiles in `registry`.
+    ///
+    /// UVs are measured in voxels so textures repeat across greedy-merged quads,
+    /// and [`ATTRIBUTE_TILE_INDEX`] selects the texture array layer. Unregistered voxels use tile 0.
+    pub fn build_textured(&self, registry: &BlockRegistry) -> Mesh {
+        let voxels = self.voxels.as_ref();
+        let faces = RIGHT_HANDED_Y_UP_CONFIG.faces;
+        let mut uvs = Vec::new();
+        let mut tile_indices = Vec::new();
+
+        let mesh = build_quads(voxels, &self.shape, self.min, self.max, |face_idx, quad| {
+            uvs.extend_from_slice(&faces[face_idx].tex_coords(
+                RIGHT_HANDED_Y_UP_CONFIG.u_flip_face,
+                true,
+                quad,
+            ));
+
+            let id = voxels[self.shape.linearize(quad.minimum) as usize].into();
+            let tile = registry
+                .get(id)
+                .map(|textures| textures.faces[face_idx])
+                .unwrap_or_default();
+            tile_indices.extend_from_slice(&[tile; 4]);
+        });
+
+        mesh.with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, VertexAttributeValues::Float32x2(uvs))
+            .with_inserted_attribute(
+                ATTRIBUTE_TILE_INDEX,
+                VertexAttributeValues::Uint32(tile_indices),
+            )
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{AssetVoxel, Chunk, VoxelAttributes};
+use bevy::prelude::*;
+use block_mesh::MergeVoxel;
+use ndshape::{ConstShape, ConstShape3u32};
+use std::collections::HashMap;
+
+/// The width, height and depth of a brick in voxels.
+pub const BRICK_SIZE: u32 = 8;
+
+const BRICK_LEN: usize = (BRICK_SIZE * BRICK_SIZE * BRICK_SIZE) as usize;
+
+/// The shape of a single brick.
+pub type BrickShape = ConstShape3u32<BRICK_SIZE, BRICK_SIZE, BRICK_SIZE>;
+
+/// The shape of a brick with one voxel of padding on each side, used for meshing.
+pub type PaddedBrickShape =
+    ConstShape3u32<{ BRICK_SIZE + 2 }, { BRICK_SIZE + 2 }, { BRICK_SIZE + 2 }>;
+
+/// Sparse voxel storage made of [`BRICK_SIZE`]³ bricks.
+///
+/// Only bricks containing at least one non-empty voxel are allocated,
+/// so very large models (e.g. 2048³ scans) only pay for their occupied regions.
+#[derive(Clone, Debug)]
+pub struct BrickMap<V> {
+    bricks: HashMap<IVec3, Box<[V; BRICK_LEN]>>,
+    empty: V,
+}
+
+impl<V> BrickMap<V>
+where
+    V: Copy + PartialEq,
+{
+    /// Create an empty brickmap, where `empty` is returned for unallocated voxels.
+    pub fn new(empty: V) -> Self {
+        Self {
+            bricks: HashMap::new(),
+            empty,
+        }
+    }
+
+    /// Returns the voxel at `pos`.
+    pub fn get(&self, pos: IVec3) -> V {
+        let (key, local) = split(pos);
+        self.bricks
+            .get(&key)
+            .map(|brick| brick[BrickShape::linearize(local.to_array()) as usize])
+            .unwrap_or(self.empty)
+    }
+
+    /// Set the voxel at `pos`, allocating its brick if needed.
+    pub fn set(&mut self, pos: IVec3, voxel: V) {
+        let (key, local) = split(pos);
+        let idx = BrickShape::linearize(local.to_array()) as usize;
+
+        if let Some(brick) = self.bricks.get_mut(&key) {
+            brick[idx] = voxel;
+        } else if voxel != self.empty {
+            let mut brick = Box::new([self.empty; BRICK_LEN]);
+            brick[idx] = voxel;
+            self.bricks.insert(key, brick);
+        }
+    }
+
+    /// Returns the voxel returned for unallocated positions.
+    pub fn empty(&self) -> V {
+        self.empty
+    }
+
+    /// Returns the number of allocated bricks.
+    pub fn brick_count(&self) -> usize {
+        self.bricks.len()
+    }
+
+    /// Returns the brick at `key` (in brick coordinates), if allocated.
+    pub fn brick(&self, key: IVec3) -> Option<&[V]> {
+        self.bricks.get(&key).map(|brick| brick.as_slice())
+    }
+
+    /// Iterate over allocated bricks by their brick coordinate, in no particular order.
+    ///
+    /// Voxels in each brick are linearized with [`BrickShape`].
+    pub fn bricks(&self) -> impl Iterator<Item = (IVec3, &[V])> + '_ {
+        self.bricks
+            .iter()
+            .map(|(key, brick)| (*key, brick.as_slice()))
+    }
+
+    /// Free bricks that only contain empty voxels.
+    pub fn prune(&mut self) {
+        let empty = self.empty;
+        self.bricks
+            .retain(|_, brick| brick.iter().any(|voxel| *voxel != empty));
+    }
+
+    /// Returns the inclusive minimum and exclusive maximum voxel positions of the allocated bricks.
+    pub fn bounds(&self) -> Option<(IVec3, IVec3)> {
+        let mut keys = self.bricks.keys();
+        let first = *keys.next()?;
+        let (min, max) = keys.fold((first, first), |(min, max), key| {
+            (min.min(*key), max.max(*key))
+        });
+        Some((
+            min * BRICK_SIZE as i32,
+            (max + IVec3::ONE) * BRICK_SIZE as i32,
+        ))
+    }
+
+    /// Copy the brick at `key` and a one voxel border from its neighbours into a chunk ready for meshing.
+    ///
+    /// Returns `None` if the brick isn't allocated.
+    pub fn brick_chunk(&self, key: IVec3) -> Option<Chunk<V, Vec<V>, PaddedBrickShape>> {
+        self.bricks.get(&key)?;
+
+        let origin = key * BRICK_SIZE as i32 - IVec3::ONE;
+        let voxels = (0..PaddedBrickShape::USIZE)
+            .map(|idx| {
+                let local = UVec3::from(PaddedBrickShape::delinearize(idx as u32));
+                self.get(origin + local.as_ivec3())
+            })
+            .collect();
+
+        Some(Chunk::new(
+            voxels,
+            PaddedBrickShape {},
+            UVec3::ZERO,
+            UVec3::splat(BRICK_SIZE + 1),
+        ))
+    }
+}
+
+impl<V> BrickMap<V>
+where
+    V: MergeVoxel + VoxelAttributes + Copy + PartialEq,
+{
+    /// Mesh each allocated brick with the greedy mesher.
+    ///
+    /// Each mesh is positioned relative to [`brick_translation`] of its brick coordinate.
+    pub fn build_meshes(&self) -> impl Iterator<Item = (IVec3, Mesh)> + '_ {
+        self.bricks.keys().filter_map(|key| {
+            let mesh = self.brick_chunk(*key)?.build();
+            (mesh.count_vertices() > 0).then_some((*key, mesh))
+        })
+    }
+}
+
+impl BrickMap<AssetVoxel> {
+    /// Build a brickmap from a MagicaVoxel model, converting from Z-up to Y-up like [`VoxFileAsset::models`](crate::VoxFileAsset::models).
+    ///
+    /// Voxels are placed in model space without padding or the model's scene transform,
+    /// see [`VoxFileAsset::brickmap`](crate::VoxFileAsset::brickmap) to place them like the scene.
+    pub fn from_model(model: &dot_vox::Model) -> Self {
+        model
+            .voxels
+            .iter()
+            .map(|voxel| {
+                (
+                    IVec3::new(voxel.x as _, voxel.z as _, voxel.y as _),
+                    AssetVoxel { idx: voxel.i + 1 },
+                )
+            })
+            .collect()
+    }
+}
+
+impl<V> Extend<(IVec3, V)> for BrickMap<V>
+where
+    V: Copy + PartialEq,
+{
+    fn extend<T: IntoIterator<Item = (IVec3, V)>>(&mut self, iter: T) {
+        for (pos, voxel) in iter {
+            self.set(pos, voxel);
+        }
+    }
+}
+
+impl<V> FromIterator<(IVec3, V)> for BrickMap<V>
+where
+    V: Copy + PartialEq + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (IVec3, V)>>(iter: T) -> Self {
+        let mut brickmap = Self::new(V::default());
+        brickmap.extend(iter);
+        brickmap
+    }
+}
+
+/// Returns the translation of a mesh built from the brick at `key` by [`BrickMap::build_meshes`].
+pub fn brick_translation(key: IVec3) -> Vec3 {
+    (key * BRICK_SIZE as i32 - IVec3::ONE).as_vec3()
+}
+
+fn split(pos: IVec3) -> (IVec3, UVec3) {
+    let size = BRICK_SIZE as i32;
+    (
+        pos.div_euclid(IVec3::splat(size)),
+        pos.rem_euclid(IVec3::splat(size)).as_uvec3(),
+    )
+}
@@ -0,0 +1,367 @@
+//! Brushes and boolean operations for editing voxel data.
+//!
+//! Every edit returns the [`EditBounds`] of the voxels it changed, so only the affected chunks need to be remeshed.
+
+use crate::{BrickMap, Chunk, PalettedVoxels};
+use bevy::prelude::*;
+use ndshape::Shape;
+use std::hash::Hash;
+
+/// The region of voxels changed by an edit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EditBounds {
+    /// The inclusive minimum position.
+    pub min: IVec3,
+    /// The exclusive maximum position.
+    pub max: IVec3,
+}
+
+impl EditBounds {
+    /// Returns the bounds of a single voxel.
+    pub fn voxel(pos: IVec3) -> Self {
+        Self {
+            min: pos,
+            max: pos + IVec3::ONE,
+        }
+    }
+
+    /// Returns the smallest bounds containing both `self` and `other`.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Returns the number of voxels in the bounds along each axis.
+    pub fn size(&self) -> UVec3 {
+        (self.max - self.min).max(IVec3::ZERO).as_uvec3()
+    }
+
+    /// Returns `true` if `pos` is inside the bounds.
+    pub fn contains(&self, pos: IVec3) -> bool {
+        pos.cmpge(self.min).all() && pos.cmplt(self.max).all()
+    }
+
+    /// Iterate over the coordinates of the chunks of `chunk_size` voxels that must be remeshed.
+    ///
+    /// This includes neighbouring chunks whose one voxel of padding overlaps the bounds.
+    pub fn chunks(&self, chunk_size: UVec3) -> impl Iterator<Item = IVec3> + use<> {
+        let chunk_size = chunk_size.max(UVec3::ONE).as_ivec3();
+        let min = (self.min - IVec3::ONE).div_euclid(chunk_size);
+        let max = self.max.div_euclid(chunk_size) + IVec3::ONE;
+        positions(min, max)
+    }
+}
+
+/// Voxel storage that can be edited with [`Brush`]es and boolean operations.
+///
+/// Positions outside of [`extent`](Self::extent) read as [`empty`](Self::empty) and writes to them are ignored.
+pub trait VoxelVolume {
+    type Voxel: Copy + PartialEq;
+
+    /// Returns the voxel used for empty space.
+    fn empty(&self) -> Self::Voxel;
+
+    /// Returns the inclusive minimum and exclusive maximum positions that can be stored, or `None` if unbounded.
+    fn extent(&self) -> Option<(IVec3, IVec3)>;
+
+    /// Returns the inclusive minimum and exclusive maximum positions that may contain non-empty voxels.
+    fn occupied(&self) -> Option<(IVec3, IVec3)> {
+        self.extent()
+    }
+
+    /// Returns the voxel at `pos`.
+    fn get(&self, pos: IVec3) -> Self::Voxel;
+
+    /// Set the voxel at `pos`, returning the previous voxel.
+    fn set(&mut self, pos: IVec3, voxel: Self::Voxel) -> Self::Voxel;
+
+    /// Set every voxel inside `brush` to `voxel`.
+    fn fill(&mut self, brush: &Brush, voxel: Self::Voxel) -> Option<EditBounds> {
+        let (min, max) = clip(brush.bounds(), self.extent())?;
+        let mut bounds = None;
+        for pos in positions(min, max).filter(|pos| brush.contains(*pos)) {
+            write(self, pos, voxel, &mut bounds);
+        }
+        bounds
+    }
+
+    /// Set every voxel inside `brush` to [`empty`](Self::empty).
+    fn erase(&mut self, brush: &Brush) -> Option<EditBounds> {
+        let empty = self.empty();
+        self.fill(brush, empty)
+    }
+
+    /// Replace every non-empty voxel inside `brush` with `voxel`, keeping the shape of the volume.
+    fn paint(&mut self, brush: &Brush, voxel: Self::Voxel) -> Option<EditBounds> {
+        let (min, max) = clip(brush.bounds(), self.extent())?;
+        let empty = self.empty();
+        let mut bounds = None;
+        for pos in positions(min, max).filter(|pos| brush.contains(*pos)) {
+            if self.get(pos) != empty {
+                write(self, pos, voxel, &mut bounds);
+            }
+        }
+        bounds
+    }
+
+    /// Copy the non-empty voxels of `other`, moved by `offset`, into this volume.
+    fn union<O>(&mut self, other: &O, offset: IVec3) -> Option<EditBounds>
+    where
+        O: VoxelVolume<Voxel = Self::Voxel>,
+    {
+        let (min, max) = other.occupied()?;
+        let (min, max) = clip((min + offset, max + offset), self.extent())?;
+        let mut bounds = None;
+        for pos in positions(min, max) {
+            let voxel = other.get(pos - offset);
+            if voxel != other.empty() {
+                write(self, pos, voxel, &mut bounds);
+            }
+        }
+        bounds
+    }
+
+    /// Erase the voxels covered by the non-empty voxels of `other`, moved by `offset`.
+    fn subtract<O>(&mut self, other: &O, offset: IVec3) -> Option<EditBounds>
+    where
+        O: VoxelVolume<Voxel = Self::Voxel>,
+    {
+        let (min, max) = other.occupied()?;
+        let (min, max) = clip((min + offset, max + offset), self.occupied())?;
+        let empty = self.empty();
+        let mut bounds = None;
+        for pos in positions(min, max) {
+            if other.get(pos - offset) != other.empty() {
+                write(self, pos, empty, &mut bounds);
+            }
+        }
+        bounds
+    }
+
+    /// Erase the voxels not covered by the non-empty voxels of `other`, moved by `offset`.
+    fn intersect<O>(&mut self, other: &O, offset: IVec3) -> Option<EditBounds>
+    where
+        O: VoxelVolume<Voxel = Self::Voxel>,
+    {
+        let (min, max) = self.occupied()?;
+        let empty = self.empty();
+        let mut bounds = None;
+        for pos in positions(min, max) {
+            if other.get(pos - offset) == other.empty() {
+                write(self, pos, empty, &mut bounds);
+            }
+        }
+        bounds
+    }
+}
+
+fn write<V: VoxelVolume + ?Sized>(
+    volume: &mut V,
+    pos: IVec3,
+    voxel: V::Voxel,
+    bounds: &mut Option<EditBounds>,
+) {
+    if volume.get(pos) == voxel {
+        return;
+    }
+
+    volume.set(pos, voxel);
+    let voxel_bounds = EditBounds::voxel(pos);
+    *bounds = Some(bounds.map_or(voxel_bounds, |bounds| bounds.union(voxel_bounds)));
+}
+
+fn clip((min, max): (IVec3, IVec3), extent: Option<(IVec3, IVec3)>) -> Option<(IVec3, IVec3)> {
+    let (min, max) = match extent {
+        Some((extent_min, extent_max)) => (min.max(extent_min), max.min(extent_max)),
+        None => (min, max),
+    };
+    max.cmpgt(min).all().then_some((min, max))
+}
+
+pub(crate) fn positions(min: IVec3, max: IVec3) -> impl Iterator<Item = IVec3> {
+    (min.z..max.z).flat_map(move |z| {
+        (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| IVec3::new(x, y, z)))
+    })
+}
+
+/// A shape of voxels to edit, in the coordinates of a [`VoxelVolume`].
+///
+/// A voxel is inside a brush if its centre (`pos + 0.5`) is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Brush {
+    Sphere {
+        center: Vec3,
+        radius: f32,
+    },
+    /// Every voxel from `min` (inclusive) to `max` (exclusive).
+    Cuboid {
+        min: IVec3,
+        max: IVec3,
+    },
+    /// A cylinder with flat ends at `start` and `end`.
+    Cylinder {
+        start: Vec3,
+        end: Vec3,
+        radius: f32,
+    },
+    /// A line with rounded ends. A radius of `0.5` draws a line one voxel thick.
+    Line {
+        start: Vec3,
+        end: Vec3,
+        radius: f32,
+    },
+}
+
+impl Brush {
+    /// Returns a line brush one voxel thick between the centres of voxels `start` and `end`.
+    pub fn line(start: IVec3, end: IVec3) -> Self {
+        Self::Line {
+            start: start.as_vec3() + Vec3::splat(0.5),
+            end: end.as_vec3() + Vec3::splat(0.5),
+            radius: 0.5,
+        }
+    }
+
+    /// Returns `true` if the voxel at `pos` is inside the brush.
+    pub fn contains(&self, pos: IVec3) -> bool {
+        let point = pos.as_vec3() + Vec3::splat(0.5);
+        match *self {
+            Self::Sphere { center, radius } => point.distance_squared(center) <= radius * radius,
+            Self::Cuboid { min, max } => pos.cmpge(min).all() && pos.cmplt(max).all(),
+            Self::Cylinder { start, end, radius } => {
+                let axis = end - start;
+                let length_squared = axis.length_squared();
+                if length_squared == 0. {
+                    return false;
+                }
+                let t = (point - start).dot(axis) / length_squared;
+                (0. ..=1.).contains(&t)
+                    && point.distance_squared(start + axis * t) <= radius * radius
+            }
+            Self::Line { start, end, radius } => {
+                let axis = end - start;
+                let t = if axis.length_squared() > 0. {
+                    ((point - start).dot(axis) / axis.length_squared()).clamp(0., 1.)
+                } else {
+                    0.
+                };
+                point.distance_squared(start + axis * t) <= radius * radius
+            }
+        }
+    }
+
+    /// Returns the inclusive minimum and exclusive maximum positions of the voxels that may be inside the brush.
+    pub fn bounds(&self) -> (IVec3, IVec3) {
+        let (min, max) = match *self {
+            Self::Cuboid { min, max } => return (min, max),
+            Self::Sphere { center, radius } => (center - radius, center + radius),
+            Self::Cylinder { start, end, radius } | Self::Line { start, end, radius } => {
+                (start.min(end) - radius, start.max(end) + radius)
+            }
+        };
+        (
+            (min - 0.5).ceil().as_ivec3(),
+            (max - 0.5).floor().as_ivec3() + IVec3::ONE,
+        )
+    }
+}
+
+impl<V> VoxelVolume for BrickMap<V>
+where
+    V: Copy + PartialEq,
+{
+    type Voxel = V;
+
+    fn empty(&self) -> V {
+        BrickMap::empty(self)
+    }
+
+    fn extent(&self) -> Option<(IVec3, IVec3)> {
+        None
+    }
+
+    fn occupied(&self) -> Option<(IVec3, IVec3)> {
+        self.bounds()
+    }
+
+    fn get(&self, pos: IVec3) -> V {
+        BrickMap::get(self, pos)
+    }
+
+    fn set(&mut self, pos: IVec3, voxel: V) -> V {
+        let previous = BrickMap::get(self, pos);
+        BrickMap::set(self, pos, voxel);
+        previous
+    }
+}
+
+/// Chunks are edited in the coordinates of their shape, excluding the one voxel of padding around `min` and `max`.
+impl<V, S> VoxelVolume for Chunk<V, Vec<V>, S>
+where
+    V: Copy + PartialEq + Default,
+    S: Shape<3, Coord = u32>,
+{
+    type Voxel = V;
+
+    fn empty(&self) -> V {
+        V::default()
+    }
+
+    fn extent(&self) -> Option<(IVec3, IVec3)> {
+        Some(chunk_extent(self.min, self.max))
+    }
+
+    fn get(&self, pos: IVec3) -> V {
+        chunk_index(self, pos).map_or_else(V::default, |idx| self.voxels[idx])
+    }
+
+    fn set(&mut self, pos: IVec3, voxel: V) -> V {
+        match chunk_index(self, pos) {
+            Some(idx) => std::mem::replace(&mut self.voxels[idx], voxel),
+            None => V::default(),
+        }
+    }
+}
+
+/// Chunks are edited in the coordinates of their shape, excluding the one voxel of padding around `min` and `max`.
+impl<V, S> VoxelVolume for Chunk<V, PalettedVoxels<V>, S>
+where
+    V: Copy + Eq + Hash + Default,
+    S: Shape<3, Coord = u32>,
+{
+    type Voxel = V;
+
+    fn empty(&self) -> V {
+        V::default()
+    }
+
+    fn extent(&self) -> Option<(IVec3, IVec3)> {
+        Some(chunk_extent(self.min, self.max))
+    }
+
+    fn get(&self, pos: IVec3) -> V {
+        chunk_index(self, pos).map_or_else(V::default, |idx| self.voxels.get(idx))
+    }
+
+    fn set(&mut self, pos: IVec3, voxel: V) -> V {
+        match chunk_index(self, pos) {
+            Some(idx) => self.voxels.set(idx, voxel),
+            None => V::default(),
+        }
+    }
+}
+
+fn chunk_extent(min: UVec3, max: UVec3) -> (IVec3, IVec3) {
+    (min.as_ivec3() + IVec3::ONE, max.as_ivec3())
+}
+
+fn chunk_index<V, VS, S>(chunk: &Chunk<V, VS, S>, pos: IVec3) -> Option<usize>
+where
+    S: Shape<3, Coord = u32>,
+{
+    let (min, max) = chunk_extent(chunk.min, chunk.max);
+    (pos.cmpge(min).all() && pos.cmplt(max).all())
+        .then(|| chunk.shape.linearize(pos.as_uvec3().to_array()) as usize)
+}
@@ -0,0 +1,423 @@
+//! glTF, Wavefront OBJ and PLY export of voxel meshes, for debugging and use with third-party tools.
+//!
+//! Meshes are first converted to [`ExportMesh`]es, which resolve each vertex's colour from a [`VoxelMaterial`].
+
+use crate::{
+    ATTRIBUTE_BLEND_WEIGHT, ATTRIBUTE_COLOR_INDEX, ATTRIBUTE_PACKED_VOXEL, FACE_NORMALS,
+    VoxelMaterial, VoxelScene, unpack_face, unpack_position,
+};
+use bevy::{
+    mesh::{Indices, VertexAttributeValues},
+    prelude::*,
+    render::render_resource::PrimitiveTopology,
+};
+use serde_json::{Value, json};
+use std::io::{self, Write};
+
+/// A triangle mesh with float attributes and the palette colour of each vertex.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExportMesh {
+    pub name: String,
+    /// The transform of the mesh in the exported scene.
+    pub transform: Transform,
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    /// The palette index of each vertex, used to assign OBJ materials.
+    pub color_indices: Vec<u8>,
+    /// The linear colour of each vertex, blended for smooth meshes.
+    pub colors: Vec<Vec3>,
+    pub indices: Vec<u32>,
+}
+
+impl ExportMesh {
+    /// Convert a mesh built with [`MeshBuilder::build`](crate::MeshBuilder::build),
+    /// [`Chunk::build_packed`](crate::Chunk::build_packed) or [`Chunk::build_smooth`](crate::Chunk::build_smooth),
+    /// coloured by `material`.
+    ///
+    /// Returns `None` if the mesh isn't a triangle list with positions or packed vertices.
+    pub fn from_mesh(
+        name: impl Into<String>,
+        mesh: &Mesh,
+        material: &VoxelMaterial,
+    ) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+
+        let (positions, normals, attributes): (Vec<Vec3>, Vec<Vec3>, Vec<u32>) =
+            if let Some(VertexAttributeValues::Uint32x2(packed)) =
+                mesh.attribute(ATTRIBUTE_PACKED_VOXEL)
+            {
+                (
+                    packed
+                        .iter()
+                        .map(|packed| unpack_position(*packed))
+                        .collect(),
+                    packed
+                        .iter()
+                        .map(|packed| FACE_NORMALS[unpack_face(*packed) as usize % 6])
+                        .collect(),
+                    packed.iter().map(|packed| packed[1]).collect(),
+                )
+            } else {
+                let Some(VertexAttributeValues::Float32x3(positions)) =
+                    mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+                else {
+                    return None;
+                };
+                let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+                    Some(VertexAttributeValues::Float32x3(normals)) => {
+                        normals.iter().map(|normal| Vec3::from(*normal)).collect()
+                    }
+                    _ => vec![Vec3::ZERO; positions.len()],
+                };
+                let attributes = match mesh.attribute(ATTRIBUTE_COLOR_INDEX) {
+                    Some(VertexAttributeValues::Uint32(attributes)) => attributes.clone(),
+                    _ => vec![0; positions.len()],
+                };
+                (
+                    positions
+                        .iter()
+                        .map(|position| Vec3::from(*position))
+                        .collect(),
+                    normals,
+                    attributes,
+                )
+            };
+
+        let blend_weights = match mesh.attribute(ATTRIBUTE_BLEND_WEIGHT) {
+            Some(VertexAttributeValues::Float32(weights)) => Some(weights),
+            _ => None,
+        };
+        let colors = attributes
+            .iter()
+            .enumerate()
+            .map(|(idx, attributes)| {
+                let color = material.colors[(*attributes & 0xff) as usize];
+                match blend_weights {
+                    Some(weights) => color.lerp(
+                        material.colors[((*attributes >> 8) & 0xff) as usize],
+                        weights[idx],
+                    ),
+                    None => color,
+                }
+            })
+            .collect();
+
+        let indices = match mesh.indices() {
+            Some(Indices::U16(indices)) => indices.iter().map(|idx| *idx as u32).collect(),
+            Some(Indices::U32(indices)) => indices.clone(),
+            None => (0..positions.len() as u32).collect(),
+        };
+
+        Some(Self {
+            name: name.into(),
+            transform: Transform::IDENTITY,
+            positions,
+            normals,
+            color_indices: attributes
+                .iter()
+                .map(|attributes| *attributes as u8)
+                .collect(),
+            colors,
+            indices,
+        })
+    }
+
+    /// Convert every non-empty chunk mesh of a scene, named after its model's label and chunk index.
+    ///
+    /// Scenes loaded by [`SceneLoader`](crate::scene::SceneLoader) keep their meshes in `Assets<Mesh>` instead, so they are skipped.
+    pub fn from_scene(scene: &VoxelScene) -> Vec<Self> {
+        let mut meshes = Vec::new();
+        for (model, label) in scene.models.iter().zip(scene.model_labels()) {
+            for (idx, lit_mesh) in model.meshes.iter().enumerate() {
+                let Some(mut mesh) = lit_mesh.mesh.as_ref().and_then(|mesh| {
+                    Self::from_mesh(format!("{label}_{idx}"), mesh, &scene.material)
+                }) else {
+                    continue;
+                };
+                if mesh.indices.is_empty() {
+                    continue;
+                }
+
+                mesh.transform = model.transform * lit_mesh.transform;
+                meshes.push(mesh);
+            }
+        }
+        meshes
+    }
+
+    /// Returns the positions transformed by [`transform`](Self::transform).
+    pub fn world_positions(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.positions
+            .iter()
+            .map(|position| self.transform.transform_point(*position))
+    }
+
+    /// Returns the normals rotated by [`transform`](Self::transform).
+    pub fn world_normals(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.normals
+            .iter()
+            .map(|normal| (self.transform.rotation * *normal).normalize_or_zero())
+    }
+}
+
+/// Write meshes as a Wavefront OBJ file with one object per mesh, transformed into scene space.
+///
+/// Each palette index used by the meshes gets a material named `palette_<index>` in `mtl`,
+/// with its colour as `Kd` and its emission as `Ke`, both in linear space.
+/// `mtl_file` is the path of the MTL file relative to the OBJ file.
+/// Vertex colours are also written after each position, which many tools read.
+pub fn write_obj(
+    meshes: &[ExportMesh],
+    material: &VoxelMaterial,
+    mtl_file: &str,
+    obj: &mut impl Write,
+    mtl: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(obj, "# exported by voxy")?;
+    writeln!(obj, "mtllib {mtl_file}")?;
+
+    let mut used = [false; 256];
+    let mut offset = 1;
+    for mesh in meshes {
+        writeln!(obj, "o {}", mesh.name)?;
+        for (Vec3 { x, y, z }, color) in mesh.world_positions().zip(&mesh.colors) {
+            writeln!(obj, "v {x} {y} {z} {} {} {}", color.x, color.y, color.z)?;
+        }
+        for Vec3 { x, y, z } in mesh.world_normals() {
+            writeln!(obj, "vn {x} {y} {z}")?;
+        }
+
+        let mut current = None;
+        for triangle in mesh.indices.chunks_exact(3) {
+            let color_index = mesh.color_indices[triangle[0] as usize];
+            if current != Some(color_index) {
+                writeln!(obj, "usemtl palette_{color_index}")?;
+                used[color_index as usize] = true;
+                current = Some(color_index);
+            }
+
+            let [a, b, c] = [0, 1, 2].map(|corner| triangle[corner] + offset);
+            writeln!(obj, "f {a}//{a} {b}//{b} {c}//{c}")?;
+        }
+        offset += mesh.positions.len() as u32;
+    }
+
+    writeln!(mtl, "# exported by voxy")?;
+    for (idx, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        let Vec3 { x, y, z } = material.colors[idx];
+        let emission = material.emissions[idx];
+        writeln!(mtl, "newmtl palette_{idx}")?;
+        writeln!(mtl, "Kd {x} {y} {z}")?;
+        if emission.x > 0. {
+            let Vec3 { x, y, z } = material.colors[idx] * emission.x;
+            writeln!(mtl, "Ke {x} {y} {z}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Write meshes as an ASCII PLY file with per-vertex normals and colours, transformed into scene space.
+///
+/// Colours are written as sRGB bytes, as most tools expect.
+pub fn write_ply(meshes: &[ExportMesh], writer: &mut impl Write) -> io::Result<()> {
+    let vertices: usize = meshes.iter().map(|mesh| mesh.positions.len()).sum();
+    let faces: usize = meshes.iter().map(|mesh| mesh.indices.len() / 3).sum();
+
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format ascii 1.0")?;
+    writeln!(writer, "comment exported by voxy")?;
+    writeln!(writer, "element vertex {vertices}")?;
+    for property in ["x", "y", "z", "nx", "ny", "nz"] {
+        writeln!(writer, "property float {property}")?;
+    }
+    for property in ["red", "green", "blue"] {
+        writeln!(writer, "property uchar {property}")?;
+    }
+    writeln!(writer, "element face {faces}")?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    for mesh in meshes {
+        for ((position, normal), color) in mesh
+            .world_positions()
+            .zip(mesh.world_normals())
+            .zip(&mesh.colors)
+        {
+            let [r, g, b, _] = Color::linear_rgb(color.x, color.y, color.z)
+                .to_srgba()
+                .to_u8_array();
+            writeln!(
+                writer,
+                "{} {} {} {} {} {} {r} {g} {b}",
+                position.x, position.y, position.z, normal.x, normal.y, normal.z
+            )?;
+        }
+    }
+
+    let mut offset = 0;
+    for mesh in meshes {
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| triangle[corner] + offset);
+            writeln!(writer, "3 {a} {b} {c}")?;
+        }
+        offset += mesh.positions.len() as u32;
+    }
+    Ok(())
+}
+
+/// Write meshes as glTF 2.0 with vertex colours, either as a binary `.glb` or a `.gltf` with an embedded buffer.
+pub fn write_gltf(meshes: &[ExportMesh], binary: bool, writer: &mut impl Write) -> io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut gltf_meshes = Vec::new();
+    let mut nodes = Vec::new();
+
+    let mut push_view = |buffer: &mut Vec<u8>, data: &[u8], target: u32| {
+        buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": buffer.len(),
+            "byteLength": data.len(),
+            "target": target,
+        }));
+        buffer.extend_from_slice(data);
+        buffer_views.len() - 1
+    };
+    const ARRAY_BUFFER: u32 = 34962;
+    const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+    const FLOAT: u32 = 5126;
+    const UNSIGNED_INT: u32 = 5125;
+
+    for mesh in meshes {
+        let (min, max) = mesh.positions.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), position| (min.min(*position), max.max(*position)),
+        );
+
+        let mut vec3_accessor = |data: &[Vec3], bounds: Option<(Vec3, Vec3)>| {
+            let bytes: Vec<u8> = data
+                .iter()
+                .flat_map(|value| value.to_array())
+                .flat_map(f32::to_le_bytes)
+                .collect();
+            let view = push_view(&mut buffer, &bytes, ARRAY_BUFFER);
+            let mut accessor = json!({
+                "bufferView": view,
+                "componentType": FLOAT,
+                "count": data.len(),
+                "type": "VEC3",
+            });
+            if let Some((min, max)) = bounds {
+                accessor["min"] = json!(min.to_array());
+                accessor["max"] = json!(max.to_array());
+            }
+            accessors.push(accessor);
+            accessors.len() - 1
+        };
+        let position = vec3_accessor(&mesh.positions, Some((min, max)));
+        let normal = vec3_accessor(&mesh.normals, None);
+        let color = vec3_accessor(&mesh.colors, None);
+
+        let bytes: Vec<u8> = mesh
+            .indices
+            .iter()
+            .flat_map(|idx| idx.to_le_bytes())
+            .collect();
+        let view = push_view(&mut buffer, &bytes, ELEMENT_ARRAY_BUFFER);
+        accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": mesh.indices.len(),
+            "type": "SCALAR",
+        }));
+
+        gltf_meshes.push(json!({
+            "name": mesh.name,
+            "primitives": [{
+                "attributes": { "POSITION": position, "NORMAL": normal, "COLOR_0": color },
+                "indices": accessors.len() - 1,
+                "material": 0,
+            }],
+        }));
+        nodes.push(json!({
+            "name": mesh.name,
+            "mesh": gltf_meshes.len() - 1,
+            "translation": mesh.transform.translation.to_array(),
+            "rotation": mesh.transform.rotation.to_array(),
+            "scale": mesh.transform.scale.to_array(),
+        }));
+    }
+
+    let mut buffer_json = json!({ "byteLength": buffer.len() });
+    if !binary {
+        buffer_json["uri"] = Value::String(format!(
+            "data:application/octet-stream;base64,{}",
+            base64(&buffer)
+        ));
+    }
+
+    let gltf = json!({
+        "asset": { "version": "2.0", "generator": "voxy" },
+        "scene": 0,
+        "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+        "nodes": nodes,
+        "meshes": gltf_meshes,
+        "materials": [{
+            "pbrMetallicRoughness": {
+                "baseColorFactor": [1., 1., 1., 1.],
+                "metallicFactor": 0.,
+                "roughnessFactor": 1.,
+            },
+        }],
+        "buffers": if buffer.is_empty() { json!([]) } else { json!([buffer_json]) },
+        "bufferViews": buffer_views,
+        "accessors": accessors,
+    });
+
+    if !binary {
+        return serde_json::to_writer_pretty(writer, &gltf).map_err(io::Error::other);
+    }
+
+    // Chunks are padded to 4 bytes, JSON with spaces and binary data with zeros.
+    let mut json = serde_json::to_vec(&gltf).map_err(io::Error::other)?;
+    json.resize(json.len().next_multiple_of(4), b' ');
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+    let mut length = 12 + 8 + json.len();
+    if !buffer.is_empty() {
+        length += 8 + buffer.len();
+    }
+    writer.write_all(b"glTF")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&(length as u32).to_le_bytes())?;
+    writer.write_all(&(json.len() as u32).to_le_bytes())?;
+    writer.write_all(b"JSON")?;
+    writer.write_all(&json)?;
+    if !buffer.is_empty() {
+        writer.write_all(&(buffer.len() as u32).to_le_bytes())?;
+        writer.write_all(b"BIN\0")?;
+        writer.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for bytes in data.chunks(3) {
+        let n = (bytes[0] as u32) << 16
+            | (*bytes.get(1).unwrap_or(&0) as u32) << 8
+            | *bytes.get(2).unwrap_or(&0) as u32;
+        for idx in 0..4 {
+            if idx <= bytes.len() {
+                out.push(ALPHABET[(n >> (18 - idx * 6)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
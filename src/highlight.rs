@@ -0,0 +1,120 @@
+use crate::{VoxelMaterial, VoxelSceneChunk};
+use bevy::{color::ColorToPacked, mesh::MeshTag, prelude::*};
+
+pub struct HighlightPlugin;
+
+impl Plugin for HighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (apply_highlights, remove_highlights.after(apply_highlights)),
+        );
+    }
+}
+
+/// Tint a spawned [`VoxelScene`](crate::VoxelScene) with a highlight colour, e.g. to show that it is hovered or selected.
+///
+/// Insert this on the entity with the [`VoxelSceneHandle`](crate::scene::VoxelSceneHandle),
+/// or on a [`VoxelSceneModel`](crate::VoxelSceneModel) to highlight a single model.
+/// The colour is mixed into the lit colour of the voxels by its alpha, and the highlight of the nearest ancestor wins.
+///
+/// The highlight is stored in the [`MeshTag`] of each [`VoxelMaterial`] mesh below the entity, so highlighted entities
+/// keep their material, including any [`VoxelPaletteOverride`](crate::VoxelPaletteOverride) or
+/// [`PaletteAnimation`](crate::PaletteAnimation). Meshes that use their [`MeshTag`] for something else can't be highlighted.
+#[derive(Clone, Copy, Debug, PartialEq, Component)]
+pub struct VoxelHighlight {
+    pub color: Color,
+}
+
+impl VoxelHighlight {
+    pub fn new(color: impl Into<Color>) -> Self {
+        Self {
+            color: color.into(),
+        }
+    }
+
+    /// Returns the colour packed into a [`MeshTag`] for `voxel_material.wgsl`.
+    pub fn tag(&self) -> MeshTag {
+        MeshTag(u32::from_le_bytes(self.color.to_linear().to_u8_array()))
+    }
+}
+
+/// Returns the highlight of `entity` or its nearest highlighted ancestor.
+fn nearest_highlight<'a>(
+    entity: Entity,
+    highlight_query: &'a Query<&VoxelHighlight>,
+    parent_query: &Query<&ChildOf>,
+) -> Option<&'a VoxelHighlight> {
+    std::iter::once(entity)
+        .chain(parent_query.iter_ancestors(entity))
+        .find_map(|entity| highlight_query.get(entity).ok())
+}
+
+/// Tag the meshes below changed highlights, and new meshes spawned below highlighted entities (e.g. on reload).
+#[allow(clippy::type_complexity)]
+pub fn apply_highlights(
+    mut commands: Commands,
+    changed_query: Query<Entity, Changed<VoxelHighlight>>,
+    added_query: Query<Entity, Added<MeshMaterial3d<VoxelMaterial>>>,
+    mesh_query: Query<Option<&MeshTag>, With<MeshMaterial3d<VoxelMaterial>>>,
+    highlight_query: Query<&VoxelHighlight>,
+    parent_query: Query<&ChildOf>,
+    children_query: Query<&Children>,
+) {
+    let changed = changed_query
+        .iter()
+        .flat_map(|entity| std::iter::once(entity).chain(children_query.iter_descendants(entity)));
+    for entity in changed.chain(&added_query) {
+        let Ok(current) = mesh_query.get(entity) else {
+            continue;
+        };
+        let Some(highlight) = nearest_highlight(entity, &highlight_query, &parent_query) else {
+            continue;
+        };
+
+        let tag = highlight.tag();
+        if current != Some(&tag) {
+            commands.entity(entity).insert(tag);
+        }
+    }
+}
+
+/// Restore the meshes below entities whose [`VoxelHighlight`] was removed, to the highlight of an outer ancestor if any.
+pub fn remove_highlights(
+    mut commands: Commands,
+    mut removed: RemovedComponents<VoxelHighlight>,
+    mesh_query: Query<(), (With<MeshMaterial3d<VoxelMaterial>>, With<MeshTag>)>,
+    highlight_query: Query<&VoxelHighlight>,
+    parent_query: Query<&ChildOf>,
+    children_query: Query<&Children>,
+) {
+    for entity in removed.read() {
+        for mesh in std::iter::once(entity).chain(children_query.iter_descendants(entity)) {
+            if !mesh_query.contains(mesh) {
+                continue;
+            }
+
+            match nearest_highlight(mesh, &highlight_query, &parent_query) {
+                Some(highlight) => commands.entity(mesh).insert(highlight.tag()),
+                None => commands.entity(mesh).remove::<MeshTag>(),
+            };
+        }
+    }
+}
+
+/// Draw a wireframe cube around a voxel of a scene chunk, such as the voxel of a picking `VoxelHit`.
+///
+/// `voxel` is in model coordinates, like [`VoxelSceneChunk::origin`],
+/// and `transform` is the [`GlobalTransform`] of the chunk entity.
+pub fn voxel_outline<Config: GizmoConfigGroup>(
+    gizmos: &mut Gizmos<Config>,
+    chunk: &VoxelSceneChunk,
+    transform: &GlobalTransform,
+    voxel: IVec3,
+    color: impl Into<Color>,
+) {
+    // Grow the cube slightly so it isn't hidden by the voxel's faces.
+    let center = (voxel - chunk.origin).as_vec3() + Vec3::splat(0.5);
+    let cube = Transform::from_translation(center).with_scale(Vec3::splat(1.01));
+    gizmos.cuboid(transform.mul_transform(cube), color);
+}
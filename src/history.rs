@@ -0,0 +1,304 @@
+use crate::{EditBounds, VoxelVolume};
+use bevy::prelude::*;
+use std::{
+    collections::{HashMap, VecDeque},
+    mem,
+};
+
+/// A [`VoxelVolume`] that remembers the original value of every voxel written through it.
+///
+/// Created by [`EditHistory::edit`].
+pub struct EditRecorder<'a, Vol: VoxelVolume> {
+    volume: &'a mut Vol,
+    before: HashMap<IVec3, Vol::Voxel>,
+}
+
+impl<'a, Vol: VoxelVolume> EditRecorder<'a, Vol> {
+    pub fn new(volume: &'a mut Vol) -> Self {
+        Self {
+            volume,
+            before: HashMap::new(),
+        }
+    }
+
+    /// Returns the changes made through this recorder, or `None` if every voxel is unchanged.
+    pub fn finish(self) -> Option<VoxelEdit<Vol::Voxel>> {
+        let changes = self.before.into_iter().filter_map(|(pos, before)| {
+            let after = self.volume.get(pos);
+            (after != before).then_some((pos, before, after))
+        });
+        VoxelEdit::from_changes(changes)
+    }
+}
+
+impl<Vol: VoxelVolume> VoxelVolume for EditRecorder<'_, Vol> {
+    type Voxel = Vol::Voxel;
+
+    fn empty(&self) -> Self::Voxel {
+        self.volume.empty()
+    }
+
+    fn extent(&self) -> Option<(IVec3, IVec3)> {
+        self.volume.extent()
+    }
+
+    fn occupied(&self) -> Option<(IVec3, IVec3)> {
+        self.volume.occupied()
+    }
+
+    fn get(&self, pos: IVec3) -> Self::Voxel {
+        self.volume.get(pos)
+    }
+
+    fn set(&mut self, pos: IVec3, voxel: Self::Voxel) -> Self::Voxel {
+        let previous = self.volume.set(pos, voxel);
+        self.before.entry(pos).or_insert(previous);
+        previous
+    }
+}
+
+/// A run of consecutive changed voxels with the same before and after values.
+#[derive(Clone, Copy, Debug)]
+struct EditRun<V> {
+    /// The number of unchanged voxels since the previous run.
+    skip: u32,
+    len: u32,
+    before: V,
+    after: V,
+}
+
+/// The before and after values of the voxels changed by an edit.
+///
+/// Changes are run-length encoded in the order of [`EditBounds`],
+/// so filling or erasing a region with a single voxel costs a few bytes per row.
+#[derive(Clone, Debug)]
+pub struct VoxelEdit<V> {
+    bounds: EditBounds,
+    runs: Vec<EditRun<V>>,
+    len: usize,
+}
+
+impl<V: Copy + PartialEq> VoxelEdit<V> {
+    /// Encode `(position, before, after)` changes, or return `None` if there are none.
+    pub fn from_changes(changes: impl IntoIterator<Item = (IVec3, V, V)>) -> Option<Self> {
+        let mut changes: Vec<_> = changes.into_iter().collect();
+        let bounds = changes
+            .iter()
+            .map(|(pos, ..)| EditBounds::voxel(*pos))
+            .reduce(EditBounds::union)?;
+
+        let size = bounds.size();
+        let linearize = |pos: IVec3| {
+            let local = (pos - bounds.min).as_uvec3();
+            local.x + size.x * (local.y + size.y * local.z)
+        };
+        changes.sort_unstable_by_key(|(pos, ..)| linearize(*pos));
+
+        let mut runs: Vec<EditRun<V>> = Vec::new();
+        let mut next = 0;
+        for (pos, before, after) in &changes {
+            let idx = linearize(*pos);
+            match runs.last_mut() {
+                Some(run) if idx == next && run.before == *before && run.after == *after => {
+                    run.len += 1;
+                }
+                _ => runs.push(EditRun {
+                    skip: idx - next,
+                    len: 1,
+                    before: *before,
+                    after: *after,
+                }),
+            }
+            next = idx + 1;
+        }
+        runs.shrink_to_fit();
+
+        Some(Self {
+            bounds,
+            runs,
+            len: changes.len(),
+        })
+    }
+
+    /// Returns the region of the changed voxels.
+    pub fn bounds(&self) -> EditBounds {
+        self.bounds
+    }
+
+    /// Returns the number of changed voxels.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no voxels were changed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the approximate number of bytes used by this edit.
+    pub fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.runs.capacity() * mem::size_of::<EditRun<V>>()
+    }
+
+    /// Iterate over the changed voxels as `(position, before, after)`.
+    pub fn changes(&self) -> impl Iterator<Item = (IVec3, V, V)> + '_ {
+        let size = self.bounds.size();
+        let mut next = 0;
+        self.runs.iter().flat_map(move |run| {
+            let start = next + run.skip;
+            next = start + run.len;
+            (start..next).map(move |idx| {
+                let local =
+                    UVec3::new(idx % size.x, idx / size.x % size.y, idx / (size.x * size.y));
+                (self.bounds.min + local.as_ivec3(), run.before, run.after)
+            })
+        })
+    }
+
+    /// Write the values from before the edit into `volume`.
+    pub fn revert<Vol: VoxelVolume<Voxel = V> + ?Sized>(&self, volume: &mut Vol) -> EditBounds {
+        for (pos, before, _) in self.changes() {
+            volume.set(pos, before);
+        }
+        self.bounds
+    }
+
+    /// Write the values from after the edit into `volume`.
+    pub fn apply<Vol: VoxelVolume<Voxel = V> + ?Sized>(&self, volume: &mut Vol) -> EditBounds {
+        for (pos, _, after) in self.changes() {
+            volume.set(pos, after);
+        }
+        self.bounds
+    }
+}
+
+/// Undo and redo stacks of [`VoxelEdit`]s.
+///
+/// The oldest edits are dropped once the history uses more than its memory limit,
+/// but the most recent edit is always kept.
+///
+/// Insert an `EditHistory<AssetVoxel>` on a [`VoxelSceneChunk`](crate::VoxelSceneChunk) entity
+/// and pass it [`VoxelSceneChunk::voxels_mut`](crate::VoxelSceneChunk::voxels_mut),
+/// so the chunk, its lights and the neighbouring chunks it borders are remeshed after every edit, undo and redo.
+#[derive(Component)]
+pub struct EditHistory<V: Send + Sync + 'static> {
+    undo: VecDeque<VoxelEdit<V>>,
+    redo: Vec<VoxelEdit<V>>,
+    memory_limit: usize,
+    memory_usage: usize,
+}
+
+impl<V: Send + Sync + 'static> Default for EditHistory<V> {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MEMORY_LIMIT)
+    }
+}
+
+impl<V: Send + Sync + 'static> EditHistory<V> {
+    /// The default memory limit, in bytes.
+    pub const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+    /// Create an empty history that uses at most `memory_limit` bytes.
+    pub fn new(memory_limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            memory_limit,
+            memory_usage: 0,
+        }
+    }
+
+    /// Returns the number of edits that can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Returns the number of edits that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Returns the approximate number of bytes used by the recorded edits.
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Returns the maximum number of bytes used by the recorded edits.
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    /// Remove every recorded edit.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.memory_usage = 0;
+    }
+}
+
+impl<V: Copy + PartialEq + Send + Sync + 'static> EditHistory<V> {
+    /// Set the maximum number of bytes used by the recorded edits, dropping the oldest edits if needed.
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.memory_limit = memory_limit;
+        self.trim();
+    }
+
+    /// Make changes to `volume` as a single undoable edit, returning the region that changed.
+    pub fn edit<Vol>(
+        &mut self,
+        volume: &mut Vol,
+        f: impl FnOnce(&mut EditRecorder<'_, Vol>),
+    ) -> Option<EditBounds>
+    where
+        Vol: VoxelVolume<Voxel = V>,
+    {
+        let mut recorder = EditRecorder::new(volume);
+        f(&mut recorder);
+
+        let edit = recorder.finish()?;
+        let bounds = edit.bounds();
+        self.push(edit);
+        Some(bounds)
+    }
+
+    /// Record an edit that was already applied, clearing the redo stack.
+    pub fn push(&mut self, edit: VoxelEdit<V>) {
+        for edit in self.redo.drain(..) {
+            self.memory_usage -= edit.memory_usage();
+        }
+
+        self.memory_usage += edit.memory_usage();
+        self.undo.push_back(edit);
+        self.trim();
+    }
+
+    /// Revert the most recent edit, returning the region that changed.
+    pub fn undo<Vol>(&mut self, volume: &mut Vol) -> Option<EditBounds>
+    where
+        Vol: VoxelVolume<Voxel = V> + ?Sized,
+    {
+        let edit = self.undo.pop_back()?;
+        let bounds = edit.revert(volume);
+        self.redo.push(edit);
+        Some(bounds)
+    }
+
+    /// Reapply the most recently undone edit, returning the region that changed.
+    pub fn redo<Vol>(&mut self, volume: &mut Vol) -> Option<EditBounds>
+    where
+        Vol: VoxelVolume<Voxel = V> + ?Sized,
+    {
+        let edit = self.redo.pop()?;
+        let bounds = edit.apply(volume);
+        self.undo.push_back(edit);
+        Some(bounds)
+    }
+
+    fn trim(&mut self) {
+        while self.memory_usage > self.memory_limit && self.undo.len() > 1 {
+            if let Some(edit) = self.undo.pop_front() {
+                self.memory_usage -= edit.memory_usage();
+            }
+        }
+    }
+}
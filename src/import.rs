@@ -0,0 +1,404 @@
+use crate::{AssetModel, AssetVoxel, QuantizedPalette, VoxelScene};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedFolder, RenderAssetUsages, io::Reader},
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    prelude::*,
+};
+use ndshape::{RuntimeShape, Shape};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Registers the [`HeightmapLoader`] and [`SliceSheetLoader`].
+pub struct ImportPlugin;
+
+impl Plugin for ImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset_loader::<HeightmapLoader>()
+            .init_asset_loader::<SliceSheetLoader>();
+    }
+}
+
+/// Settings for building a [`VoxelScene`] from a heightmap.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HeightmapSettings {
+    /// The height in voxels of the brightest pixel.
+    pub max_height: u32,
+    /// Colour voxels by their height, interpolating between `(height from 0 to 1, colour)` stops sorted by height.
+    pub gradient: Vec<(f32, Color)>,
+    /// The asset path of an image that colours each column instead of `gradient`, stretched over the heightmap.
+    ///
+    /// Only used by [`HeightmapLoader`].
+    pub color_map: Option<String>,
+    /// Split the terrain into chunks of at most `chunk_size`³ voxels, like [`SceneLoaderSettings::chunk_size`](crate::SceneLoaderSettings::chunk_size).
+    pub chunk_size: Option<u32>,
+    /// Build compact meshes with [`Chunk::build_packed`](crate::Chunk::build_packed).
+    pub packed_vertices: bool,
+}
+
+impl Default for HeightmapSettings {
+    fn default() -> Self {
+        Self {
+            max_height: 32,
+            gradient: vec![
+                (0., Color::srgb(0.2, 0.4, 0.8)),
+                (0.3, Color::srgb(0.85, 0.8, 0.55)),
+                (0.4, Color::srgb(0.3, 0.6, 0.2)),
+                (0.75, Color::srgb(0.45, 0.4, 0.35)),
+                (1., Color::WHITE),
+            ],
+            color_map: None,
+            chunk_size: Some(32),
+            packed_vertices: false,
+        }
+    }
+}
+
+/// Which channel of a slice decides whether a pixel is a voxel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SliceChannel {
+    /// Use the alpha channel, for slices with transparent backgrounds.
+    #[default]
+    Alpha,
+    /// Use the luminance, for greyscale scans.
+    Luminance,
+}
+
+/// Settings for building a [`VoxelScene`] from a stack of image slices.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SliceSettings {
+    pub channel: SliceChannel,
+    /// Pixels whose channel is above this value, from `0.` to `1.`, become voxels.
+    pub threshold: f32,
+    /// The number of slices per row of a sprite sheet, used by [`SliceSheetLoader`].
+    pub columns: u32,
+    /// The number of rows of slices in a sprite sheet, used by [`SliceSheetLoader`].
+    pub rows: u32,
+    /// The maximum number of palette colours.
+    pub max_colors: usize,
+    /// Split the volume into chunks of at most `chunk_size`³ voxels, like [`SceneLoaderSettings::chunk_size`](crate::SceneLoaderSettings::chunk_size).
+    pub chunk_size: Option<u32>,
+    /// Build compact meshes with [`Chunk::build_packed`](crate::Chunk::build_packed).
+    pub packed_vertices: bool,
+}
+
+impl Default for SliceSettings {
+    fn default() -> Self {
+        Self {
+            channel: SliceChannel::Alpha,
+            threshold: 0.5,
+            columns: 1,
+            rows: 1,
+            max_colors: QuantizedPalette::MAX_COLORS,
+            chunk_size: Some(32),
+            packed_vertices: false,
+        }
+    }
+}
+
+impl VoxelScene {
+    /// Build a terrain of voxel columns from a greyscale heightmap.
+    ///
+    /// Each pixel is a column along Y whose height is its red (or grey) channel times [`HeightmapSettings::max_height`],
+    /// with image X and Y mapped to voxel X and Z.
+    /// Columns are coloured from `color_map` if set, otherwise from [`HeightmapSettings::gradient`].
+    pub fn from_heightmap(
+        heightmap: &Image,
+        color_map: Option<&Image>,
+        settings: &HeightmapSettings,
+    ) -> Self {
+        let size = heightmap.size();
+        let max_height = settings.max_height.max(1);
+        let heights: Vec<u32> = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let value = channels(heightmap, x, y).x.clamp(0., 1.);
+                (value * max_height as f32).round() as u32
+            })
+            .collect();
+
+        // Colour each height level, or each column from the colour map.
+        let (palette, indices): (QuantizedPalette, Vec<u8>) = match color_map {
+            Some(color_map) => {
+                let map_size = color_map.size().as_vec2();
+                let colors: Vec<Vec3> = (0..size.y)
+                    .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+                    .map(|(x, y)| {
+                        let pixel = (Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / size.as_vec2()
+                            * map_size)
+                            .as_uvec2();
+                        color_map
+                            .get_color_at(pixel.x, pixel.y)
+                            .map_or(Vec3::ONE, |color| color.to_linear().to_vec3())
+                    })
+                    .collect();
+                let palette = QuantizedPalette::from_samples(&colors, QuantizedPalette::MAX_COLORS);
+                let mut cache = HashMap::new();
+                let indices = colors
+                    .iter()
+                    .map(|color| {
+                        *cache
+                            .entry(color.to_array().map(f32::to_bits))
+                            .or_insert_with(|| palette.nearest(*color))
+                    })
+                    .collect();
+                (palette, indices)
+            }
+            None => {
+                let colors: Vec<Vec3> = (0..max_height)
+                    .map(|level| {
+                        sample_gradient(
+                            &settings.gradient,
+                            (level as f32 + 0.5) / max_height as f32,
+                        )
+                    })
+                    .collect();
+                let palette = QuantizedPalette::from_samples(&colors, QuantizedPalette::MAX_COLORS);
+                let indices = colors.iter().map(|color| palette.nearest(*color)).collect();
+                (palette, indices)
+            }
+        };
+
+        let shape = RuntimeShape::<u32, 3>::new([size.x + 2, max_height + 2, size.y + 2]);
+        let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+        for z in 0..size.y {
+            for x in 0..size.x {
+                let column = (z * size.x + x) as usize;
+                for y in 0..heights[column] {
+                    let idx = match color_map {
+                        Some(_) => indices[column],
+                        None => indices[y as usize],
+                    };
+                    voxels[shape.linearize([x + 1, y + 1, z + 1]) as usize] =
+                        AssetVoxel { idx: idx + 1 };
+                }
+            }
+        }
+
+        let model =
+            AssetModel::from_voxels(voxels, shape, settings.chunk_size, Some("heightmap".into()));
+        VoxelScene::from_models([model], palette.material(), settings.packed_vertices)
+    }
+
+    /// Build a volume from a stack of equally sized 2D slices, the first slice at the bottom.
+    ///
+    /// Image X and Y are mapped to voxel X and Z, and each slice is one voxel tall.
+    /// Pixels whose [`SliceSettings::channel`] is above [`SliceSettings::threshold`] become voxels coloured like the pixel.
+    ///
+    /// See [`VoxelScene::from_slice_folder`] to import a folder of slice images.
+    pub fn from_slices(slices: &[&Image], settings: &SliceSettings) -> Self {
+        let size = slices
+            .iter()
+            .map(|slice| slice.size())
+            .reduce(UVec2::min)
+            .unwrap_or_default();
+        let regions: Vec<_> = slices
+            .iter()
+            .map(|slice| (*slice, URect::from_corners(UVec2::ZERO, size)))
+            .collect();
+        slices_scene(&regions, settings)
+    }
+
+    /// Build a volume like [`VoxelScene::from_slices`] from the images of a folder loaded with [`AssetServer::load_folder`].
+    ///
+    /// Slices are stacked in the order of their asset paths, so number them with leading zeros, e.g. `slice_007.png`.
+    /// Assets in the folder that aren't images are ignored.
+    /// Returns `None` if any image hasn't finished loading.
+    pub fn from_slice_folder(
+        folder: &LoadedFolder,
+        images: &Assets<Image>,
+        settings: &SliceSettings,
+    ) -> Option<Self> {
+        let mut handles: Vec<_> = folder
+            .handles
+            .iter()
+            .filter_map(|handle| handle.clone().try_typed::<Image>().ok())
+            .collect();
+        handles.sort_by_cached_key(|handle| handle.path().map(ToString::to_string));
+
+        let slices = handles
+            .iter()
+            .map(|handle| images.get(handle))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::from_slices(&slices, settings))
+    }
+}
+
+/// Build a volume from rectangles of images, one per slice.
+fn slices_scene(slices: &[(&Image, URect)], settings: &SliceSettings) -> VoxelScene {
+    let size = slices.first().map_or(UVec2::ZERO, |(_, rect)| rect.size());
+    let height = slices.len() as u32;
+
+    let mut colors = HashMap::new();
+    for (y, (image, rect)) in slices.iter().enumerate() {
+        for z in 0..size.y {
+            for x in 0..size.x {
+                let pixel = rect.min + UVec2::new(x, z);
+                let value = channels(image, pixel.x, pixel.y);
+                let value = match settings.channel {
+                    SliceChannel::Alpha => value.w,
+                    SliceChannel::Luminance => {
+                        value.truncate().dot(Vec3::new(0.2126, 0.7152, 0.0722))
+                    }
+                };
+                if value > settings.threshold
+                    && let Ok(color) = image.get_color_at(pixel.x, pixel.y)
+                {
+                    colors.insert(UVec3::new(x, y as u32, z), color.to_linear().to_vec3());
+                }
+            }
+        }
+    }
+
+    let samples: Vec<Vec3> = colors.values().copied().collect();
+    let palette = QuantizedPalette::from_samples(&samples, settings.max_colors);
+    let mut cache = HashMap::new();
+
+    let shape = RuntimeShape::<u32, 3>::new([size.x + 2, height + 2, size.y + 2]);
+    let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+    for (pos, color) in &colors {
+        let idx = *cache
+            .entry(color.to_array().map(f32::to_bits))
+            .or_insert_with(|| palette.nearest(*color));
+        voxels[shape.linearize((*pos + UVec3::ONE).to_array()) as usize] =
+            AssetVoxel { idx: idx + 1 };
+    }
+
+    let model = AssetModel::from_voxels(voxels, shape, settings.chunk_size, Some("slices".into()));
+    VoxelScene::from_models([model], palette.material(), settings.packed_vertices)
+}
+
+/// Returns the stored channel values of a pixel from `0.` to `1.`, without colour space conversion.
+fn channels(image: &Image, x: u32, y: u32) -> Vec4 {
+    let Ok(color) = image.get_color_at(x, y) else {
+        return Vec4::ZERO;
+    };
+    if image.texture_descriptor.format.is_srgb() {
+        color.to_srgba().to_vec4()
+    } else {
+        color.to_linear().to_vec4()
+    }
+}
+
+fn sample_gradient(gradient: &[(f32, Color)], t: f32) -> Vec3 {
+    let Some(next) = gradient.iter().position(|(stop, _)| *stop >= t) else {
+        return gradient
+            .last()
+            .map_or(Vec3::ONE, |(_, color)| color.to_linear().to_vec3());
+    };
+    if next == 0 {
+        return gradient[0].1.to_linear().to_vec3();
+    }
+
+    let (start, start_color) = gradient[next - 1];
+    let (end, end_color) = gradient[next];
+    let t = ((t - start) / (end - start).max(f32::EPSILON)).clamp(0., 1.);
+    start_color.mix(&end_color, t).to_linear().to_vec3()
+}
+
+async fn read_image(
+    reader: &mut dyn Reader,
+    load_context: &LoadContext<'_>,
+) -> Result<Image, Box<dyn std::error::Error + Send + Sync>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    let extension = load_context
+        .path()
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("png");
+    decode_image(&bytes, extension)
+}
+
+fn decode_image(
+    bytes: &[u8],
+    extension: &str,
+) -> Result<Image, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(Image::from_buffer(
+        bytes,
+        ImageType::Extension(extension),
+        CompressedImageFormats::NONE,
+        false,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )?)
+}
+
+/// Loads greyscale `.heightmap.png` images as a [`VoxelScene`] with [`VoxelScene::from_heightmap`].
+#[derive(Default)]
+pub struct HeightmapLoader;
+
+impl AssetLoader for HeightmapLoader {
+    type Asset = VoxelScene;
+
+    type Settings = HeightmapSettings;
+
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let heightmap = read_image(reader, load_context).await?;
+        let color_map = match &settings.color_map {
+            Some(path) => {
+                let extension = path.rsplit('.').next().unwrap_or("png").to_string();
+                let bytes = load_context.read_asset_bytes(path.clone()).await?;
+                Some(decode_image(&bytes, &extension)?)
+            }
+            None => None,
+        };
+
+        let mut scene = VoxelScene::from_heightmap(&heightmap, color_map.as_ref(), settings);
+        scene.add_labeled_assets(load_context);
+        Ok(scene)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["heightmap.png"]
+    }
+}
+
+/// Loads `.slices.png` sprite sheets of [`SliceSettings::columns`] by [`SliceSettings::rows`] slices as a [`VoxelScene`].
+///
+/// Slices are read left to right and top to bottom, like [`VoxelScene::from_slices`].
+#[derive(Default)]
+pub struct SliceSheetLoader;
+
+impl AssetLoader for SliceSheetLoader {
+    type Asset = VoxelScene;
+
+    type Settings = SliceSettings;
+
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let sheet = read_image(reader, load_context).await?;
+
+        let grid = UVec2::new(settings.columns.max(1), settings.rows.max(1));
+        let size = sheet.size() / grid;
+        let regions: Vec<_> = (0..grid.y)
+            .flat_map(|row| (0..grid.x).map(move |column| UVec2::new(column, row)))
+            .map(|cell| {
+                (
+                    &sheet,
+                    URect::from_corners(cell * size, (cell + UVec2::ONE) * size),
+                )
+            })
+            .collect();
+
+        let mut scene = slices_scene(&regions, settings);
+        scene.add_labeled_assets(load_context);
+        Ok(scene)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["slices.png"]
+    }
+}
@@ -0,0 +1,788 @@
+use crate::{
+    VoxelMaterial,
+    voxel_material::{
+        VOXEL_INSTANCES_PREPASS_SHADER_HANDLE, VOXEL_MATERIAL_SHADER_HANDLE, voxel_vertex_layout,
+    },
+};
+use bevy::{
+    asset::AssetEvent,
+    camera::visibility::NoFrustumCulling,
+    core_pipeline::{
+        core_3d::{CORE_3D_DEPTH_FORMAT, Opaque3d, Opaque3dBatchSetKey, Opaque3dBinKey},
+        prepass::{
+            Opaque3dPrepass, OpaqueNoLightmap3dBatchSetKey, OpaqueNoLightmap3dBinKey,
+            prepass_target_descriptors,
+        },
+    },
+    ecs::system::{
+        SystemChangeTick, SystemParamItem,
+        lifetimeless::{Read, SRes},
+    },
+    mesh::{MeshVertexBufferLayoutRef, VertexBufferLayout},
+    pbr::{
+        LightEntity, LightKeyCache, MaterialPipeline, MaterialPipelineKey, MeshPipelineKey,
+        PrepassPipeline, RenderMeshInstanceFlags, RenderMeshInstances, SetMeshViewBindGroup,
+        SetMeshViewBindingArrayBindGroup, SetPrepassViewBindGroup, SetPrepassViewEmptyBindGroup,
+        Shadow, ShadowBatchSetKey, ShadowBinKey, ViewKeyCache, ViewKeyPrepassCache,
+        init_prepass_pipeline,
+    },
+    prelude::*,
+    render::{
+        Extract, ExtractSchedule, Render, RenderApp, RenderStartup, RenderSystems,
+        mesh::{RenderMesh, RenderMeshBufferInfo, allocator::MeshAllocator},
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, BinnedRenderPhaseType, DrawFunctions, InputUniformIndex, PhaseItem,
+            RenderCommand, RenderCommandResult, SetItemPipeline, TrackedRenderPass,
+            ViewBinnedRenderPhases,
+        },
+        render_resource::{binding_types::storage_buffer_read_only_sized, *},
+        renderer::{RenderDevice, RenderQueue},
+        sync_world::{MainEntity, RenderEntity},
+        view::ExtractedView,
+    },
+    shader::ShaderDefVal,
+};
+use bytemuck::{Pod, Zeroable};
+use std::collections::HashSet;
+
+pub struct VoxelInstancingPlugin;
+
+impl Plugin for VoxelInstancingPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .add_render_command::<Opaque3d, DrawVoxelInstances>()
+            .add_render_command::<Opaque3dPrepass, DrawVoxelInstancesPrepass>()
+            .add_render_command::<Shadow, DrawVoxelInstancesPrepass>()
+            .init_resource::<SpecializedMeshPipelines<VoxelInstancesPipeline>>()
+            .add_systems(
+                RenderStartup,
+                init_voxel_instances_pipeline.after(init_prepass_pipeline),
+            )
+            .add_systems(ExtractSchedule, extract_voxel_instances)
+            .add_systems(
+                Render,
+                (
+                    queue_voxel_instances.in_set(RenderSystems::QueueMeshes),
+                    prepare_voxel_instances.in_set(RenderSystems::PrepareBindGroups),
+                ),
+            );
+    }
+}
+
+/// A single instance of a [`VoxelInstances`] mesh.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VoxelInstance {
+    /// The transform of this instance relative to the [`VoxelInstances`] entity.
+    pub transform: Transform,
+    /// The index of this instance's palette in [`VoxelInstances::palettes`].
+    pub palette: u32,
+}
+
+/// Draw the entity's [`Mesh3d`] once per instance in a single instanced draw call.
+///
+/// Instances are coloured like a [`VoxelMaterial`] mesh using one of `palettes` each,
+/// so the entity shouldn't also have a [`MeshMaterial3d`].
+/// They are drawn in the opaque pass, the prepass and shadow maps, but don't spawn point lights for emissive voxels.
+/// Instances are assumed to be uniformly scaled, and have no motion vectors of their own.
+///
+/// The instance and palette buffers are only uploaded again when this component, the entity's transform,
+/// or one of the palettes changes.
+#[derive(Clone, Debug, Default, Component)]
+#[require(NoFrustumCulling)]
+pub struct VoxelInstances {
+    pub palettes: Vec<Handle<VoxelMaterial>>,
+    pub instances: Vec<VoxelInstance>,
+}
+
+impl VoxelInstances {
+    /// Create an empty set of instances using `palette`.
+    pub fn new(palette: Handle<VoxelMaterial>) -> Self {
+        Self {
+            palettes: vec![palette],
+            instances: Vec::new(),
+        }
+    }
+
+    /// Add a palette, returning its index for [`VoxelInstance::palette`].
+    pub fn add_palette(&mut self, palette: Handle<VoxelMaterial>) -> u32 {
+        self.palettes.push(palette);
+        self.palettes.len() as u32 - 1
+    }
+
+    /// Add an instance at `transform` using the palette at index `palette`.
+    pub fn push(&mut self, transform: Transform, palette: u32) {
+        self.instances.push(VoxelInstance { transform, palette });
+    }
+
+    /// Returns the instance buffer contents for an entity at `global_transform`.
+    ///
+    /// Palette indices past the last palette are clamped to it.
+    pub fn instance_data(&self, global_transform: &GlobalTransform) -> Vec<VoxelInstanceData> {
+        let max_palette = (self.palettes.len() as u32).saturating_sub(1);
+        let world_from_entity = global_transform.affine();
+
+        self.instances
+            .iter()
+            .map(|instance| {
+                let world_from_local =
+                    Mat4::from(world_from_entity * instance.transform.compute_affine()).transpose();
+                VoxelInstanceData {
+                    rows: [
+                        world_from_local.x_axis.to_array(),
+                        world_from_local.y_axis.to_array(),
+                        world_from_local.z_axis.to_array(),
+                    ],
+                    palette: instance.palette.min(max_palette),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the palette buffer contents, with 256 entries for each palette,
+    /// or `None` if there are no palettes or one of them isn't loaded.
+    pub fn palette_data(
+        &self,
+        materials: &Assets<VoxelMaterial>,
+    ) -> Option<Vec<VoxelPaletteEntry>> {
+        let palettes = self
+            .palettes
+            .iter()
+            .map(|palette| materials.get(palette))
+            .collect::<Option<Vec<_>>>()
+            .filter(|palettes| !palettes.is_empty())?;
+
+        Some(
+            palettes
+                .into_iter()
+                .flat_map(|material| material.colors.iter().zip(&material.emissions))
+                .map(|(color, emissive)| VoxelPaletteEntry {
+                    color: color.extend(1.),
+                    emissive: emissive.extend(0.),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The per-instance vertex data of a [`VoxelInstances`] entity.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct VoxelInstanceData {
+    /// Rows of the world transform of the instance.
+    pub rows: [[f32; 4]; 3],
+    pub palette: u32,
+}
+
+/// A palette entry of a [`VoxelInstances`] entity, read by `voxel_material.wgsl`.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct VoxelPaletteEntry {
+    pub color: Vec4,
+    pub emissive: Vec4,
+}
+
+#[derive(Component)]
+struct ExtractedVoxelInstances(Vec<VoxelInstanceData>);
+
+#[derive(Component)]
+struct ExtractedVoxelPalettes(Vec<VoxelPaletteEntry>);
+
+/// Extract the instances and palettes of entities whose data changed, leaving the rest in place.
+#[allow(clippy::type_complexity)]
+fn extract_voxel_instances(
+    mut commands: Commands,
+    query: Extract<
+        Query<(
+            RenderEntity,
+            Ref<VoxelInstances>,
+            Ref<GlobalTransform>,
+            &ViewVisibility,
+        )>,
+    >,
+    materials: Extract<Res<Assets<VoxelMaterial>>>,
+    mut material_events: Extract<MessageReader<AssetEvent<VoxelMaterial>>>,
+    mut extracted: Local<HashSet<Entity>>,
+) {
+    let previous = std::mem::take(&mut *extracted);
+    let changed_materials: HashSet<_> = material_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (render_entity, voxel_instances, global_transform, visibility) in &query {
+        if !visibility.get() || voxel_instances.instances.is_empty() {
+            continue;
+        }
+
+        let is_new = !previous.contains(&render_entity) || voxel_instances.is_changed();
+        let palettes_changed = voxel_instances
+            .palettes
+            .iter()
+            .any(|palette| changed_materials.contains(&palette.id()));
+
+        if is_new || palettes_changed {
+            // Wait until every palette is loaded so palette indices stay valid.
+            let Some(palettes) = voxel_instances.palette_data(&materials) else {
+                continue;
+            };
+            commands
+                .entity(render_entity)
+                .insert(ExtractedVoxelPalettes(palettes));
+        }
+
+        if is_new || global_transform.is_changed() {
+            commands
+                .entity(render_entity)
+                .insert(ExtractedVoxelInstances(
+                    voxel_instances.instance_data(&global_transform),
+                ));
+        }
+
+        extracted.insert(render_entity);
+    }
+
+    for entity in previous.difference(&extracted) {
+        if let Ok(mut entity_commands) = commands.get_entity(*entity) {
+            entity_commands.remove::<(
+                ExtractedVoxelInstances,
+                ExtractedVoxelPalettes,
+                VoxelInstanceBuffers,
+            )>();
+        }
+    }
+}
+
+#[derive(Resource)]
+struct VoxelInstancesPipeline {
+    prepass_pipeline: PrepassPipeline,
+    palette_layout: BindGroupLayout,
+}
+
+fn init_voxel_instances_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    prepass_pipeline: Res<PrepassPipeline>,
+) {
+    let palette_layout = render_device.create_bind_group_layout(
+        "voxel_instances_palette_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::VERTEX,
+            storage_buffer_read_only_sized(false, None),
+        ),
+    );
+
+    commands.insert_resource(VoxelInstancesPipeline {
+        prepass_pipeline: prepass_pipeline.clone(),
+        palette_layout,
+    });
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct VoxelInstancesPipelineKey {
+    mesh_key: MeshPipelineKey,
+    /// Whether this is a depth-only pipeline for the prepass or shadow maps.
+    prepass: bool,
+}
+
+impl VoxelInstancesPipeline {
+    fn material_pipeline(&self) -> &MaterialPipeline {
+        &self.prepass_pipeline.material_pipeline
+    }
+
+    /// Specialize the prepass pipeline, which only needs positions and normals from the instance transforms.
+    fn specialize_prepass(
+        &self,
+        key: MeshPipelineKey,
+        vertex_buffers: Vec<VertexBufferLayout>,
+        mut shader_defs: Vec<ShaderDefVal>,
+    ) -> RenderPipelineDescriptor {
+        let normal_prepass = key.contains(MeshPipelineKey::NORMAL_PREPASS);
+        let motion_vector_prepass = key.contains(MeshPipelineKey::MOTION_VECTOR_PREPASS);
+        let emulate_unclipped_depth = key.contains(MeshPipelineKey::UNCLIPPED_DEPTH_ORTHO)
+            && !self.prepass_pipeline.depth_clip_control_supported;
+
+        shader_defs.push("PREPASS_PIPELINE".into());
+        if key.contains(MeshPipelineKey::DEPTH_PREPASS) {
+            shader_defs.push("DEPTH_PREPASS".into());
+        }
+        if normal_prepass {
+            shader_defs.push("NORMAL_PREPASS".into());
+            shader_defs.push("NORMAL_PREPASS_OR_DEFERRED_PREPASS".into());
+        }
+        if motion_vector_prepass {
+            shader_defs.push("MOTION_VECTOR_PREPASS".into());
+            shader_defs.push("MOTION_VECTOR_PREPASS_OR_DEFERRED_PREPASS".into());
+        }
+        if emulate_unclipped_depth {
+            shader_defs.push("UNCLIPPED_DEPTH_ORTHO_EMULATION".into());
+        }
+        if normal_prepass || motion_vector_prepass || emulate_unclipped_depth {
+            shader_defs.push("PREPASS_FRAGMENT".into());
+        }
+
+        let mut targets = prepass_target_descriptors(normal_prepass, motion_vector_prepass, false);
+        if targets.iter().all(Option::is_none) {
+            targets.clear();
+        }
+
+        // Bevy's default prepass fragment shader writes the normals, motion vectors and emulated depth.
+        let fragment = (!targets.is_empty() || emulate_unclipped_depth).then(|| FragmentState {
+            shader: self.prepass_pipeline.default_prepass_shader.clone(),
+            shader_defs: shader_defs.clone(),
+            targets,
+            ..default()
+        });
+
+        let view_layout = if motion_vector_prepass {
+            &self.prepass_pipeline.view_layout_motion_vectors
+        } else {
+            &self.prepass_pipeline.view_layout_no_motion_vectors
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("voxel_instances_prepass_pipeline".into()),
+            layout: vec![
+                view_layout.clone(),
+                self.prepass_pipeline.empty_layout.clone(),
+            ],
+            vertex: VertexState {
+                shader: VOXEL_INSTANCES_PREPASS_SHADER_HANDLE,
+                shader_defs,
+                buffers: vertex_buffers,
+                ..default()
+            },
+            fragment,
+            primitive: PrimitiveState {
+                topology: key.primitive_topology(),
+                unclipped_depth: key.contains(MeshPipelineKey::UNCLIPPED_DEPTH_ORTHO)
+                    && self.prepass_pipeline.depth_clip_control_supported,
+                ..default()
+            },
+            depth_stencil: Some(DepthStencilState {
+                format: CORE_3D_DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: default(),
+                bias: default(),
+            }),
+            multisample: MultisampleState {
+                count: key.msaa_samples(),
+                ..default()
+            },
+            ..default()
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for VoxelInstancesPipeline {
+    type Key = VoxelInstancesPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut shader_defs = vec![
+            "VOXEL_INSTANCED".into(),
+            ShaderDefVal::UInt("MATERIAL_BIND_GROUP".into(), 3),
+        ];
+        let vertex_layout = voxel_vertex_layout(layout, &mut shader_defs)?;
+
+        // Shader locations 0-2 are taken by the voxel vertex attributes.
+        let instance_layout = VertexBufferLayout::from_vertex_formats(
+            VertexStepMode::Instance,
+            [
+                VertexFormat::Float32x4,
+                VertexFormat::Float32x4,
+                VertexFormat::Float32x4,
+                VertexFormat::Uint32,
+            ],
+        )
+        .offset_locations_by(3);
+
+        if key.prepass {
+            return Ok(self.specialize_prepass(
+                key.mesh_key,
+                vec![vertex_layout, instance_layout],
+                shader_defs,
+            ));
+        }
+
+        let mut descriptor = self
+            .material_pipeline()
+            .mesh_pipeline
+            .specialize(key.mesh_key, layout)?;
+        VoxelMaterial::specialize(
+            self.material_pipeline(),
+            &mut descriptor,
+            layout,
+            MaterialPipelineKey {
+                mesh_key: key.mesh_key,
+                bind_group_data: (),
+            },
+        )?;
+
+        descriptor.vertex.shader = VOXEL_MATERIAL_SHADER_HANDLE;
+        descriptor.vertex.shader_defs.extend(shader_defs.clone());
+        descriptor.vertex.buffers.push(instance_layout);
+
+        // Instances take their transforms from the instance buffer instead of the mesh bind group.
+        descriptor.layout[2] = self.prepass_pipeline.empty_layout.clone();
+        descriptor.layout.push(self.palette_layout.clone());
+
+        let fragment = descriptor.fragment.as_mut().unwrap();
+        fragment.shader = VOXEL_MATERIAL_SHADER_HANDLE;
+        fragment.shader_defs.extend(shader_defs);
+
+        Ok(descriptor)
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn queue_voxel_instances(
+    opaque_draw_functions: Res<DrawFunctions<Opaque3d>>,
+    prepass_draw_functions: Res<DrawFunctions<Opaque3dPrepass>>,
+    shadow_draw_functions: Res<DrawFunctions<Shadow>>,
+    pipeline: Res<VoxelInstancesPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<VoxelInstancesPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    mesh_allocator: Res<MeshAllocator>,
+    query: Query<(Entity, &MainEntity), With<ExtractedVoxelInstances>>,
+    (view_key_cache, prepass_key_cache, light_key_cache): (
+        Res<ViewKeyCache>,
+        Res<ViewKeyPrepassCache>,
+        Res<LightKeyCache>,
+    ),
+    (mut opaque_phases, mut prepass_phases, mut shadow_phases): (
+        ResMut<ViewBinnedRenderPhases<Opaque3d>>,
+        ResMut<ViewBinnedRenderPhases<Opaque3dPrepass>>,
+        ResMut<ViewBinnedRenderPhases<Shadow>>,
+    ),
+    views: Query<&ExtractedView, Without<LightEntity>>,
+    light_views: Query<&ExtractedView, With<LightEntity>>,
+    ticks: SystemChangeTick,
+) {
+    let opaque_draw_function = opaque_draw_functions.read().id::<DrawVoxelInstances>();
+    let prepass_draw_function = prepass_draw_functions
+        .read()
+        .id::<DrawVoxelInstancesPrepass>();
+    let shadow_draw_function = shadow_draw_functions
+        .read()
+        .id::<DrawVoxelInstancesPrepass>();
+    let change_tick = ticks.this_run();
+
+    for (entity, main_entity) in &query {
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*main_entity) else {
+            continue;
+        };
+        let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+            continue;
+        };
+        let (vertex_slab, index_slab) = mesh_allocator.mesh_slabs(&mesh_instance.mesh_asset_id);
+        let vertex_slab = vertex_slab.unwrap_or_default();
+        let asset_id = mesh_instance.mesh_asset_id.untyped();
+        let mesh_key = MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+
+        let mut specialize = |mesh_key, prepass| {
+            let key = VoxelInstancesPipelineKey { mesh_key, prepass };
+            pipelines
+                .specialize(&pipeline_cache, &pipeline, key, &mesh.layout)
+                .ok()
+        };
+
+        for view in &views {
+            if let Some(opaque_phase) = opaque_phases.get_mut(&view.retained_view_entity)
+                && let Some(view_key) = view_key_cache.get(&view.retained_view_entity)
+                && let Some(pipeline) = specialize(*view_key | mesh_key, false)
+            {
+                opaque_phase.add(
+                    Opaque3dBatchSetKey {
+                        pipeline,
+                        draw_function: opaque_draw_function,
+                        material_bind_group_index: None,
+                        vertex_slab,
+                        index_slab,
+                        lightmap_slab: None,
+                    },
+                    Opaque3dBinKey { asset_id },
+                    (entity, *main_entity),
+                    InputUniformIndex::default(),
+                    BinnedRenderPhaseType::NonMesh,
+                    change_tick,
+                );
+            }
+
+            if let Some(prepass_phase) = prepass_phases.get_mut(&view.retained_view_entity)
+                && let Some(view_key) = prepass_key_cache.get(&view.retained_view_entity)
+                && let Some(pipeline) = specialize(*view_key | mesh_key, true)
+            {
+                prepass_phase.add(
+                    OpaqueNoLightmap3dBatchSetKey {
+                        pipeline,
+                        draw_function: prepass_draw_function,
+                        material_bind_group_index: None,
+                        vertex_slab,
+                        index_slab,
+                    },
+                    OpaqueNoLightmap3dBinKey { asset_id },
+                    (entity, *main_entity),
+                    InputUniformIndex::default(),
+                    BinnedRenderPhaseType::NonMesh,
+                    change_tick,
+                );
+            }
+        }
+
+        if !mesh_instance
+            .flags
+            .contains(RenderMeshInstanceFlags::SHADOW_CASTER)
+        {
+            continue;
+        }
+
+        for view in &light_views {
+            if let Some(shadow_phase) = shadow_phases.get_mut(&view.retained_view_entity)
+                && let Some(light_key) = light_key_cache.get(&view.retained_view_entity)
+                && let Some(pipeline) = specialize(*light_key | mesh_key, true)
+            {
+                shadow_phase.add(
+                    ShadowBatchSetKey {
+                        pipeline,
+                        draw_function: shadow_draw_function,
+                        material_bind_group_index: None,
+                        vertex_slab,
+                        index_slab,
+                    },
+                    ShadowBinKey { asset_id },
+                    (entity, *main_entity),
+                    InputUniformIndex::default(),
+                    BinnedRenderPhaseType::NonMesh,
+                    change_tick,
+                );
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct VoxelInstanceBuffers {
+    instances: Buffer,
+    length: u32,
+    palettes: Buffer,
+    palette_bind_group: BindGroup,
+}
+
+/// Write `contents` into `buffer`, or replace it with a new buffer if the size changed.
+///
+/// Returns `true` if the buffer was replaced.
+fn write_or_replace_buffer(
+    buffer: &mut Buffer,
+    contents: &[u8],
+    label: &str,
+    usage: BufferUsages,
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+) -> bool {
+    if buffer.size() == contents.len() as u64 {
+        render_queue.write_buffer(buffer, 0, contents);
+        return false;
+    }
+
+    *buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some(label),
+        contents,
+        usage,
+    });
+    true
+}
+
+/// Upload the instances and palettes of entities that were extracted again, reusing their buffers where possible.
+#[allow(clippy::type_complexity)]
+fn prepare_voxel_instances(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        Ref<ExtractedVoxelInstances>,
+        Ref<ExtractedVoxelPalettes>,
+        Option<&mut VoxelInstanceBuffers>,
+    )>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline: Res<VoxelInstancesPipeline>,
+) {
+    const INSTANCES_LABEL: &str = "voxel_instances_buffer";
+    const INSTANCES_USAGE: BufferUsages = BufferUsages::VERTEX.union(BufferUsages::COPY_DST);
+    const PALETTES_LABEL: &str = "voxel_instances_palette_buffer";
+    const PALETTES_USAGE: BufferUsages = BufferUsages::STORAGE.union(BufferUsages::COPY_DST);
+
+    let palette_bind_group = |palettes: &Buffer| {
+        render_device.create_bind_group(
+            "voxel_instances_palette_bind_group",
+            &pipeline.palette_layout,
+            &BindGroupEntries::single(palettes.as_entire_binding()),
+        )
+    };
+
+    for (entity, instances, palettes, buffers) in &mut query {
+        let Some(mut buffers) = buffers else {
+            let instance_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some(INSTANCES_LABEL),
+                contents: bytemuck::cast_slice(&instances.0),
+                usage: INSTANCES_USAGE,
+            });
+            let palette_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some(PALETTES_LABEL),
+                contents: bytemuck::cast_slice(&palettes.0),
+                usage: PALETTES_USAGE,
+            });
+
+            commands.entity(entity).insert(VoxelInstanceBuffers {
+                instances: instance_buffer,
+                length: instances.0.len() as u32,
+                palette_bind_group: palette_bind_group(&palette_buffer),
+                palettes: palette_buffer,
+            });
+            continue;
+        };
+
+        if instances.is_changed() {
+            write_or_replace_buffer(
+                &mut buffers.instances,
+                bytemuck::cast_slice(&instances.0),
+                INSTANCES_LABEL,
+                INSTANCES_USAGE,
+                &render_device,
+                &render_queue,
+            );
+            buffers.length = instances.0.len() as u32;
+        }
+
+        if palettes.is_changed()
+            && write_or_replace_buffer(
+                &mut buffers.palettes,
+                bytemuck::cast_slice(&palettes.0),
+                PALETTES_LABEL,
+                PALETTES_USAGE,
+                &render_device,
+                &render_queue,
+            )
+        {
+            buffers.palette_bind_group = palette_bind_group(&buffers.palettes);
+        }
+    }
+}
+
+type DrawVoxelInstances = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshViewBindingArrayBindGroup<1>,
+    SetPrepassViewEmptyBindGroup<2>,
+    SetPaletteBindGroup<3>,
+    DrawMeshInstanced,
+);
+
+type DrawVoxelInstancesPrepass = (
+    SetItemPipeline,
+    SetPrepassViewBindGroup<0>,
+    SetPrepassViewEmptyBindGroup<1>,
+    DrawMeshInstanced,
+);
+
+struct SetPaletteBindGroup<const I: usize>;
+
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetPaletteBindGroup<I> {
+    type Param = ();
+    type ViewQuery = ();
+    type ItemQuery = Read<VoxelInstanceBuffers>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        buffers: Option<&'w VoxelInstanceBuffers>,
+        _param: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(buffers) = buffers else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_bind_group(I, &buffers.palette_bind_group, &[]);
+        RenderCommandResult::Success
+    }
+}
+
+struct DrawMeshInstanced;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawMeshInstanced {
+    type Param = (
+        SRes<RenderAssets<RenderMesh>>,
+        SRes<RenderMeshInstances>,
+        SRes<MeshAllocator>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = Read<VoxelInstanceBuffers>;
+
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        buffers: Option<&'w VoxelInstanceBuffers>,
+        (meshes, render_mesh_instances, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let mesh_allocator = mesh_allocator.into_inner();
+
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(item.main_entity())
+        else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(buffers) = buffers else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(vertex_buffer_slice) =
+            mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id)
+        else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_vertex_buffer(0, vertex_buffer_slice.buffer.slice(..));
+        pass.set_vertex_buffer(1, buffers.instances.slice(..));
+
+        match &gpu_mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed {
+                index_format,
+                count,
+            } => {
+                let Some(index_buffer_slice) =
+                    mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id)
+                else {
+                    return RenderCommandResult::Skip;
+                };
+
+                pass.set_index_buffer(index_buffer_slice.buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(
+                    index_buffer_slice.range.start..(index_buffer_slice.range.start + count),
+                    vertex_buffer_slice.range.start as i32,
+                    0..buffers.length,
+                );
+            }
+            RenderMeshBufferInfo::NonIndexed => {
+                pass.draw(vertex_buffer_slice.range, 0..buffers.length);
+            }
+        }
+
+        RenderCommandResult::Success
+    }
+}
@@ -4,40 +4,153 @@ use bevy::{
     prelude::*,
     render::render_resource::{PrimitiveTopology, VertexFormat},
 };
-use block_mesh::{GreedyQuadsBuffer, MergeVoxel, RIGHT_HANDED_Y_UP_CONFIG, greedy_quads};
+use block_mesh::{
+    GreedyQuadsBuffer, MergeVoxel, OrientedBlockFace, RIGHT_HANDED_Y_UP_CONFIG, UnorientedQuad,
+    greedy_quads,
+};
 use ndshape::Shape;
-use std::marker::PhantomData;
+use std::{fmt, hash::Hash, marker::PhantomData};
 
 pub mod prelude {
-    pub use crate::scene::{VoxelScene, VoxelSceneHandle, VoxelSceneModels};
+    pub use crate::block::{BlockRegistry, BlockTextures};
+    pub use crate::edit::{Brush, VoxelVolume};
+    pub use crate::highlight::VoxelHighlight;
+    pub use crate::instancing::{VoxelInstance, VoxelInstances};
+    pub use crate::palette::VoxelPaletteOverride;
+    pub use crate::palette_animation::PaletteAnimation;
+    pub use crate::scene::{
+        VoxelScene, VoxelSceneHandle, VoxelSceneLoadFailed, VoxelSceneModelRef, VoxelSceneModels,
+        VoxelSceneReady, VoxelSceneReloaded,
+    };
+    pub use crate::textured_voxel_material::TexturedVoxelMaterial;
     pub use crate::voxel_material::VoxelMaterial;
+    pub use crate::voxel_type::{VoxelType, VoxelTypeRegistry, VoxelVisibility};
 }
 
 mod asset;
-pub use self::asset::{AssetChunk, AssetVoxel, VoxAssetLoader, VoxFileAsset, VoxFileAssetPlugin};
+pub use self::asset::{
+    AssetChunk, AssetModel, AssetVoxel, AssetVoxelChunk, VoxAssetLoader, VoxFileAsset,
+    VoxFileAssetPlugin,
+};
+
+mod block;
+pub use self::block::{ATTRIBUTE_TILE_INDEX, BlockRegistry, BlockTextures};
+
+pub mod brickmap;
+pub use self::brickmap::BrickMap;
+
+pub mod edit;
+pub use self::edit::{Brush, EditBounds, VoxelVolume};
+
+pub mod export;
+pub use self::export::ExportMesh;
+
+mod highlight;
+pub use self::highlight::{HighlightPlugin, VoxelHighlight, voxel_outline};
+
+mod history;
+pub use self::history::{EditHistory, EditRecorder, VoxelEdit};
+
+mod import;
+pub use self::import::{
+    HeightmapLoader, HeightmapSettings, ImportPlugin, SliceChannel, SliceSettings, SliceSheetLoader,
+};
+
+mod instancing;
+pub use self::instancing::{
+    VoxelInstance, VoxelInstanceData, VoxelInstances, VoxelInstancingPlugin, VoxelPaletteEntry,
+};
+
+mod packed;
+pub use self::packed::{
+    ATTRIBUTE_PACKED_VOXEL, FACE_NORMALS, PACKED_POSITION_MAX, pack_vertex, packed_aabb,
+    unpack_face, unpack_position,
+};
+
+mod palette;
+pub use self::palette::{PalettePlugin, PaletteVariants, VoxelPaletteOverride};
+
+mod paletted;
+pub use self::paletted::PalettedVoxels;
+
+mod palette_animation;
+pub use self::palette_animation::{
+    AnimatedPalette, ColorCycle, ColorKeyframes, EmissionPulse, PaletteAnimation,
+    PaletteAnimationPlugin,
+};
+
+#[cfg(feature = "picking")]
+mod picking;
+#[cfg(feature = "picking")]
+pub use self::picking::{VoxelHit, VoxelPickingPlugin, cast_ray};
+
+mod quantize;
+pub use self::quantize::QuantizedPalette;
 
 pub mod scene;
-pub use self::scene::{ScenePlugin, VoxelLight, VoxelScene, VoxelSceneModels};
+pub use self::scene::{
+    SceneLoaderSettings, ScenePlugin, VoxelLight, VoxelModel, VoxelScene, VoxelSceneChunk,
+    VoxelSceneLoadFailed, VoxelSceneModel, VoxelSceneModelRef, VoxelSceneModels, VoxelSceneReady,
+    VoxelSceneReloadSettings, VoxelSceneReloaded,
+};
+
+mod smooth;
+pub use self::smooth::{ATTRIBUTE_BLEND_WEIGHT, SdfVoxel, SmoothVoxel};
+
+mod stats;
+pub use self::stats::{MeshStats, ModelStats, SceneStats, ValidationIssue};
+
+mod textured_voxel_material;
+pub use self::textured_voxel_material::{TexturedVoxelMaterial, TexturedVoxelMaterialPlugin};
 
 mod voxel_material;
 pub use self::voxel_material::{VoxelMaterial, VoxelMaterialPlugin};
 
+mod voxel_type;
+pub use self::voxel_type::{VoxelId, VoxelType, VoxelTypeRegistry, VoxelVisibility};
+
+mod voxelize;
+pub use self::voxelize::{VoxelizeMode, VoxelizeSettings, Voxelized, voxelize};
+
 pub struct DefaultPlugins;
 
 impl Plugin for DefaultPlugins {
     fn build(&self, app: &mut App) {
-        app.add_plugins((VoxelMaterialPlugin, VoxFileAssetPlugin, ScenePlugin));
+        app.add_plugins((
+            VoxelMaterialPlugin,
+            TexturedVoxelMaterialPlugin,
+            VoxFileAssetPlugin,
+            ScenePlugin,
+            PalettePlugin,
+            PaletteAnimationPlugin,
+            HighlightPlugin,
+            ImportPlugin,
+            VoxelInstancingPlugin,
+        ));
+
+        #[cfg(feature = "picking")]
+        app.add_plugins(VoxelPickingPlugin);
     }
 }
 
 pub const ATTRIBUTE_COLOR_INDEX: MeshVertexAttribute =
     MeshVertexAttribute::new("ColorIndex", 988940917, VertexFormat::Uint32);
 
+/// Per-quad vertex data supplied by a voxel.
+///
+/// The returned value is written to [`ATTRIBUTE_COLOR_INDEX`] for every vertex of quads made of this voxel.
+/// [`VoxelMaterial`] reads it as a palette index,
+/// but custom shaders can pack wider material indices, flags or light into the remaining bits.
+pub trait VoxelAttributes {
+    fn attributes(&self) -> u32;
+}
+
 /// A chunk of voxels that can be built into a mesh.
 ///
 /// This struct produces a [`Mesh`] with standard attributes so it can be rendered with a [`VoxelMaterial`] or extended with custom shaders.
 ///
-/// [`ATTRIBUTE_COLOR_INDEX`] is inserted into the mesh for each quad, representing the voxel index.
+/// [`ATTRIBUTE_COLOR_INDEX`] is inserted into the mesh for each quad, using the value of [`VoxelAttributes::attributes`].
+#[derive(Clone)]
 pub struct Chunk<V, VS, S> {
     pub voxels: VS,
     pub shape: S,
@@ -46,6 +159,16 @@ pub struct Chunk<V, VS, S> {
     _marker: PhantomData<V>,
 }
 
+impl<V, VS: AsRef<[V]>, S> fmt::Debug for Chunk<V, VS, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chunk")
+            .field("len", &self.voxels.as_ref().len())
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<V, VS, S> Chunk<V, VS, S> {
     pub fn new(voxels: VS, shape: S, min: UVec3, max: UVec3) -> Self {
         Self {
@@ -61,64 +184,130 @@ impl<V, VS, S> Chunk<V, VS, S> {
 impl<V, VS, S> MeshBuilder for Chunk<V, VS, S>
 where
     VS: AsRef<[V]>,
-    V: MergeVoxel + AsRef<u8>,
+    V: MergeVoxel + VoxelAttributes,
     S: Shape<3, Coord = u32>,
 {
     fn build(&self) -> Mesh {
-        let faces = RIGHT_HANDED_Y_UP_CONFIG.faces;
-        let mut quad_buffer = GreedyQuadsBuffer::new(self.voxels.as_ref().len());
-
-        greedy_quads(
-            self.voxels.as_ref(),
-            &self.shape,
-            self.min.into(),
-            self.max.into(),
-            &faces,
-            &mut quad_buffer,
-        );
-
-        let num_indices = quad_buffer.quads.num_quads() * 6;
-        let num_vertices = quad_buffer.quads.num_quads() * 4;
-
-        let mut indices = Vec::with_capacity(num_indices);
-        let mut positions = Vec::with_capacity(num_vertices);
-        let mut normals = Vec::with_capacity(num_vertices);
-        let mut color_indices = Vec::with_capacity(num_vertices);
-
-        for (quads, face) in quad_buffer.quads.groups.into_iter().zip(faces) {
-            for quad in quads {
-                let quad_indices = face.quad_mesh_indices(positions.len() as u32);
-                indices.extend_from_slice(&quad_indices);
-
-                let quad_positions = face.quad_mesh_positions(&quad, 1.);
-                positions.extend_from_slice(&quad_positions);
-
-                let quad_normals = face.quad_mesh_normals();
-                normals.extend_from_slice(&quad_normals);
-
-                let idx = self.shape.linearize(quad.minimum);
-                for _ in 0..4 {
-                    color_indices.push(*self.voxels.as_ref()[idx as usize].as_ref() as u32 - 1);
-                }
-            }
-        }
+        build_mesh(self.voxels.as_ref(), &self.shape, self.min, self.max)
+    }
+}
 
-        Mesh::new(
-            PrimitiveTopology::TriangleList,
-            RenderAssetUsages::default(),
-        )
-        .with_inserted_attribute(
-            Mesh::ATTRIBUTE_POSITION,
-            VertexAttributeValues::Float32x3(positions),
-        )
-        .with_inserted_attribute(
-            Mesh::ATTRIBUTE_NORMAL,
-            VertexAttributeValues::Float32x3(normals),
-        )
-        .with_inserted_attribute(
-            ATTRIBUTE_COLOR_INDEX,
-            VertexAttributeValues::Uint32(color_indices),
-        )
-        .with_inserted_indices(Indices::U32(indices))
+impl<V, S> MeshBuilder for Chunk<V, PalettedVoxels<V>, S>
+where
+    V: MergeVoxel + VoxelAttributes + Copy + Eq + Hash,
+    S: Shape<3, Coord = u32>,
+{
+    fn build(&self) -> Mesh {
+        self.build_with_scratch(&mut Vec::new())
+    }
+}
+
+impl<V, S> Chunk<V, PalettedVoxels<V>, S>
+where
+    V: MergeVoxel + VoxelAttributes + Copy + Eq + Hash,
+    S: Shape<3, Coord = u32>,
+{
+    /// Build the mesh like [`MeshBuilder::build`], decoding the voxels into `scratch` instead of a new allocation.
+    ///
+    /// Reuse the same buffer when meshing many chunks.
+    pub fn build_with_scratch(&self, scratch: &mut Vec<V>) -> Mesh {
+        self.voxels.expand_into(scratch);
+        build_mesh(scratch, &self.shape, self.min, self.max)
     }
 }
+
+fn build_mesh<V, S>(voxels: &[V], shape: &S, min: UVec3, max: UVec3) -> Mesh
+where
+    V: MergeVoxel + VoxelAttributes,
+    S: Shape<3, Coord = u32>,
+{
+    let mut color_indices = Vec::new();
+    let mesh = build_quads(voxels, shape, min, max, |_, quad| {
+        let idx = shape.linearize(quad.minimum);
+        color_indices.extend_from_slice(&[voxels[idx as usize].attributes(); 4]);
+    });
+    mesh.with_inserted_attribute(
+        ATTRIBUTE_COLOR_INDEX,
+        VertexAttributeValues::Uint32(color_indices),
+    )
+}
+
+/// Greedy-mesh `voxels` and call `on_quad` with the face index, face and each quad,
+/// in [`RIGHT_HANDED_Y_UP_CONFIG`] order.
+///
+/// Every mesh builder goes through this, so they all share the same face order and winding.
+pub(crate) fn greedy_faces<V, S>(
+    voxels: &[V],
+    shape: &S,
+    min: UVec3,
+    max: UVec3,
+    mut on_quad: impl FnMut(usize, &OrientedBlockFace, &UnorientedQuad),
+) where
+    V: MergeVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    let faces = RIGHT_HANDED_Y_UP_CONFIG.faces;
+    let mut quad_buffer = GreedyQuadsBuffer::new(voxels.len());
+
+    greedy_quads(
+        voxels,
+        shape,
+        min.into(),
+        max.into(),
+        &faces,
+        &mut quad_buffer,
+    );
+
+    for (face_idx, (quads, face)) in quad_buffer.quads.groups.into_iter().zip(faces).enumerate() {
+        for quad in quads {
+            on_quad(face_idx, &face, &quad);
+        }
+    }
+}
+
+/// Greedy-mesh `voxels` into a mesh with positions, normals, and indices.
+///
+/// `on_quad` is called with the face index (in [`RIGHT_HANDED_Y_UP_CONFIG`] order) and each quad,
+/// in vertex order, so callers can add their own per-vertex attributes.
+fn build_quads<V, S>(
+    voxels: &[V],
+    shape: &S,
+    min: UVec3,
+    max: UVec3,
+    mut on_quad: impl FnMut(usize, &UnorientedQuad),
+) -> Mesh
+where
+    V: MergeVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    let mut indices = Vec::new();
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+
+    greedy_faces(voxels, shape, min, max, |face_idx, face, quad| {
+        let quad_indices = face.quad_mesh_indices(positions.len() as u32);
+        indices.extend_from_slice(&quad_indices);
+
+        let quad_positions = face.quad_mesh_positions(quad, 1.);
+        positions.extend_from_slice(&quad_positions);
+
+        let quad_normals = face.quad_mesh_normals();
+        normals.extend_from_slice(&quad_normals);
+
+        on_quad(face_idx, quad);
+    });
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        VertexAttributeValues::Float32x3(positions),
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        VertexAttributeValues::Float32x3(normals),
+    )
+    .with_inserted_indices(Indices::U32(indices))
+}
@@ -0,0 +1,132 @@
+use crate::{Chunk, VoxelAttributes, build_mesh, greedy_faces};
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::primitives::Aabb,
+    mesh::{Indices, MeshVertexAttribute, VertexAttributeValues},
+    prelude::*,
+    render::render_resource::{PrimitiveTopology, VertexFormat},
+};
+use block_mesh::MergeVoxel;
+use ndshape::Shape;
+
+/// Packed position, face and voxel attributes for each vertex of a compact voxel mesh.
+///
+/// The first word stores the local position as 9-bit integers in bits 0-26 and the face index in bits 27-29.
+/// The second word stores the value of [`VoxelAttributes::attributes`].
+pub const ATTRIBUTE_PACKED_VOXEL: MeshVertexAttribute =
+    MeshVertexAttribute::new("PackedVoxel", 988940919, VertexFormat::Uint32x2);
+
+/// The maximum coordinate of a packed vertex position.
+pub const PACKED_POSITION_MAX: u32 = (1 << 9) - 1;
+
+/// Normals of each face index, in the same order as [`RIGHT_HANDED_Y_UP_CONFIG`](block_mesh::RIGHT_HANDED_Y_UP_CONFIG).
+pub const FACE_NORMALS: [Vec3; 6] = [
+    Vec3::NEG_X,
+    Vec3::NEG_Y,
+    Vec3::NEG_Z,
+    Vec3::X,
+    Vec3::Y,
+    Vec3::Z,
+];
+
+/// Pack a vertex position, face index and voxel attributes into an [`ATTRIBUTE_PACKED_VOXEL`] value.
+pub fn pack_vertex(position: UVec3, face: u32, attributes: u32) -> [u32; 2] {
+    debug_assert!(position.cmple(UVec3::splat(PACKED_POSITION_MAX)).all());
+    debug_assert!(face < 6);
+
+    [
+        position.x | (position.y << 9) | (position.z << 18) | (face << 27),
+        attributes,
+    ]
+}
+
+/// Returns the local position of a packed vertex.
+pub fn unpack_position(packed: [u32; 2]) -> Vec3 {
+    UVec3::new(
+        packed[0] & PACKED_POSITION_MAX,
+        (packed[0] >> 9) & PACKED_POSITION_MAX,
+        (packed[0] >> 18) & PACKED_POSITION_MAX,
+    )
+    .as_vec3()
+}
+
+/// Returns the face index of a packed vertex.
+pub fn unpack_face(packed: [u32; 2]) -> u32 {
+    (packed[0] >> 27) & 0b111
+}
+
+/// Returns the bounding box of a mesh built with [`Chunk::build_packed`].
+///
+/// Packed meshes have no [`Mesh::ATTRIBUTE_POSITION`], so Bevy can't compute their [`Aabb`] automatically.
+pub fn packed_aabb(mesh: &Mesh) -> Option<Aabb> {
+    let Some(VertexAttributeValues::Uint32x2(values)) = mesh.attribute(ATTRIBUTE_PACKED_VOXEL)
+    else {
+        return None;
+    };
+
+    let mut positions = values.iter().map(|packed| unpack_position(*packed));
+    let first = positions.next()?;
+    let (min, max) = positions.fold((first, first), |(min, max), pos| {
+        (min.min(pos), max.max(pos))
+    });
+    Some(Aabb::from_min_max(min, max))
+}
+
+impl<V, VS, S> Chunk<V, VS, S>
+where
+    VS: AsRef<[V]>,
+    V: MergeVoxel + VoxelAttributes,
+    S: Shape<3, Coord = u32>,
+{
+    /// Build a compact mesh for this chunk using [`ATTRIBUTE_PACKED_VOXEL`] instead of float positions and normals.
+    ///
+    /// Each vertex takes 8 bytes instead of 28, and `u16` indices are used when there are at most 65536 vertices.
+    /// [`VoxelMaterial`](crate::VoxelMaterial) decodes packed meshes in its vertex shader.
+    ///
+    /// Vertex positions must fit in [`PACKED_POSITION_MAX`], so if `max` is larger than that on any axis,
+    /// this falls back to an unpacked mesh like [`MeshBuilder::build`].
+    pub fn build_packed(&self) -> Mesh {
+        let voxels = self.voxels.as_ref();
+        if self.max.cmpgt(UVec3::splat(PACKED_POSITION_MAX)).any() {
+            return build_mesh(voxels, &self.shape, self.min, self.max);
+        }
+
+        let mut indices = Vec::new();
+        let mut packed = Vec::new();
+
+        greedy_faces(
+            voxels,
+            &self.shape,
+            self.min,
+            self.max,
+            |face_idx, face, quad| {
+                indices.extend_from_slice(&face.quad_mesh_indices(packed.len() as u32));
+
+                let attributes = voxels[self.shape.linearize(quad.minimum) as usize].attributes();
+                for position in face.quad_mesh_positions(quad, 1.) {
+                    packed.push(pack_vertex(
+                        Vec3::from(position).as_uvec3(),
+                        face_idx as u32,
+                        attributes,
+                    ));
+                }
+            },
+        );
+
+        let indices = if packed.len() <= u16::MAX as usize + 1 {
+            Indices::U16(indices.into_iter().map(|idx| idx as u16).collect())
+        } else {
+            Indices::U32(indices)
+        };
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(
+            ATTRIBUTE_PACKED_VOXEL,
+            VertexAttributeValues::Uint32x2(packed),
+        )
+        .with_inserted_indices(indices)
+    }
+}
@@ -0,0 +1,23 @@
+#define_import_path voxy::packed_vertex
+
+// Decodes vertices packed with `voxy::pack_vertex`.
+
+fn position(packed: vec2<u32>) -> vec3<f32> {
+    return vec3<f32>(
+        f32(packed.x & 511u),
+        f32((packed.x >> 9u) & 511u),
+        f32((packed.x >> 18u) & 511u)
+    );
+}
+
+fn normal(packed: vec2<u32>) -> vec3<f32> {
+    // Faces are ordered -X, -Y, -Z, +X, +Y, +Z.
+    let face = (packed.x >> 27u) & 7u;
+    let axis = face % 3u;
+    let sign = select(-1.0, 1.0, face >= 3u);
+    return vec3<f32>(f32(axis == 0u), f32(axis == 1u), f32(axis == 2u)) * sign;
+}
+
+fn color_index(packed: vec2<u32>) -> u32 {
+    return packed.y;
+}
@@ -0,0 +1,261 @@
+use crate::{
+    VoxelMaterial,
+    scene::{
+        LoadedAssets, SpawnedScene, VoxelSceneModels, handle_model_ref_events, handle_scene_events,
+        load_model_refs, load_scenes, spawned_scene_id,
+    },
+};
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap, hash_map::Entry};
+
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PaletteVariants>().add_systems(
+            Update,
+            (
+                apply_palette_overrides
+                    .after(load_scenes)
+                    .after(load_model_refs)
+                    .after(handle_scene_events)
+                    .after(handle_model_ref_events),
+                remove_palette_overrides.after(apply_palette_overrides),
+            ),
+        );
+    }
+}
+
+/// Recolour a spawned [`VoxelScene`](crate::VoxelScene) without affecting other instances of the same scene.
+///
+/// Insert this on the entity with the [`VoxelSceneHandle`](crate::scene::VoxelSceneHandle)
+/// or [`VoxelSceneModelRef`](crate::scene::VoxelSceneModelRef).
+/// Instances with identical overrides share the same material variant.
+#[derive(Clone, Debug, PartialEq, Component)]
+pub struct VoxelPaletteOverride {
+    /// Replace the colour and emission of a palette index (the key) with those of another index (the value).
+    pub remap: BTreeMap<u8, u8>,
+    /// Replace the colour of a palette index.
+    pub colors: BTreeMap<u8, Color>,
+    /// Multiply every colour in the palette.
+    pub tint: Color,
+}
+
+impl Default for VoxelPaletteOverride {
+    fn default() -> Self {
+        Self {
+            remap: BTreeMap::new(),
+            colors: BTreeMap::new(),
+            tint: Color::WHITE,
+        }
+    }
+}
+
+impl VoxelPaletteOverride {
+    /// Create an override that tints every colour.
+    pub fn tinted(tint: impl Into<Color>) -> Self {
+        Self {
+            tint: tint.into(),
+            ..default()
+        }
+    }
+
+    /// Replace palette index `from` with the colour and emission of `to`.
+    pub fn with_remap(mut self, from: u8, to: u8) -> Self {
+        self.remap.insert(from, to);
+        self
+    }
+
+    /// Replace the colour of palette index `idx`.
+    pub fn with_color(mut self, idx: u8, color: impl Into<Color>) -> Self {
+        self.colors.insert(idx, color.into());
+        self
+    }
+
+    /// Returns a copy of `material` with this override applied.
+    pub fn apply(&self, material: &VoxelMaterial) -> VoxelMaterial {
+        let mut variant = material.clone();
+
+        for (from, to) in &self.remap {
+            variant.colors[*from as usize] = material.colors[*to as usize];
+            variant.emissions[*from as usize] = material.emissions[*to as usize];
+        }
+
+        for (idx, color) in &self.colors {
+            variant.colors[*idx as usize] = color.to_linear().to_vec3();
+        }
+
+        let tint = self.tint.to_linear().to_vec3();
+        for color in &mut variant.colors {
+            *color *= tint;
+        }
+
+        variant
+    }
+
+    fn key(&self) -> OverrideKey {
+        OverrideKey {
+            remap: self.remap.iter().map(|(from, to)| (*from, *to)).collect(),
+            colors: self
+                .colors
+                .iter()
+                .map(|(idx, color)| (*idx, color_bits(*color)))
+                .collect(),
+            tint: color_bits(self.tint),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct OverrideKey {
+    remap: Vec<(u8, u8)>,
+    colors: Vec<(u8, [u32; 4])>,
+    tint: [u32; 4],
+}
+
+pub(crate) fn color_bits(color: Color) -> [u32; 4] {
+    color.to_linear().to_f32_array().map(f32::to_bits)
+}
+
+/// Cache of material variants created for [`VoxelPaletteOverride`]s, keyed by base material and override.
+///
+/// Each variant counts the entities whose override uses it,
+/// and is removed once the last of those overrides is changed or removed.
+#[derive(Default, Resource)]
+pub struct PaletteVariants {
+    variants: HashMap<VariantKey, PaletteVariant>,
+    owners: HashMap<Entity, VariantKey>,
+}
+
+type VariantKey = (AssetId<VoxelMaterial>, OverrideKey);
+
+struct PaletteVariant {
+    handle: Handle<VoxelMaterial>,
+    owners: usize,
+}
+
+impl PaletteVariants {
+    /// Returns the number of cached variants.
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Returns `true` if there are no cached variants.
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// Returns the variant of `base` for `entity`'s override, creating it if needed,
+    /// and releases the variant `entity` used before.
+    fn acquire(
+        &mut self,
+        entity: Entity,
+        base: &Handle<VoxelMaterial>,
+        palette_override: &VoxelPaletteOverride,
+        materials: &mut Assets<VoxelMaterial>,
+    ) -> Option<Handle<VoxelMaterial>> {
+        let key = (base.id(), palette_override.key());
+        if let Some(owned) = self.owners.get(&entity)
+            && *owned == key
+        {
+            return Some(self.variants[&key].handle.clone());
+        }
+
+        let variant = match self.variants.entry(key.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let material = palette_override.apply(materials.get(base)?);
+                entry.insert(PaletteVariant {
+                    handle: materials.add(material),
+                    owners: 0,
+                })
+            }
+        };
+        variant.owners += 1;
+        let handle = variant.handle.clone();
+
+        self.release(entity);
+        self.owners.insert(entity, key);
+        Some(handle)
+    }
+
+    /// Stop `entity` from using its variant, removing the variant if nothing else uses it.
+    fn release(&mut self, entity: Entity) {
+        let Some(key) = self.owners.remove(&entity) else {
+            return;
+        };
+        if let Some(variant) = self.variants.get_mut(&key) {
+            variant.owners -= 1;
+            if variant.owners == 0 {
+                self.variants.remove(&key);
+            }
+        }
+    }
+}
+
+pub fn apply_palette_overrides(
+    query: Query<(
+        Entity,
+        SpawnedScene,
+        Ref<VoxelPaletteOverride>,
+        Ref<VoxelSceneModels>,
+    )>,
+    children_query: Query<&Children>,
+    mut material_query: Query<&mut MeshMaterial3d<VoxelMaterial>>,
+    loaded_assets: Res<LoadedAssets>,
+    mut variants: ResMut<PaletteVariants>,
+    mut materials: ResMut<Assets<VoxelMaterial>>,
+) {
+    for (entity, scene, palette_override, models) in &query {
+        if !palette_override.is_changed() && !models.is_changed() {
+            continue;
+        }
+        let Some(base) = spawned_scene_id(scene).and_then(|id| loaded_assets.material(id)) else {
+            continue;
+        };
+
+        if let Some(variant) = variants.acquire(entity, base, &palette_override, &mut materials) {
+            set_scene_material(entity, &variant, &children_query, &mut material_query);
+        }
+    }
+}
+
+/// Restore the shared material of scenes whose [`VoxelPaletteOverride`] was removed, and release their variants.
+pub fn remove_palette_overrides(
+    mut removed: RemovedComponents<VoxelPaletteOverride>,
+    query: Query<SpawnedScene, With<VoxelSceneModels>>,
+    override_query: Query<(), With<VoxelPaletteOverride>>,
+    children_query: Query<&Children>,
+    mut material_query: Query<&mut MeshMaterial3d<VoxelMaterial>>,
+    loaded_assets: Res<LoadedAssets>,
+    mut variants: ResMut<PaletteVariants>,
+) {
+    for entity in removed.read() {
+        // The override was inserted again after it was removed.
+        if override_query.contains(entity) {
+            continue;
+        }
+        variants.release(entity);
+
+        if let Ok(scene) = query.get(entity)
+            && let Some(base) = spawned_scene_id(scene).and_then(|id| loaded_assets.material(id))
+        {
+            set_scene_material(entity, base, &children_query, &mut material_query);
+        }
+    }
+}
+
+pub(crate) fn set_scene_material(
+    entity: Entity,
+    material: &Handle<VoxelMaterial>,
+    children_query: &Query<&Children>,
+    material_query: &mut Query<&mut MeshMaterial3d<VoxelMaterial>>,
+) {
+    for descendant in children_query.iter_descendants(entity) {
+        if let Ok(mut mesh_material) = material_query.get_mut(descendant)
+            && mesh_material.0 != *material
+        {
+            mesh_material.0 = material.clone();
+        }
+    }
+}
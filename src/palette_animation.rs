@@ -0,0 +1,227 @@
+use crate::{
+    VoxelMaterial,
+    palette::{apply_palette_overrides, remove_palette_overrides, set_scene_material},
+    scene::VoxelSceneModels,
+};
+use bevy::prelude::*;
+use std::{f32::consts::TAU, ops::RangeInclusive};
+
+pub struct PaletteAnimationPlugin;
+
+impl Plugin for PaletteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                animate_palettes
+                    .after(apply_palette_overrides)
+                    .after(remove_palette_overrides),
+                remove_palette_animations.after(animate_palettes),
+            ),
+        );
+    }
+}
+
+/// Rotate the colours and emissions of a range of palette indices.
+#[derive(Clone, Debug)]
+pub struct ColorCycle {
+    pub range: RangeInclusive<u8>,
+    /// Number of indices to shift per second.
+    pub speed: f32,
+}
+
+/// Pulse the emission of a palette index with a sine wave.
+#[derive(Clone, Debug)]
+pub struct EmissionPulse {
+    pub index: u8,
+    pub min: f32,
+    pub max: f32,
+    /// Number of pulses per second.
+    pub frequency: f32,
+    /// Phase offset in cycles, from `0.` to `1.`.
+    pub phase: f32,
+}
+
+/// Linearly interpolate the colour of a palette index between keyframes, looping after the last one.
+#[derive(Clone, Debug)]
+pub struct ColorKeyframes {
+    pub index: u8,
+    /// Keyframes as `(time in seconds, colour)`, sorted by time.
+    pub keyframes: Vec<(f32, Color)>,
+}
+
+impl ColorKeyframes {
+    /// Returns the colour at `time`.
+    pub fn sample(&self, time: f32) -> Option<Color> {
+        let (first, last) = (self.keyframes.first()?, self.keyframes.last()?);
+        if last.0 <= 0. {
+            return Some(last.1);
+        }
+
+        let time = time.rem_euclid(last.0);
+        let next = self
+            .keyframes
+            .iter()
+            .position(|(keyframe_time, _)| *keyframe_time > time)
+            .unwrap_or(self.keyframes.len() - 1);
+        if next == 0 {
+            return Some(first.1);
+        }
+
+        let (start_time, start) = self.keyframes[next - 1];
+        let (end_time, end) = self.keyframes[next];
+        let t = ((time - start_time) / (end_time - start_time).max(f32::EPSILON)).clamp(0., 1.);
+        Some(start.mix(&end, t))
+    }
+}
+
+/// Animate the palette of a spawned [`VoxelScene`](crate::VoxelScene) without remeshing.
+///
+/// Insert this on the entity with the [`VoxelSceneHandle`](crate::scene::VoxelSceneHandle).
+/// Each animated instance gets its own copy of the scene's material (including any [`VoxelPaletteOverride`](crate::VoxelPaletteOverride)),
+/// whose `colors` and `emissions` are updated every frame.
+#[derive(Clone, Debug, Default, Component)]
+pub struct PaletteAnimation {
+    pub cycles: Vec<ColorCycle>,
+    pub pulses: Vec<EmissionPulse>,
+    pub keyframes: Vec<ColorKeyframes>,
+}
+
+impl PaletteAnimation {
+    /// Rotate the palette indices in `range` by `speed` indices per second.
+    pub fn with_cycle(mut self, range: RangeInclusive<u8>, speed: f32) -> Self {
+        self.cycles.push(ColorCycle { range, speed });
+        self
+    }
+
+    /// Pulse the emission of palette index `index` between `min` and `max`, `frequency` times per second.
+    pub fn with_pulse(mut self, index: u8, min: f32, max: f32, frequency: f32) -> Self {
+        self.pulses.push(EmissionPulse {
+            index,
+            min,
+            max,
+            frequency,
+            phase: 0.,
+        });
+        self
+    }
+
+    /// Interpolate the colour of palette index `index` between `keyframes`.
+    pub fn with_keyframes(mut self, index: u8, keyframes: Vec<(f32, Color)>) -> Self {
+        self.keyframes.push(ColorKeyframes { index, keyframes });
+        self
+    }
+
+    /// Write the animated palette at `time` into `material`, starting from `base`.
+    pub fn apply(&self, base: &VoxelMaterial, time: f32, material: &mut VoxelMaterial) {
+        material.colors = base.colors;
+        material.emissions = base.emissions;
+
+        for cycle in &self.cycles {
+            let start = *cycle.range.start() as usize;
+            let len = (*cycle.range.end() as usize + 1).saturating_sub(start);
+            if len == 0 {
+                continue;
+            }
+
+            let offset = (time * cycle.speed).floor().rem_euclid(len as f32) as usize;
+            for idx in 0..len {
+                let src = start + (idx + offset) % len;
+                material.colors[start + idx] = base.colors[src];
+                material.emissions[start + idx] = base.emissions[src];
+            }
+        }
+
+        for keyframes in &self.keyframes {
+            if let Some(color) = keyframes.sample(time) {
+                material.colors[keyframes.index as usize] = color.to_linear().to_vec3();
+            }
+        }
+
+        for pulse in &self.pulses {
+            let wave = 0.5 + 0.5 * (TAU * (pulse.frequency * time + pulse.phase)).sin();
+            material.emissions[pulse.index as usize].x = pulse.min + (pulse.max - pulse.min) * wave;
+        }
+    }
+}
+
+/// The per-instance material of an entity with a [`PaletteAnimation`].
+#[derive(Component)]
+pub struct AnimatedPalette {
+    /// The material the animation started from, restored when the animation is removed.
+    pub base: Handle<VoxelMaterial>,
+    /// The animated copy of `base`.
+    pub material: Handle<VoxelMaterial>,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn animate_palettes(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &PaletteAnimation,
+        Option<&AnimatedPalette>,
+        Ref<VoxelSceneModels>,
+    )>,
+    children_query: Query<&Children>,
+    mut material_query: Query<&mut MeshMaterial3d<VoxelMaterial>>,
+    mut materials: ResMut<Assets<VoxelMaterial>>,
+    time: Res<Time>,
+) {
+    for (entity, animation, animated, models) in &mut query {
+        let Some(current) = children_query
+            .iter_descendants(entity)
+            .find_map(|descendant| material_query.get(descendant).ok())
+            .map(|mesh_material| mesh_material.0.clone())
+        else {
+            continue;
+        };
+
+        // Copy the current material if the scene was (re)spawned or another system replaced it.
+        let (base, material) = match animated {
+            Some(animated) if !models.is_changed() && animated.material == current => {
+                (animated.base.clone(), animated.material.clone())
+            }
+            _ => {
+                let base = match animated {
+                    Some(animated) if animated.material == current => animated.base.clone(),
+                    _ => current,
+                };
+                let Some(base_material) = materials.get(&base).cloned() else {
+                    continue;
+                };
+
+                let material = materials.add(base_material);
+                set_scene_material(entity, &material, &children_query, &mut material_query);
+                commands.entity(entity).insert(AnimatedPalette {
+                    base: base.clone(),
+                    material: material.clone(),
+                });
+                (base, material)
+            }
+        };
+
+        let Some(base) = materials.get(&base).cloned() else {
+            continue;
+        };
+        if let Some(material) = materials.get_mut(&material) {
+            animation.apply(&base, time.elapsed_secs(), material);
+        }
+    }
+}
+
+/// Restore the base material of scenes whose [`PaletteAnimation`] was removed.
+pub fn remove_palette_animations(
+    mut commands: Commands,
+    mut removed: RemovedComponents<PaletteAnimation>,
+    query: Query<&AnimatedPalette>,
+    children_query: Query<&Children>,
+    mut material_query: Query<&mut MeshMaterial3d<VoxelMaterial>>,
+) {
+    for entity in removed.read() {
+        if let Ok(animated) = query.get(entity) {
+            set_scene_material(entity, &animated.base, &children_query, &mut material_query);
+            commands.entity(entity).remove::<AnimatedPalette>();
+        }
+    }
+}
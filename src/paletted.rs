@@ -0,0 +1,196 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Palette-compressed voxel storage.
+///
+/// Each distinct voxel is stored once in a local palette and every position stores a bit-packed index into it.
+/// Indices start at 1 bit and grow up to 16 bits as new voxels are inserted, so a chunk of air costs a few bytes
+/// and typical terrain with a handful of block types costs 2-4 bits per voxel.
+///
+/// Use [`PalettedVoxels::expand_into`] (or [`Chunk::build_with_scratch`](crate::Chunk::build_with_scratch))
+/// to decode into a reused dense buffer for meshing.
+#[derive(Clone, Debug)]
+pub struct PalettedVoxels<V> {
+    palette: Vec<V>,
+    lookup: HashMap<V, u16>,
+    words: Vec<u64>,
+    bits: u32,
+    len: usize,
+}
+
+impl<V> PalettedVoxels<V>
+where
+    V: Copy + Eq + Hash,
+{
+    /// The maximum number of bits used to store a palette index.
+    pub const MAX_BITS: u32 = 16;
+
+    /// Create storage for `len` voxels, all set to `fill`.
+    pub fn new(len: usize, fill: V) -> Self {
+        let bits = 1;
+        Self {
+            palette: vec![fill],
+            lookup: HashMap::from([(fill, 0)]),
+            words: vec![0; words_for(len, bits)],
+            bits,
+            len,
+        }
+    }
+
+    /// Compress a dense slice of voxels.
+    pub fn from_slice(voxels: &[V]) -> Self {
+        let Some(first) = voxels.first() else {
+            return Self {
+                palette: Vec::new(),
+                lookup: HashMap::new(),
+                words: Vec::new(),
+                bits: 1,
+                len: 0,
+            };
+        };
+
+        let mut storage = Self::new(voxels.len(), *first);
+        for (idx, voxel) in voxels.iter().enumerate().skip(1) {
+            storage.set(idx, *voxel);
+        }
+        storage
+    }
+
+    /// Returns the number of voxels.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this storage contains no voxels.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the local palette of distinct voxels.
+    pub fn palette(&self) -> &[V] {
+        &self.palette
+    }
+
+    /// Returns the number of bits currently used per voxel.
+    pub fn bits_per_index(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the voxel at `idx`.
+    ///
+    /// # Panics
+    /// Panics if `idx` is out of bounds.
+    pub fn get(&self, idx: usize) -> V {
+        assert!(idx < self.len, "index {idx} out of bounds");
+        self.palette[self.index(idx) as usize]
+    }
+
+    /// Set the voxel at `idx`, returning the previous voxel.
+    ///
+    /// Grows the bits per index if `voxel` doesn't fit in the current palette.
+    ///
+    /// # Panics
+    /// Panics if `idx` is out of bounds or the palette would exceed `2^16` entries.
+    pub fn set(&mut self, idx: usize, voxel: V) -> V {
+        assert!(idx < self.len, "index {idx} out of bounds");
+
+        let palette_idx = match self.lookup.get(&voxel) {
+            Some(palette_idx) => *palette_idx,
+            None => {
+                let palette_idx = self.palette.len();
+                assert!(
+                    palette_idx < 1 << Self::MAX_BITS,
+                    "palette exceeds {} entries",
+                    1 << Self::MAX_BITS
+                );
+
+                if palette_idx >= 1 << self.bits {
+                    self.repack(self.bits + 1);
+                }
+
+                self.palette.push(voxel);
+                self.lookup.insert(voxel, palette_idx as u16);
+                palette_idx as u16
+            }
+        };
+
+        let old = self.index(idx);
+        self.set_index(idx, palette_idx);
+        self.palette[old as usize]
+    }
+
+    /// Decode every voxel into `dst`, replacing its contents.
+    pub fn expand_into(&self, dst: &mut Vec<V>) {
+        dst.clear();
+        dst.reserve(self.len);
+
+        let per_word = (u64::BITS / self.bits) as usize;
+        let mask = (1u64 << self.bits) - 1;
+
+        for word in &self.words {
+            let remaining = (self.len - dst.len()).min(per_word);
+            let mut word = *word;
+            for _ in 0..remaining {
+                dst.push(self.palette[(word & mask) as usize]);
+                word >>= self.bits;
+            }
+        }
+    }
+
+    /// Decode every voxel into a new dense vector.
+    pub fn to_vec(&self) -> Vec<V> {
+        let mut voxels = Vec::new();
+        self.expand_into(&mut voxels);
+        voxels
+    }
+
+    /// Returns the approximate number of heap bytes used by this storage.
+    pub fn heap_size(&self) -> usize {
+        self.words.capacity() * size_of::<u64>()
+            + self.palette.capacity() * size_of::<V>()
+            + self.lookup.capacity() * (size_of::<V>() + size_of::<u16>())
+    }
+
+    fn index(&self, idx: usize) -> u16 {
+        let per_word = (u64::BITS / self.bits) as usize;
+        let shift = (idx % per_word) as u32 * self.bits;
+        let mask = (1u64 << self.bits) - 1;
+        ((self.words[idx / per_word] >> shift) & mask) as u16
+    }
+
+    fn set_index(&mut self, idx: usize, palette_idx: u16) {
+        let per_word = (u64::BITS / self.bits) as usize;
+        let shift = (idx % per_word) as u32 * self.bits;
+        let mask = (1u64 << self.bits) - 1;
+        let word = &mut self.words[idx / per_word];
+        *word = (*word & !(mask << shift)) | ((palette_idx as u64) << shift);
+    }
+
+    fn repack(&mut self, bits: u32) {
+        let mut repacked = Self {
+            palette: Vec::new(),
+            lookup: HashMap::new(),
+            words: vec![0; words_for(self.len, bits)],
+            bits,
+            len: self.len,
+        };
+        for idx in 0..self.len {
+            repacked.set_index(idx, self.index(idx));
+        }
+
+        self.words = repacked.words;
+        self.bits = bits;
+    }
+}
+
+impl<V> From<&[V]> for PalettedVoxels<V>
+where
+    V: Copy + Eq + Hash,
+{
+    fn from(voxels: &[V]) -> Self {
+        Self::from_slice(voxels)
+    }
+}
+
+fn words_for(len: usize, bits: u32) -> usize {
+    len.div_ceil((u64::BITS / bits) as usize)
+}
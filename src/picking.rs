@@ -0,0 +1,214 @@
+use crate::{AssetVoxelChunk, scene::VoxelSceneChunk};
+use bevy::{
+    picking::{
+        PickingSystems,
+        backend::{HitData, PointerHits, ray::RayMap},
+    },
+    prelude::*,
+};
+use ndshape::Shape;
+
+/// A picking backend that casts pointer rays through the voxels of spawned scene chunks.
+///
+/// Unlike mesh picking, this works with packed meshes. The [`HitData`] of pointer events on a chunk has the world
+/// position and normal of the face that was hit.
+///
+/// Pointer events can't carry backend-specific data, so the voxel isn't part of the hit. Instead, pass the event's
+/// target and hit to [`VoxelHit::from_hit`] along with the chunk's [`VoxelSceneChunk`] and [`GlobalTransform`],
+/// e.g. in an observer of `Pointer<Click>`, to find the voxel behind the face.
+pub struct VoxelPickingPlugin;
+
+impl Plugin for VoxelPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, update_voxel_hits.in_set(PickingSystems::Backend));
+    }
+}
+
+/// A voxel hit by a pointer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoxelHit {
+    /// The chunk entity that was hit.
+    pub entity: Entity,
+    /// The coordinates of the voxel in its model.
+    pub voxel: IVec3,
+    /// The normal of the face that was hit, in model space.
+    pub normal: IVec3,
+    /// The palette index of the voxel.
+    pub palette_index: u8,
+    /// The world position of the hit.
+    pub position: Vec3,
+    /// The distance from the ray origin to the hit.
+    pub distance: f32,
+}
+
+impl VoxelHit {
+    /// Returns the voxel of the chunk `entity` at `hit`, e.g. the hit of a `Pointer<Click>` event on the chunk.
+    ///
+    /// `transform` is the [`GlobalTransform`] of the chunk.
+    /// Returns `None` if the hit has no position or normal, or if there is no voxel behind it.
+    pub fn from_hit(
+        entity: Entity,
+        chunk: &VoxelSceneChunk,
+        transform: &GlobalTransform,
+        hit: &HitData,
+    ) -> Option<Self> {
+        let voxels = chunk.voxels.as_ref()?;
+        let position = hit.position?;
+        let local_from_world = transform.affine().inverse();
+        let local_normal = local_from_world.transform_vector3(hit.normal?);
+        let axis = local_normal.abs().max_position();
+        let mut normal = IVec3::ZERO;
+        normal[axis] = local_normal[axis].signum() as i32;
+
+        // Step half a voxel back through the face that was hit.
+        let pos = (local_from_world.transform_point3(position) - normal.as_vec3() * 0.5)
+            .floor()
+            .as_ivec3();
+        let size = UVec3::from(voxels.shape.as_array()).as_ivec3();
+        if pos.cmplt(IVec3::ZERO).any() || pos.cmpge(size).any() {
+            return None;
+        }
+
+        let voxel = voxels.voxels[voxels.shape.linearize(pos.as_uvec3().into()) as usize];
+        Some(Self {
+            entity,
+            voxel: chunk.origin + pos,
+            normal,
+            palette_index: voxel.idx.checked_sub(1)?,
+            position,
+            distance: hit.depth,
+        })
+    }
+}
+
+/// Report a hit for the first voxel of each visible, hoverable chunk along each pointer ray.
+pub fn update_voxel_hits(
+    ray_map: Res<RayMap>,
+    cameras: Query<&Camera>,
+    chunks: Query<(
+        Entity,
+        &VoxelSceneChunk,
+        &GlobalTransform,
+        &ViewVisibility,
+        Option<&Pickable>,
+    )>,
+    mut pointer_hits_writer: MessageWriter<PointerHits>,
+) {
+    for (&ray_id, &ray) in ray_map.iter() {
+        let Ok(camera) = cameras.get(ray_id.camera) else {
+            continue;
+        };
+
+        let mut picks = Vec::new();
+        for (entity, chunk, global_transform, visibility, pickable) in &chunks {
+            let Some(voxels) = &chunk.voxels else {
+                continue;
+            };
+            if !visibility.get() || pickable.is_some_and(|pickable| !pickable.is_hoverable) {
+                continue;
+            }
+
+            let local_from_world = global_transform.affine().inverse();
+            let origin = local_from_world.transform_point3(ray.origin);
+            let direction = local_from_world.transform_vector3(*ray.direction);
+            let Some((_, normal, t)) = cast_ray(voxels, origin, direction) else {
+                continue;
+            };
+
+            let position = global_transform.transform_point(origin + direction * t);
+            let world_normal = global_transform
+                .affine()
+                .transform_vector3(normal.as_vec3())
+                .normalize();
+            let distance = position.distance(ray.origin);
+
+            picks.push((
+                entity,
+                HitData::new(ray_id.camera, distance, Some(position), Some(world_normal)),
+            ));
+        }
+
+        if !picks.is_empty() {
+            pointer_hits_writer.write(PointerHits::new(ray_id.pointer, picks, camera.order as f32));
+        }
+    }
+}
+
+/// Walk a ray through the interior (non-padding) voxels of `chunk`, in chunk space.
+///
+/// Returns the padded coordinates of the first solid voxel, the normal of the face the ray entered it through,
+/// and the ray parameter `t` of the hit, so the hit is at `origin + direction * t`.
+pub fn cast_ray(
+    chunk: &AssetVoxelChunk,
+    origin: Vec3,
+    direction: Vec3,
+) -> Option<(UVec3, IVec3, f32)> {
+    let min = IVec3::ONE;
+    let max = UVec3::from(chunk.shape.as_array()).as_ivec3() - IVec3::ONE;
+    if max.cmple(min).any() {
+        return None;
+    }
+
+    // Clip the ray to the interior bounds.
+    let inv_direction = direction.recip();
+    let t0 = (min.as_vec3() - origin) * inv_direction;
+    let t1 = (max.as_vec3() - origin) * inv_direction;
+    let t_near = t0.min(t1);
+    let t_far = t0.max(t1);
+    let (mut t, entry_axis) = [t_near.x, t_near.y, t_near.z]
+        .into_iter()
+        .enumerate()
+        .filter(|(_, t)| !t.is_nan())
+        .map(|(axis, t)| (t, axis))
+        .max_by(|a, b| a.0.total_cmp(&b.0))?;
+    let t_exit = t_far.min_element();
+    if t_exit < t.max(0.) {
+        return None;
+    }
+
+    let step = Vec3::select(direction.cmpeq(Vec3::ZERO), Vec3::ZERO, direction.signum()).as_ivec3();
+    let mut normal = IVec3::ZERO;
+    if t > 0. {
+        normal[entry_axis] = -step[entry_axis];
+    } else {
+        t = 0.;
+    }
+
+    let start = origin + direction * t;
+    let mut voxel = start.floor().as_ivec3().clamp(min, max - IVec3::ONE);
+    let mut t_max = Vec3::INFINITY;
+    let mut t_delta = Vec3::INFINITY;
+    for axis in 0..3 {
+        if step[axis] != 0 {
+            let boundary = (voxel[axis] + step[axis].max(0)) as f32;
+            t_max[axis] = (boundary - origin[axis]) * inv_direction[axis];
+            t_delta[axis] = inv_direction[axis].abs();
+        }
+    }
+
+    loop {
+        let pos = voxel.as_uvec3();
+        if chunk.voxels[chunk.shape.linearize(pos.into()) as usize].idx != 0 {
+            if normal == IVec3::ZERO {
+                // The ray started inside this voxel, so report the face facing the ray.
+                let axis = direction.abs().max_position();
+                normal[axis] = -step[axis];
+            }
+            return Some((pos, normal, t));
+        }
+
+        let axis = t_max.min_position();
+        if t_max[axis] > t_exit {
+            return None;
+        }
+
+        t = t_max[axis];
+        voxel[axis] += step[axis];
+        if voxel[axis] < min[axis] || voxel[axis] >= max[axis] {
+            return None;
+        }
+        t_max[axis] += t_delta[axis];
+        normal = IVec3::ZERO;
+        normal[axis] = -step[axis];
+    }
+}
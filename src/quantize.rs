@@ -0,0 +1,120 @@
+use crate::VoxelMaterial;
+use bevy::prelude::*;
+
+/// A palette of at most 255 colours built from sampled colours with median cut quantization.
+///
+/// Colours are stored in linear space like [`VoxelMaterial::colors`].
+/// A palette fits in a `VoxelMaterial` with one entry to spare,
+/// because [`AssetVoxel`](crate::AssetVoxel) index 0 is empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QuantizedPalette {
+    pub colors: Vec<Vec3>,
+}
+
+impl QuantizedPalette {
+    /// The maximum number of colours in a palette.
+    pub const MAX_COLORS: usize = 255;
+
+    /// Build a palette of at most `max_colors` colours (clamped to [`MAX_COLORS`](Self::MAX_COLORS)) from linear `samples`.
+    ///
+    /// If there are fewer distinct samples than `max_colors`, each of them is kept exactly.
+    /// Samples with NaN or infinite components are ignored.
+    pub fn from_samples(samples: &[Vec3], max_colors: usize) -> Self {
+        let max_colors = max_colors.clamp(1, Self::MAX_COLORS);
+
+        // Split in sRGB space so the palette is spread evenly to the eye.
+        let mut points: Vec<Vec3> = samples
+            .iter()
+            .filter(|color| color.is_finite())
+            .map(|color| to_srgb(*color))
+            .collect();
+        if points.is_empty() {
+            return Self::default();
+        }
+        points.sort_unstable_by(|a, b| {
+            a.x.total_cmp(&b.x)
+                .then(a.y.total_cmp(&b.y))
+                .then(a.z.total_cmp(&b.z))
+        });
+        points.dedup();
+
+        let mut boxes = Vec::with_capacity(max_colors);
+        boxes.push(0..points.len());
+        while boxes.len() < max_colors {
+            // Split the box with the widest channel range.
+            let Some((box_idx, axis, _)) = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, range)| range.len() > 1)
+                .map(|(idx, range)| {
+                    let (min, max) = bounds(&points[range.clone()]);
+                    let extent = max - min;
+                    (idx, extent.max_position(), extent.max_element())
+                })
+                .max_by(|a, b| a.2.total_cmp(&b.2))
+            else {
+                break;
+            };
+
+            let range = boxes.swap_remove(box_idx);
+            points[range.clone()].sort_unstable_by(|a, b| a[axis].total_cmp(&b[axis]));
+            let mid = range.start + range.len() / 2;
+            boxes.push(range.start..mid);
+            boxes.push(mid..range.end);
+        }
+
+        let colors = boxes
+            .into_iter()
+            .map(|range| {
+                let sum: Vec3 = points[range.clone()].iter().sum();
+                from_srgb(sum / range.len() as f32)
+            })
+            .collect();
+        Self { colors }
+    }
+
+    /// Returns the index of the palette colour closest to linear `color`.
+    pub fn nearest(&self, color: Vec3) -> u8 {
+        let color = to_srgb(color);
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                to_srgb(**a)
+                    .distance_squared(color)
+                    .total_cmp(&to_srgb(**b).distance_squared(color))
+            })
+            .map_or(0, |(idx, _)| idx as u8)
+    }
+
+    /// Returns a material with this palette and no emission.
+    ///
+    /// Palette colour `i` is used by voxels with [`AssetVoxel::idx`](crate::AssetVoxel::idx) `i + 1`.
+    pub fn material(&self) -> VoxelMaterial {
+        let mut colors = [Vec3::ONE; 256];
+        for (dst, src) in colors.iter_mut().zip(&self.colors) {
+            *dst = *src;
+        }
+
+        VoxelMaterial {
+            colors,
+            emissions: [Vec3::new(0., 1., 1.); 256],
+        }
+    }
+}
+
+fn bounds(points: &[Vec3]) -> (Vec3, Vec3) {
+    points.iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), point| (min.min(*point), max.max(*point)),
+    )
+}
+
+fn to_srgb(color: Vec3) -> Vec3 {
+    let srgb = Color::linear_rgb(color.x, color.y, color.z).to_srgba();
+    Vec3::new(srgb.red, srgb.green, srgb.blue)
+}
+
+fn from_srgb(color: Vec3) -> Vec3 {
+    Color::srgb(color.x, color.y, color.z).to_linear().to_vec3()
+}
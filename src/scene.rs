@@ -1,12 +1,22 @@
-use crate::{VoxAssetLoader, VoxelMaterial};
+use crate::{
+    ATTRIBUTE_PACKED_VOXEL, AssetChunk, AssetModel, AssetVoxelChunk, Chunk, EditBounds, MeshStats,
+    VoxAssetLoader, VoxelMaterial, packed_aabb,
+};
 use bevy::{
-    asset::{AssetLoader, LoadContext, io::Reader},
-    ecs::system::EntityCommands,
+    asset::{AssetLoadError, AssetLoader, LoadContext, LoadState, RenderAssetUsages, io::Reader},
+    camera::primitives::{Aabb, MeshAabb},
+    ecs::{
+        hierarchy::ChildSpawnerCommands,
+        query::AnyOf,
+        system::{EntityCommands, SystemParam},
+    },
     prelude::*,
+    render::render_resource::PrimitiveTopology,
 };
 use futures::future;
 use ndshape::Shape;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 pub struct ScenePlugin;
@@ -16,7 +26,20 @@ impl Plugin for ScenePlugin {
         app.init_asset::<VoxelScene>()
             .init_asset_loader::<SceneLoader>()
             .init_resource::<LoadedAssets>()
-            .add_systems(Update, (load_scenes, handle_scene_events));
+            .add_systems(
+                Update,
+                (
+                    load_scenes,
+                    load_model_refs,
+                    handle_scene_events,
+                    handle_model_ref_events.after(handle_scene_events),
+                    remove_unused_scene_assets
+                        .after(handle_scene_events)
+                        .after(handle_model_ref_events),
+                    sync_chunk_padding.before(remesh_edited_chunks),
+                    remesh_edited_chunks,
+                ),
+            );
     }
 }
 
@@ -31,69 +54,468 @@ pub struct VoxelLight {
     pub intensity: f32,
 }
 
+/// A mesh built from a single chunk of a model, with lights for its emissive voxels.
 #[derive(Debug)]
 pub struct LitMesh {
-    pub mesh: Mesh,
+    /// The chunk's mesh, or `None` if this scene was loaded by [`SceneLoader`], which moves it into the sub-asset `handle`.
+    pub mesh: Option<Mesh>,
+    /// The statistics of the chunk's mesh, collected when it was built.
+    pub stats: MeshStats,
+    /// The voxels the mesh was built from, if this scene was built by [`VoxelScene::from_models`],
+    /// or loaded by [`SceneLoader`] with [`SceneLoaderSettings::keep_voxels`].
+    pub voxels: Option<Arc<AssetVoxelChunk>>,
+    /// The labeled sub-asset of `mesh`, if this scene was loaded by [`SceneLoader`].
+    pub handle: Option<Handle<Mesh>>,
     pub lights: Vec<VoxelLight>,
+    /// The transform of this chunk relative to its model.
+    pub transform: Transform,
+}
+
+impl LitMesh {
+    /// Create an unlit chunk mesh at `transform`, collecting its statistics.
+    pub fn new(mesh: Mesh, transform: Transform) -> Self {
+        Self {
+            stats: MeshStats::from_mesh(&mesh),
+            mesh: Some(mesh),
+            voxels: None,
+            handle: None,
+            lights: Vec::new(),
+            transform,
+        }
+    }
+
+    /// Returns the bounding box of the mesh in chunk space, or `None` if it's empty.
+    pub fn aabb(&self) -> Option<Aabb> {
+        self.stats
+            .bounds
+            .map(|(min, max)| Aabb::from_min_max(min, max))
+    }
+}
+
+/// A model in a [`VoxelScene`], made of one or more chunk meshes.
+#[derive(Debug)]
+pub struct VoxelModel {
+    pub meshes: Vec<LitMesh>,
     pub name: Option<String>,
+    /// The names of the named nodes from the scene root down to this model, e.g. `["tree_03", "trunk"]`.
+    pub path: Vec<String>,
     pub transform: Transform,
 }
 
+impl VoxelModel {
+    /// Returns `true` if the `/`-separated model or group names in `path` appear consecutively in this model's path.
+    pub fn matches(&self, path: &str) -> bool {
+        let segments: Vec<_> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        !segments.is_empty()
+            && self
+                .path
+                .windows(segments.len())
+                .any(|window| window.iter().zip(&segments).all(|(a, b)| a == b))
+    }
+
+    /// Returns the bounding box of this model's meshes in scene space.
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        self.meshes
+            .iter()
+            .filter_map(|lit_mesh| {
+                let (min, max) = lit_mesh.stats.bounds?;
+                let transform = self.transform * lit_mesh.transform;
+                let a = transform.transform_point(min);
+                let b = transform.transform_point(max);
+                Some((a.min(b), a.max(b)))
+            })
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+    }
+}
+
 #[derive(Debug, Asset, TypePath)]
 pub struct VoxelScene {
-    pub meshes: Vec<LitMesh>,
+    pub models: Vec<VoxelModel>,
     pub material: VoxelMaterial,
+    /// The labeled sub-asset of `material`, if this scene was loaded by [`SceneLoader`].
+    pub material_handle: Option<Handle<VoxelMaterial>>,
+}
+
+/// A model entity spawned from a [`VoxelModel`].
+#[derive(Clone, Debug, Component)]
+pub struct VoxelSceneModel {
+    pub name: Option<String>,
+    /// The transform of the model in the scene asset.
+    ///
+    /// On reload, models whose [`Transform`] differs from this are treated as moved by the user.
+    pub authored_transform: Transform,
+}
+
+/// A chunk mesh entity of a [`VoxelSceneModel`], which is replaced on reload.
+#[derive(Clone, Component)]
+pub struct VoxelSceneChunk {
+    /// The voxels the chunk's mesh was built from, if the scene kept them (see [`LitMesh::voxels`]).
+    ///
+    /// Picking and editing need these voxels.
+    pub voxels: Option<Arc<AssetVoxelChunk>>,
+    /// The model coordinates of the chunk's first voxel, including padding.
+    pub origin: IVec3,
+}
+
+impl VoxelSceneChunk {
+    /// Returns the voxels of this chunk for editing, copying them if they're shared with other instances of the scene.
+    ///
+    /// The chunk is remeshed after its voxels are borrowed.
+    pub fn voxels_mut(&mut self) -> Option<&mut AssetVoxelChunk> {
+        self.voxels.as_mut().map(Arc::make_mut)
+    }
+}
+
+/// Controls how a spawned scene is updated when its [`VoxelScene`] is modified.
+#[derive(Clone, Debug, Component)]
+pub struct VoxelSceneReloadSettings {
+    /// Keep the transforms of models that were moved since they were spawned, instead of resetting them to the new authored transforms.
+    pub preserve_transforms: bool,
+}
+
+impl Default for VoxelSceneReloadSettings {
+    fn default() -> Self {
+        Self {
+            preserve_transforms: true,
+        }
+    }
 }
 
 impl VoxelScene {
+    /// Mesh `models` into a scene coloured by `material`, e.g. to build scenes from voxels created at runtime.
+    ///
+    /// If `packed` is set, meshes are built with [`Chunk::build_packed`](crate::Chunk::build_packed).
+    /// The voxels are kept in [`LitMesh::voxels`].
+    pub fn from_models(
+        models: impl IntoIterator<Item = AssetModel>,
+        material: VoxelMaterial,
+        packed: bool,
+    ) -> Self {
+        let models = models
+            .into_iter()
+            .map(|asset_model| VoxelModel {
+                meshes: asset_model
+                    .chunks
+                    .into_iter()
+                    .map(|asset_chunk| {
+                        build_lit_mesh(asset_chunk, &material.emissions, packed, true)
+                    })
+                    .collect(),
+                name: asset_model.name,
+                path: asset_model.path,
+                transform: asset_model.transform,
+            })
+            .collect();
+
+        Self {
+            models,
+            material,
+            material_handle: None,
+        }
+    }
+
+    /// Returns the label of each model's sub-assets: its name, or its index if it is unnamed or its name is taken.
+    ///
+    /// Named models keep their name even if it's another model's index, e.g. `"0"`,
+    /// in which case that model's index is suffixed with `_` until it's unique.
+    pub fn model_labels(&self) -> Vec<String> {
+        let mut labels = HashSet::new();
+        let names: Vec<_> = self
+            .models
+            .iter()
+            .map(|model| {
+                model
+                    .name
+                    .clone()
+                    .filter(|name| labels.insert(name.clone()))
+            })
+            .collect();
+
+        names
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                name.unwrap_or_else(|| {
+                    let mut label = idx.to_string();
+                    while !labels.insert(label.clone()) {
+                        label.push('_');
+                    }
+                    label
+                })
+            })
+            .collect()
+    }
+
+    /// Register the material as `Material` and each model's meshes as `Model/{label}`,
+    /// or `Model/{label}/{chunk}` if the model is split into chunks.
+    pub(crate) fn add_labeled_assets(&mut self, load_context: &mut LoadContext) {
+        self.material_handle =
+            Some(load_context.add_labeled_asset("Material".to_string(), self.material.clone()));
+
+        for (label, model) in self.model_labels().into_iter().zip(&mut self.models) {
+            let is_chunked = model.meshes.len() > 1;
+            for (chunk_idx, lit_mesh) in model.meshes.iter_mut().enumerate() {
+                let label = if is_chunked {
+                    format!("Model/{label}/{chunk_idx}")
+                } else {
+                    format!("Model/{label}")
+                };
+                if let Some(mesh) = lit_mesh.mesh.take() {
+                    lit_mesh.handle = Some(load_context.add_labeled_asset(label, mesh));
+                }
+            }
+        }
+    }
+
+    /// Returns the models matching `selection` (or every model) with their meshes and spawned transforms.
+    ///
+    /// Models are offset to be centered on the entity if the selection is re-centered.
+    fn select<'a>(
+        &'a self,
+        meshes: &'a [Vec<Handle<Mesh>>],
+        selection: Option<&VoxelSceneModelRef>,
+    ) -> Vec<(&'a VoxelModel, &'a [Handle<Mesh>], Transform)> {
+        let selected: Vec<_> = self
+            .models
+            .iter()
+            .zip(meshes)
+            .filter(|(model, _)| selection.is_none_or(|selection| model.matches(&selection.path)))
+            .collect();
+
+        let offset = selection
+            .filter(|selection| selection.recenter)
+            .and_then(|_| {
+                selected
+                    .iter()
+                    .filter_map(|(model, _)| model.bounds())
+                    .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+            })
+            .map(|(min, max)| -(min + max) / 2.)
+            .unwrap_or_default();
+
+        selected
+            .into_iter()
+            .map(|(model, model_meshes)| {
+                let transform = model
+                    .transform
+                    .with_translation(model.transform.translation + offset);
+                (model, model_meshes.as_slice(), transform)
+            })
+            .collect()
+    }
+
+    /// Spawn the models of this scene as children of `entity_commands`.
+    ///
+    /// If `selection` is set, only models matching its path are spawned, optionally re-centered on the entity.
     fn spawn(
         &self,
         mut entity_commands: EntityCommands,
         material: Handle<VoxelMaterial>,
-        meshes: &[Handle<Mesh>],
+        meshes: &[Vec<Handle<Mesh>>],
+        selection: Option<&VoxelSceneModelRef>,
     ) {
+        let root = entity_commands.id();
         let mut entities = HashMap::new();
 
-        entity_commands.with_children(|parent| {
-            for (idx, lit_mesh) in self.meshes.iter().enumerate() {
-                let entity = parent
-                    .spawn_empty()
-                    .with_children(|parent| {
-                        for light in &lit_mesh.lights {
-                            parent.spawn((
-                                PointLight {
-                                    intensity: light.intensity * 100_000.,
-                                    range: 10.,
-                                    ..default()
-                                },
-                                Transform::from_translation(light.origin),
-                            ));
+        for (model, model_meshes, transform) in self.select(meshes, selection) {
+            let entity = spawn_model(
+                entity_commands.commands(),
+                root,
+                model,
+                transform,
+                &material,
+                model_meshes,
+            );
+
+            if let Some(name) = &model.name {
+                entities.insert(name.clone(), entity);
+            }
+        }
+
+        entity_commands.insert(VoxelSceneModels { entities });
+    }
+
+    /// Update the children of a spawned scene in place, matching models by name.
+    ///
+    /// Named models keep their entity and any components added to it, unnamed and removed models are despawned,
+    /// and new models are spawned. `selection` is the [`VoxelSceneModelRef`] the scene was spawned with, if any.
+    fn respawn(
+        &self,
+        mut commands: Commands,
+        root: Entity,
+        material_meshes: &MaterialMeshes,
+        selection: Option<&VoxelSceneModelRef>,
+        preserve_transforms: bool,
+        hierarchy: &SceneHierarchy,
+    ) {
+        let mut kept = HashSet::new();
+        let mut entities = HashMap::new();
+
+        let material = &material_meshes.material;
+        for (model, model_meshes, authored_transform) in
+            self.select(&material_meshes.meshes, selection)
+        {
+            let existing = model
+                .name
+                .as_ref()
+                .and_then(|name| hierarchy.models_query.get(root).ok()?.entities.get(name))
+                .copied()
+                .filter(|entity| !kept.contains(entity));
+
+            let entity = match existing
+                .and_then(|entity| Some((entity, hierarchy.model_query.get(entity).ok()?)))
+            {
+                Some((entity, (transform, scene_model))) => {
+                    for child in hierarchy.children_query.relationship_sources(entity) {
+                        if hierarchy.chunk_query.contains(child) {
+                            commands.entity(child).despawn();
                         }
-                    })
-                    .insert((
-                        MeshMaterial3d(material.clone()),
-                        Mesh3d(meshes[idx].clone()),
-                        lit_mesh.transform,
-                    ))
-                    .id();
-
-                if let Some(name) = &lit_mesh.name {
-                    entities.insert(name.clone(), entity);
+                    }
+
+                    let mut entity_commands = commands.entity(entity);
+                    if !preserve_transforms || *transform == scene_model.authored_transform {
+                        entity_commands.insert(authored_transform);
+                    }
+                    entity_commands
+                        .insert(VoxelSceneModel {
+                            name: model.name.clone(),
+                            authored_transform,
+                        })
+                        .with_children(|parent| {
+                            spawn_chunks(parent, model, material, model_meshes);
+                        });
+
+                    kept.insert(entity);
+                    entity
                 }
+                None => spawn_model(
+                    commands.reborrow(),
+                    root,
+                    model,
+                    authored_transform,
+                    material,
+                    model_meshes,
+                ),
+            };
+
+            if let Some(name) = &model.name {
+                entities.insert(name.clone(), entity);
+            }
+        }
+
+        for child in hierarchy.children_query.relationship_sources(root) {
+            if hierarchy.model_query.contains(child) && !kept.contains(&child) {
+                commands.entity(child).despawn();
             }
-        });
+        }
 
-        entity_commands.insert(VoxelSceneModels { entities });
+        commands.entity(root).insert(VoxelSceneModels { entities });
+    }
+}
+
+fn spawn_model(
+    mut commands: Commands,
+    root: Entity,
+    model: &VoxelModel,
+    transform: Transform,
+    material: &Handle<VoxelMaterial>,
+    meshes: &[Handle<Mesh>],
+) -> Entity {
+    commands
+        .spawn((
+            ChildOf(root),
+            transform,
+            Visibility::default(),
+            VoxelSceneModel {
+                name: model.name.clone(),
+                authored_transform: transform,
+            },
+        ))
+        .with_children(|parent| spawn_chunks(parent, model, material, meshes))
+        .id()
+}
+
+fn spawn_chunks(
+    parent: &mut ChildSpawnerCommands,
+    model: &VoxelModel,
+    material: &Handle<VoxelMaterial>,
+    meshes: &[Handle<Mesh>],
+) {
+    for (lit_mesh, mesh) in model.meshes.iter().zip(meshes) {
+        let mut chunk = parent.spawn((
+            VoxelSceneChunk {
+                voxels: lit_mesh.voxels.clone(),
+                origin: lit_mesh.transform.translation.as_ivec3() - IVec3::ONE,
+            },
+            MeshMaterial3d(material.clone()),
+            Mesh3d(mesh.clone()),
+            lit_mesh.transform,
+        ));
+        // Packed meshes have no positions to compute the bounding box from.
+        if let Some(aabb) = lit_mesh.aabb() {
+            chunk.insert(aabb);
+        }
+
+        chunk.with_children(|parent| spawn_lights(parent, &lit_mesh.lights));
+    }
+}
+
+fn spawn_lights(parent: &mut ChildSpawnerCommands, lights: &[VoxelLight]) {
+    for light in lights {
+        parent.spawn((
+            PointLight {
+                intensity: light.intensity * 100_000.,
+                range: 10.,
+                ..default()
+            },
+            Transform::from_translation(light.origin),
+        ));
     }
 }
 
+/// Settings for loading a [`VoxelScene`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "picking"), derive(Default))]
+#[serde(default)]
+pub struct SceneLoaderSettings {
+    /// Split each model into chunks of at most `chunk_size`³ voxels, each spawned as a separate entity.
+    ///
+    /// Smaller chunks can be frustum-culled individually. If `None`, each model is meshed as a single chunk.
+    pub chunk_size: Option<u32>,
+    /// Build compact meshes with [`Chunk::build_packed`](crate::Chunk::build_packed).
+    pub packed_vertices: bool,
+    /// Keep the voxels of each chunk in [`LitMesh::voxels`] after meshing, for picking and editing spawned chunks.
+    ///
+    /// This roughly doubles the memory used by the scene. Defaults to `true` with the `picking` feature.
+    pub keep_voxels: bool,
+}
+
+// Picking needs the voxels of loaded chunks.
+#[cfg(feature = "picking")]
+impl Default for SceneLoaderSettings {
+    fn default() -> Self {
+        Self {
+            chunk_size: None,
+            packed_vertices: false,
+            keep_voxels: true,
+        }
+    }
+}
+
+/// Loads `.vox` files as a [`VoxelScene`].
+///
+/// The scene's material is also available as the `Material` sub-asset, and each model's meshes as `Model/{label}`
+/// (see [`VoxelScene::model_labels`]), e.g. `character.vox#Model/right_arm`.
 #[derive(Default)]
 pub struct SceneLoader;
 
 impl AssetLoader for SceneLoader {
     type Asset = VoxelScene;
 
-    type Settings = ();
+    type Settings = SceneLoaderSettings;
 
     type Error = Box<dyn std::error::Error + Send + Sync>;
 
@@ -103,103 +525,282 @@ impl AssetLoader for SceneLoader {
         settings: &Self::Settings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
-        let asset = VoxAssetLoader.load(reader, settings, load_context).await?;
+        let asset = VoxAssetLoader.load(reader, &(), load_context).await?;
 
         let material = asset.material();
 
         let emissions = Arc::new(material.emissions);
-        let chunks: Vec<_> = asset.chunks().collect();
+        let asset_models: Vec<_> = asset.models(settings.chunk_size).collect();
+        let packed = settings.packed_vertices;
+        let keep_voxels = settings.keep_voxels;
 
-        let meshes = future::join_all(chunks.into_iter().map(|asset_chunk| {
+        let models = future::join_all(asset_models.into_iter().map(|asset_model| {
             let emissions = emissions.clone();
 
-            smol::unblock(move || {
-                let mesh = asset_chunk.chunk.build();
-
-                // TODO check positions
-                let mut lights = Vec::new();
-                for (idx, voxel) in asset_chunk.chunk.voxels.iter().enumerate() {
-                    let emissive = emissions[voxel.idx as usize];
-
-                    let [x, y, z] = asset_chunk
-                        .chunk
-                        .shape
-                        .delinearize(idx as _)
-                        .map(|n| n as f32);
-
-                    if emissive.x > 0. {
-                        lights.push(VoxelLight {
-                            origin: Vec3::new(x, y, z),
-                            intensity: emissive.x,
-                        });
-                    }
-                }
+            async move {
+                let meshes = future::join_all(asset_model.chunks.into_iter().map(|asset_chunk| {
+                    let emissions = emissions.clone();
+                    smol::unblock(move || {
+                        build_lit_mesh(asset_chunk, &emissions, packed, keep_voxels)
+                    })
+                }))
+                .await;
 
-                LitMesh {
-                    mesh,
-                    lights,
-                    name: asset_chunk.name,
-                    transform: asset_chunk.transform,
+                VoxelModel {
+                    meshes,
+                    name: asset_model.name,
+                    path: asset_model.path,
+                    transform: asset_model.transform,
                 }
-            })
+            }
         }))
         .await;
 
-        Ok(VoxelScene { meshes, material })
+        let mut scene = VoxelScene {
+            models,
+            material,
+            material_handle: None,
+        };
+        scene.add_labeled_assets(load_context);
+        Ok(scene)
+    }
+}
+
+fn build_lit_mesh(
+    asset_chunk: AssetChunk,
+    emissions: &[Vec3; 256],
+    packed: bool,
+    keep_voxels: bool,
+) -> LitMesh {
+    let chunk = &asset_chunk.chunk;
+    let mesh = if packed {
+        chunk.build_packed()
+    } else {
+        chunk.build()
+    };
+
+    LitMesh {
+        stats: MeshStats::from_mesh(&mesh),
+        mesh: Some(mesh),
+        lights: chunk_lights(chunk, emissions),
+        voxels: keep_voxels.then(|| Arc::new(asset_chunk.chunk)),
+        handle: None,
+        transform: asset_chunk.transform,
+    }
+}
+
+/// Returns a light for each emissive voxel of `chunk`, in chunk space.
+fn chunk_lights(chunk: &AssetVoxelChunk, emissions: &[Vec3; 256]) -> Vec<VoxelLight> {
+    emissive_lights(chunk, |voxel| match voxel.idx {
+        0 => 0.,
+        idx => emissions[idx as usize].x,
+    })
+}
+
+/// Returns a light for each voxel inside `chunk` with a positive `emission`, in chunk space.
+pub(crate) fn emissive_lights<V, VS, S>(
+    chunk: &Chunk<V, VS, S>,
+    emission: impl Fn(&V) -> f32,
+) -> Vec<VoxelLight>
+where
+    VS: AsRef<[V]>,
+    S: Shape<3, Coord = u32>,
+{
+    // TODO check positions
+    let mut lights = Vec::new();
+    for (idx, voxel) in chunk.voxels.as_ref().iter().enumerate() {
+        let pos = UVec3::from(chunk.shape.delinearize(idx as _));
+
+        // Padding voxels belong to neighbouring chunks.
+        if pos.cmple(chunk.min).any() || pos.cmpge(chunk.max).any() {
+            continue;
+        }
+
+        let intensity = emission(voxel);
+        if intensity > 0. {
+            lights.push(VoxelLight {
+                origin: pos.as_vec3(),
+                intensity,
+            });
+        }
     }
+    lights
 }
 
+#[derive(Clone)]
 struct MaterialMeshes {
     material: Handle<VoxelMaterial>,
-    meshes: Vec<Handle<Mesh>>,
+    meshes: Vec<Vec<Handle<Mesh>>>,
 }
 
+/// Meshes and materials shared by the spawned instances of each scene, released once no instances remain.
 #[derive(Default, Resource)]
 pub struct LoadedAssets {
     assets: HashMap<AssetId<VoxelScene>, MaterialMeshes>,
 }
 
+impl LoadedAssets {
+    /// Returns the shared material spawned for the scene `id`, if it has been spawned.
+    pub fn material(&self, id: AssetId<VoxelScene>) -> Option<&Handle<VoxelMaterial>> {
+        self.assets
+            .get(&id)
+            .map(|material_meshes| &material_meshes.material)
+    }
+}
+
+/// The scene assets and the meshes and materials added for them.
+#[derive(SystemParam)]
+pub struct SceneAssets<'w> {
+    scenes: Res<'w, Assets<VoxelScene>>,
+    loaded_assets: ResMut<'w, LoadedAssets>,
+    materials: ResMut<'w, Assets<VoxelMaterial>>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+}
+
+impl SceneAssets<'_> {
+    /// Add the meshes and material of scene `id` if they haven't been added yet, or if `replace` is set.
+    fn add(&mut self, id: AssetId<VoxelScene>, replace: bool) -> Option<MaterialMeshes> {
+        let scene = self.scenes.get(id)?;
+        if replace || !self.loaded_assets.assets.contains_key(&id) {
+            let material_meshes = MaterialMeshes {
+                material: scene
+                    .material_handle
+                    .clone()
+                    .unwrap_or_else(|| self.materials.add(scene.material.clone())),
+                meshes: add_meshes(scene, &mut self.meshes),
+            };
+            self.loaded_assets.assets.insert(id, material_meshes);
+        }
+        self.loaded_assets.assets.get(&id).cloned()
+    }
+
+    /// Replace the meshes and material of scene `id` if they were added for spawned instances.
+    fn reload(&mut self, id: AssetId<VoxelScene>) -> Option<MaterialMeshes> {
+        if self.loaded_assets.assets.contains_key(&id) {
+            self.add(id, true)
+        } else {
+            None
+        }
+    }
+}
+
+/// Queries over the entities of spawned scenes.
+#[derive(SystemParam)]
+pub struct SceneHierarchy<'w, 's> {
+    models_query: Query<'w, 's, &'static VoxelSceneModels>,
+    model_query: Query<'w, 's, (&'static Transform, &'static VoxelSceneModel)>,
+    chunk_query: Query<'w, 's, (), With<VoxelSceneChunk>>,
+    children_query: Query<'w, 's, &'static Children>,
+}
+
 #[derive(Component)]
 pub struct Loaded;
 
+/// Marker for scene entities whose asset failed to load, so the failure is only reported once.
+#[derive(Component)]
+pub struct LoadFailed;
+
+/// Triggered when a [`VoxelScene`] has been spawned as children of `entity` for the first time.
+#[derive(Clone, Debug, EntityEvent)]
+pub struct VoxelSceneReady {
+    pub entity: Entity,
+    pub handle: Handle<VoxelScene>,
+}
+
+/// Triggered when the children of `entity` have been updated after its [`VoxelScene`] was modified.
+#[derive(Clone, Debug, EntityEvent)]
+pub struct VoxelSceneReloaded {
+    pub entity: Entity,
+    pub handle: Handle<VoxelScene>,
+}
+
+/// Triggered when the [`VoxelScene`] of `entity` fails to load.
+#[derive(Clone, Debug, EntityEvent)]
+pub struct VoxelSceneLoadFailed {
+    pub entity: Entity,
+    pub handle: Handle<VoxelScene>,
+    pub error: Arc<AssetLoadError>,
+}
+
 #[derive(Clone, Component)]
 pub struct VoxelSceneHandle(pub Handle<VoxelScene>);
 
+/// Spawn only the models of a [`VoxelScene`] under a model or group name, as children of this entity.
+///
+/// Meshes are shared with every other instance of the scene. Models are updated in place when the scene is modified,
+/// like the models of a [`VoxelSceneHandle`].
+///
+/// An entity can't have both a [`VoxelSceneHandle`] and a `VoxelSceneModelRef`: the model ref is ignored with a warning.
+#[derive(Clone, Debug, Component)]
+pub struct VoxelSceneModelRef {
+    pub handle: Handle<VoxelScene>,
+    /// Model or group names separated by `/`, e.g. `"barrel"` or `"tree_03/trunk"`. See [`VoxelModel::matches`].
+    pub path: String,
+    /// Offset the models so the center of their bounding box is at this entity's origin.
+    pub recenter: bool,
+}
+
+impl VoxelSceneModelRef {
+    pub fn new(handle: Handle<VoxelScene>, path: impl Into<String>) -> Self {
+        Self {
+            handle,
+            path: path.into(),
+            recenter: false,
+        }
+    }
+
+    /// Re-center the spawned models on this entity.
+    pub fn recentered(mut self) -> Self {
+        self.recenter = true;
+        self
+    }
+}
+
+/// Query data for an entity spawned with a [`VoxelSceneHandle`] or a [`VoxelSceneModelRef`].
+pub(crate) type SpawnedScene = AnyOf<(&'static VoxelSceneHandle, &'static VoxelSceneModelRef)>;
+
+/// Returns the scene of an entity matched by [`SpawnedScene`].
+pub(crate) fn spawned_scene_id(
+    (handle, model_ref): (Option<&VoxelSceneHandle>, Option<&VoxelSceneModelRef>),
+) -> Option<AssetId<VoxelScene>> {
+    handle
+        .map(|handle| handle.0.id())
+        .or_else(|| model_ref.map(|model_ref| model_ref.handle.id()))
+}
+
 pub fn load_scenes(
     mut commands: Commands,
-    query: Query<(Entity, &VoxelSceneHandle), Without<Loaded>>,
+    query: Query<(Entity, &VoxelSceneHandle, Has<LoadFailed>), Without<Loaded>>,
     asset_server: Res<AssetServer>,
-    vox_assets: Res<Assets<VoxelScene>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<VoxelMaterial>>,
-    mut loaded_assets: ResMut<LoadedAssets>,
+    mut scene_assets: SceneAssets,
 ) {
-    for (entity, handle) in &query {
-        if asset_server.load_state(&handle.0).is_loaded() {
-            let scene = vox_assets.get(&handle.0).unwrap();
-
-            if !loaded_assets.assets.contains_key(&handle.0.id()) {
-                loaded_assets.assets.insert(
-                    handle.0.id(),
-                    MaterialMeshes {
-                        material: materials.add(scene.material.clone()),
-                        meshes: scene
-                            .meshes
-                            .iter()
-                            .map(|lit_mesh| meshes.add(lit_mesh.mesh.clone()))
-                            .collect(),
-                    },
-                );
-            }
+    for (entity, handle, load_failed) in &query {
+        if load_failed {
+            continue;
+        }
 
-            commands.entity(entity).insert(Loaded);
+        let load_state = asset_server.load_state(&handle.0);
+        if let LoadState::Failed(error) = load_state {
+            commands.entity(entity).insert(LoadFailed);
+            commands.trigger(VoxelSceneLoadFailed {
+                entity,
+                handle: handle.0.clone(),
+                error,
+            });
+        } else if let Some(material_meshes) = scene_assets.add(handle.0.id(), false) {
+            let scene = scene_assets.scenes.get(&handle.0).unwrap();
 
-            let material_meshes = &loaded_assets.assets.get(&handle.0.id()).unwrap();
+            commands.entity(entity).insert(Loaded);
             scene.spawn(
                 commands.entity(entity),
-                material_meshes.material.clone(),
+                material_meshes.material,
                 &material_meshes.meshes,
+                None,
             );
+            commands.trigger(VoxelSceneReady {
+                entity,
+                handle: handle.0.clone(),
+            });
         }
     }
 }
@@ -207,43 +808,324 @@ pub fn load_scenes(
 pub fn handle_scene_events(
     mut commands: Commands,
     mut events: MessageReader<AssetEvent<VoxelScene>>,
-    scenes: Res<Assets<VoxelScene>>,
-    query: Query<(Entity, &VoxelSceneHandle), With<Loaded>>,
-    mut loaded_assets: ResMut<LoadedAssets>,
-    mut materials: ResMut<Assets<VoxelMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<(Entity, &VoxelSceneHandle, Option<&VoxelSceneReloadSettings>), With<Loaded>>,
+    mut scene_assets: SceneAssets,
+    hierarchy: SceneHierarchy,
 ) {
     for event in events.read() {
         if let AssetEvent::Modified { id } = event {
-            for (entity, handle) in &query {
+            // Scenes without spawned instances are meshed when they're first spawned.
+            let Some(material_meshes) = scene_assets.reload(*id) else {
+                continue;
+            };
+            let scene = scene_assets.scenes.get(*id).unwrap();
+
+            for (entity, handle, settings) in &query {
                 if handle.0.id() == *id {
-                    let scene = scenes.get(&handle.0).unwrap();
-
-                    commands
-                        .entity(entity)
-                        .despawn_children()
-                        .remove::<VoxelSceneModels>();
-
-                    loaded_assets.assets.insert(
-                        handle.0.id(),
-                        MaterialMeshes {
-                            material: materials.add(scene.material.clone()),
-                            meshes: scene
-                                .meshes
-                                .iter()
-                                .map(|lit_mesh| meshes.add(lit_mesh.mesh.clone()))
-                                .collect(),
-                        },
+                    scene.respawn(
+                        commands.reborrow(),
+                        entity,
+                        &material_meshes,
+                        None,
+                        settings.is_none_or(|settings| settings.preserve_transforms),
+                        &hierarchy,
                     );
+                    commands.trigger(VoxelSceneReloaded {
+                        entity,
+                        handle: handle.0.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn load_model_refs(
+    mut commands: Commands,
+    query: Query<
+        (
+            Entity,
+            Ref<VoxelSceneModelRef>,
+            Has<VoxelSceneHandle>,
+            Has<LoadFailed>,
+        ),
+        Without<Loaded>,
+    >,
+    asset_server: Res<AssetServer>,
+    mut scene_assets: SceneAssets,
+) {
+    for (entity, model_ref, has_handle, load_failed) in &query {
+        if has_handle {
+            if model_ref.is_added() {
+                warn!(
+                    "{entity} has both a VoxelSceneHandle and a VoxelSceneModelRef, so the model ref is ignored. \
+                    Spawn the model ref on a separate entity."
+                );
+            }
+            continue;
+        }
+        if load_failed {
+            continue;
+        }
+
+        if let LoadState::Failed(error) = asset_server.load_state(&model_ref.handle) {
+            commands.entity(entity).insert(LoadFailed);
+            commands.trigger(VoxelSceneLoadFailed {
+                entity,
+                handle: model_ref.handle.clone(),
+                error,
+            });
+        } else if let Some(material_meshes) = scene_assets.add(model_ref.handle.id(), false) {
+            let scene = scene_assets.scenes.get(&model_ref.handle).unwrap();
 
-                    let material_meshes = &loaded_assets.assets.get(&handle.0.id()).unwrap();
-                    scene.spawn(
-                        commands.entity(entity),
-                        material_meshes.material.clone(),
-                        &material_meshes.meshes,
+            commands.entity(entity).insert(Loaded);
+            scene.spawn(
+                commands.entity(entity),
+                material_meshes.material,
+                &material_meshes.meshes,
+                Some(&model_ref),
+            );
+            commands.trigger(VoxelSceneReady {
+                entity,
+                handle: model_ref.handle.clone(),
+            });
+        }
+    }
+}
+
+/// Update the models of [`VoxelSceneModelRef`]s whose scene was modified, like the models of a [`VoxelSceneHandle`].
+#[allow(clippy::type_complexity)]
+pub fn handle_model_ref_events(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<VoxelScene>>,
+    query: Query<
+        (
+            Entity,
+            &VoxelSceneModelRef,
+            Option<&VoxelSceneReloadSettings>,
+        ),
+        (With<Loaded>, Without<VoxelSceneHandle>),
+    >,
+    scene_assets: SceneAssets,
+    hierarchy: SceneHierarchy,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event {
+            // The cached assets were replaced by `handle_scene_events`.
+            let Some(material_meshes) = scene_assets.loaded_assets.assets.get(id).cloned() else {
+                continue;
+            };
+            let Some(scene) = scene_assets.scenes.get(*id) else {
+                continue;
+            };
+
+            for (entity, model_ref, settings) in &query {
+                if model_ref.handle.id() == *id {
+                    scene.respawn(
+                        commands.reborrow(),
+                        entity,
+                        &material_meshes,
+                        Some(model_ref),
+                        settings.is_none_or(|settings| settings.preserve_transforms),
+                        &hierarchy,
                     );
+                    commands.trigger(VoxelSceneReloaded {
+                        entity,
+                        handle: model_ref.handle.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Release the cached meshes and material of scenes that are no longer spawned or have been unloaded.
+pub fn remove_unused_scene_assets(
+    mut events: MessageReader<AssetEvent<VoxelScene>>,
+    mut removed: RemovedComponents<VoxelSceneHandle>,
+    mut removed_refs: RemovedComponents<VoxelSceneModelRef>,
+    query: Query<&VoxelSceneHandle>,
+    ref_query: Query<&VoxelSceneModelRef>,
+    mut loaded_assets: ResMut<LoadedAssets>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Removed { id } | AssetEvent::Unused { id } = event {
+            loaded_assets.assets.remove(id);
+        }
+    }
+
+    if removed.read().count() + removed_refs.read().count() > 0 {
+        let used: HashSet<_> = query
+            .iter()
+            .map(|handle| handle.0.id())
+            .chain(ref_query.iter().map(|model_ref| model_ref.handle.id()))
+            .collect();
+        loaded_assets.assets.retain(|id, _| used.contains(id));
+    }
+}
+
+fn add_meshes(scene: &VoxelScene, meshes: &mut Assets<Mesh>) -> Vec<Vec<Handle<Mesh>>> {
+    scene
+        .models
+        .iter()
+        .map(|model| {
+            model
+                .meshes
+                .iter()
+                .map(|lit_mesh| {
+                    lit_mesh.handle.clone().unwrap_or_else(|| {
+                        meshes.add(lit_mesh.mesh.clone().unwrap_or_else(|| {
+                            Mesh::new(
+                                PrimitiveTopology::TriangleList,
+                                RenderAssetUsages::default(),
+                            )
+                        }))
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Copy the border voxels of edited chunks into the padding of the neighbouring chunks of their model,
+/// so the faces between them are remeshed by [`remesh_edited_chunks`].
+pub fn sync_chunk_padding(mut query: Query<(Entity, &mut VoxelSceneChunk, &ChildOf)>) {
+    let edited: Vec<_> = query
+        .iter_mut()
+        .filter(|(_, chunk, _)| chunk.is_changed() && !chunk.is_added())
+        .filter_map(|(entity, chunk, child_of)| {
+            Some((
+                entity,
+                child_of.parent(),
+                chunk.origin,
+                chunk.voxels.clone()?,
+            ))
+        })
+        .collect();
+    if edited.is_empty() {
+        return;
+    }
+
+    // Chunks are split on a grid of the largest chunk size of their model.
+    let mut models: HashMap<Entity, (UVec3, HashMap<IVec3, Entity>)> = HashMap::new();
+    for (_, chunk, child_of) in &query {
+        if let Some(voxels) = &chunk.voxels {
+            let (chunk_size, _) = models.entry(child_of.parent()).or_default();
+            *chunk_size = chunk_size.max(voxels.max - voxels.min - UVec3::ONE);
+        }
+    }
+    for (entity, chunk, child_of) in &query {
+        let Some((chunk_size, grid)) = models.get_mut(&child_of.parent()) else {
+            continue;
+        };
+        let origin = chunk.origin + IVec3::ONE;
+        grid.insert(
+            origin.div_euclid(chunk_size.max(UVec3::ONE).as_ivec3()),
+            entity,
+        );
+    }
+
+    for (entity, parent, origin, voxels) in edited {
+        let (chunk_size, grid) = &models[&parent];
+        let bounds = EditBounds {
+            min: origin + voxels.min.as_ivec3() + IVec3::ONE,
+            max: origin + voxels.max.as_ivec3(),
+        };
+
+        for neighbour in bounds.chunks(*chunk_size).filter_map(|pos| grid.get(&pos)) {
+            if *neighbour == entity {
+                continue;
+            }
+            let Ok((_, mut chunk, _)) = query.get_mut(*neighbour) else {
+                continue;
+            };
+            let Some(neighbour_voxels) = &chunk.voxels else {
+                continue;
+            };
+
+            // The edited chunk's border overlaps the neighbour's padding.
+            let size = UVec3::from(neighbour_voxels.shape.as_array()).as_ivec3();
+            let min = bounds.min.max(chunk.origin);
+            let max = bounds.max.min(chunk.origin + size);
+            let neighbour_origin = chunk.origin;
+            let changes: Vec<_> = crate::edit::positions(min, max)
+                .filter_map(|pos| {
+                    let from = voxels.shape.linearize((pos - origin).as_uvec3().into());
+                    let to = neighbour_voxels
+                        .shape
+                        .linearize((pos - neighbour_origin).as_uvec3().into());
+                    let voxel = voxels.voxels[from as usize];
+                    (neighbour_voxels.voxels[to as usize] != voxel).then_some((to, voxel))
+                })
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+
+            // Only borrow the voxels mutably if they changed, so unchanged neighbours aren't remeshed.
+            let neighbour_voxels = chunk.voxels_mut().unwrap();
+            for (idx, voxel) in changes {
+                neighbour_voxels.voxels[idx as usize] = voxel;
+            }
+        }
+    }
+}
+
+/// Rebuild the meshes and lights of chunks whose voxels were edited with [`VoxelSceneChunk::voxels_mut`].
+///
+/// Edited chunks get their own mesh, so other instances of the scene are unaffected.
+#[allow(clippy::type_complexity)]
+pub fn remesh_edited_chunks(
+    mut commands: Commands,
+    query: Query<
+        (
+            Entity,
+            Ref<VoxelSceneChunk>,
+            &Mesh3d,
+            Option<&MeshMaterial3d<VoxelMaterial>>,
+            Option<&Children>,
+        ),
+        Changed<VoxelSceneChunk>,
+    >,
+    light_query: Query<(), With<PointLight>>,
+    materials: Res<Assets<VoxelMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (entity, chunk, mesh, material, children) in &query {
+        if chunk.is_added() {
+            continue;
+        }
+        let Some(voxels) = &chunk.voxels else {
+            continue;
+        };
+
+        let packed = meshes
+            .get(&mesh.0)
+            .is_some_and(|mesh| mesh.contains_attribute(ATTRIBUTE_PACKED_VOXEL));
+        let mesh = if packed {
+            voxels.build_packed()
+        } else {
+            voxels.build()
+        };
+
+        let material = material.and_then(|material| materials.get(&material.0));
+        if material.is_some() {
+            for light in children.into_iter().flatten() {
+                if light_query.contains(*light) {
+                    commands.entity(*light).despawn();
                 }
             }
         }
+
+        let mut entity_commands = commands.entity(entity);
+        if let Some(aabb) = packed_aabb(&mesh).or_else(|| mesh.compute_aabb()) {
+            entity_commands.insert(aabb);
+        }
+        entity_commands.insert(Mesh3d(meshes.add(mesh)));
+        if let Some(material) = material {
+            let lights = chunk_lights(voxels, &material.emissions);
+            entity_commands.with_children(|parent| spawn_lights(parent, &lights));
+        }
     }
 }
@@ -0,0 +1,245 @@
+use crate::{ATTRIBUTE_COLOR_INDEX, Chunk};
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, MeshVertexAttribute, VertexAttributeValues},
+    prelude::*,
+    render::render_resource::{PrimitiveTopology, VertexFormat},
+};
+use ndshape::Shape;
+
+/// The weight of the second palette index of each vertex of a smooth mesh, from `0.` to `1.`.
+///
+/// Meshes built with [`Chunk::build_smooth`] store two palette indices in [`ATTRIBUTE_COLOR_INDEX`],
+/// the first in bits 0-7 and the second in bits 8-15.
+/// [`VoxelMaterial`](crate::VoxelMaterial) mixes their colours by this weight when the attribute is present.
+pub const ATTRIBUTE_BLEND_WEIGHT: MeshVertexAttribute =
+    MeshVertexAttribute::new("BlendWeight", 988940921, VertexFormat::Float32);
+
+/// A voxel of a density field, meshed as a smooth surface with [`Chunk::build_smooth`].
+pub trait SmoothVoxel {
+    /// Returns the signed distance from the voxel to the surface, negative inside.
+    fn distance(&self) -> f32;
+
+    /// Returns the palette index of the voxel's material.
+    fn palette_index(&self) -> u8;
+}
+
+/// A sample of a signed distance field with a palette index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SdfVoxel {
+    pub distance: f32,
+    pub palette_index: u8,
+}
+
+impl Default for SdfVoxel {
+    fn default() -> Self {
+        Self {
+            distance: 1.,
+            palette_index: 0,
+        }
+    }
+}
+
+impl SmoothVoxel for SdfVoxel {
+    fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    fn palette_index(&self) -> u8 {
+        self.palette_index
+    }
+}
+
+/// The corners of a cell, as offsets from its minimum corner.
+const CORNERS: [UVec3; 8] = [
+    UVec3::new(0, 0, 0),
+    UVec3::new(1, 0, 0),
+    UVec3::new(0, 1, 0),
+    UVec3::new(1, 1, 0),
+    UVec3::new(0, 0, 1),
+    UVec3::new(1, 0, 1),
+    UVec3::new(0, 1, 1),
+    UVec3::new(1, 1, 1),
+];
+
+/// The edges of a cell, as pairs of indices into [`CORNERS`].
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+impl<V, VS, S> Chunk<V, VS, S>
+where
+    VS: AsRef<[V]>,
+    V: SmoothVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    /// Build a smooth mesh of the surface where the voxels' [`SmoothVoxel::distance`] crosses zero, using surface nets.
+    ///
+    /// Normals follow the gradient of the distance field, and each vertex blends the two most common palette indices
+    /// of the solid voxels around it using [`ATTRIBUTE_BLEND_WEIGHT`].
+    /// The surface is built in the cells between `min` and `max`, so samples outside them only affect neighbouring chunks.
+    pub fn build_smooth(&self) -> Mesh {
+        let voxels = self.voxels.as_ref();
+        let distance =
+            |pos: UVec3| voxels[self.shape.linearize(pos.to_array()) as usize].distance();
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut color_indices = Vec::new();
+        let mut blend_weights = Vec::new();
+
+        // Place a vertex in each cell the surface passes through.
+        let cells = self.max - self.min;
+        let mut cell_vertices = vec![u32::MAX; (cells.x * cells.y * cells.z) as usize];
+        let cell_index = |cell: UVec3| (cell.x + cells.x * (cell.y + cells.y * cell.z)) as usize;
+
+        for z in 0..cells.z {
+            for y in 0..cells.y {
+                for x in 0..cells.x {
+                    let cell = UVec3::new(x, y, z);
+                    let origin = self.min + cell;
+                    let corners = CORNERS.map(|corner| distance(origin + corner));
+                    let inside = corners.iter().filter(|distance| **distance < 0.).count();
+                    if inside == 0 || inside == 8 {
+                        continue;
+                    }
+
+                    let mut sum = Vec3::ZERO;
+                    let mut crossings = 0;
+                    for (a, b) in EDGES {
+                        if (corners[a] < 0.) != (corners[b] < 0.) {
+                            let t = corners[a] / (corners[a] - corners[b]);
+                            sum += CORNERS[a].as_vec3().lerp(CORNERS[b].as_vec3(), t);
+                            crossings += 1;
+                        }
+                    }
+
+                    // Sum the differences along each edge, pointing from inside to outside.
+                    let gradient: Vec3 = CORNERS
+                        .iter()
+                        .zip(corners)
+                        .map(|(corner, distance)| (corner.as_vec3() * 2. - 1.) * distance)
+                        .sum();
+
+                    let (color_index, blend_weight) = blend(
+                        CORNERS
+                            .iter()
+                            .zip(corners)
+                            .filter(|(_, distance)| *distance < 0.)
+                            .map(|(corner, distance)| {
+                                let idx = self.shape.linearize((origin + *corner).to_array());
+                                (voxels[idx as usize].palette_index(), -distance)
+                            }),
+                    );
+
+                    cell_vertices[cell_index(cell)] = positions.len() as u32;
+                    positions.push((origin.as_vec3() + sum / crossings as f32).to_array());
+                    normals.push(gradient.normalize_or(Vec3::Y).to_array());
+                    color_indices.push(color_index);
+                    blend_weights.push(blend_weight);
+                }
+            }
+        }
+
+        // Connect the vertices of the four cells around each edge the surface crosses.
+        let mut indices = Vec::new();
+        for z in 0..cells.z {
+            for y in 0..cells.y {
+                for x in 0..cells.x {
+                    let cell = UVec3::new(x, y, z);
+                    if cell_vertices[cell_index(cell)] == u32::MAX {
+                        continue;
+                    }
+
+                    let origin = self.min + cell;
+                    let inside = distance(origin) < 0.;
+                    for axis in 0..3 {
+                        let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+                        if cell[b] == 0 || cell[c] == 0 {
+                            continue;
+                        }
+                        if inside == (distance(origin + UVec3::AXES[axis]) < 0.) {
+                            continue;
+                        }
+
+                        let quad = [
+                            cell - UVec3::AXES[b] - UVec3::AXES[c],
+                            cell - UVec3::AXES[c],
+                            cell,
+                            cell - UVec3::AXES[b],
+                        ]
+                        .map(|cell| cell_vertices[cell_index(cell)]);
+                        if quad.contains(&u32::MAX) {
+                            continue;
+                        }
+
+                        // Face towards the outside of the surface.
+                        if inside {
+                            indices.extend_from_slice(&[
+                                quad[0], quad[1], quad[2], quad[0], quad[2], quad[3],
+                            ]);
+                        } else {
+                            indices.extend_from_slice(&[
+                                quad[0], quad[2], quad[1], quad[0], quad[3], quad[2],
+                            ]);
+                        }
+                    }
+                }
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            VertexAttributeValues::Float32x3(positions),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            VertexAttributeValues::Float32x3(normals),
+        )
+        .with_inserted_attribute(
+            ATTRIBUTE_COLOR_INDEX,
+            VertexAttributeValues::Uint32(color_indices),
+        )
+        .with_inserted_attribute(
+            ATTRIBUTE_BLEND_WEIGHT,
+            VertexAttributeValues::Float32(blend_weights),
+        )
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+/// Returns the packed two most heavily weighted palette indices and the weight of the second.
+fn blend(samples: impl Iterator<Item = (u8, f32)>) -> (u32, f32) {
+    let mut weights: Vec<(u8, f32)> = Vec::with_capacity(8);
+    for (palette_index, weight) in samples {
+        match weights.iter_mut().find(|(idx, _)| *idx == palette_index) {
+            Some((_, total)) => *total += weight,
+            None => weights.push((palette_index, weight)),
+        }
+    }
+    weights.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    match weights[..] {
+        [] => (0, 0.),
+        [(a, _)] => (a as u32 | (a as u32) << 8, 0.),
+        [(a, weight_a), (b, weight_b), ..] => (
+            a as u32 | (b as u32) << 8,
+            weight_b / (weight_a + weight_b).max(f32::EPSILON),
+        ),
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    ATTRIBUTE_BLEND_WEIGHT, ATTRIBUTE_COLOR_INDEX, ATTRIBUTE_PACKED_VOXEL, Chunk, MeshBuilder,
+    VoxelScene, packed_aabb,
+};
+use bevy::{camera::primitives::MeshAabb, mesh::VertexAttributeValues, prelude::*};
+use ndshape::Shape;
+use std::fmt;
+
+/// Geometry statistics of a voxel mesh, for checking assets against a budget without a renderer.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MeshStats {
+    pub quads: usize,
+    pub vertices: usize,
+    pub indices: usize,
+    pub triangles: usize,
+    /// The bounding box of the vertices, or `None` if the mesh is empty.
+    pub bounds: Option<(Vec3, Vec3)>,
+    /// The number of vertices whose colour index is outside the 256-entry palette of a [`VoxelMaterial`](crate::VoxelMaterial),
+    /// e.g. because [`VoxelAttributes::attributes`](crate::VoxelAttributes::attributes) subtracted 1 from the empty palette index 0.
+    ///
+    /// Smooth meshes store two palette indices per vertex, and both are checked.
+    pub invalid_color_indices: usize,
+}
+
+impl MeshStats {
+    /// Collect the statistics of a mesh built with [`MeshBuilder::build`], [`Chunk::build_packed`] or [`Chunk::build_smooth`].
+    ///
+    /// Voxel meshes are made of quads, so `quads` is half the number of triangles.
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        let indices = mesh.indices().map_or(0, |indices| indices.len());
+        let triangles = if mesh.indices().is_some() {
+            indices / 3
+        } else {
+            mesh.count_vertices() / 3
+        };
+
+        let bounds = packed_aabb(mesh)
+            .or_else(|| mesh.compute_aabb())
+            .filter(|_| mesh.count_vertices() > 0)
+            .map(|aabb| (aabb.min().into(), aabb.max().into()));
+
+        let color_indices = match mesh.attribute(ATTRIBUTE_PACKED_VOXEL) {
+            Some(VertexAttributeValues::Uint32x2(values)) => {
+                values.iter().map(|packed| packed[1]).collect()
+            }
+            _ => match mesh.attribute(ATTRIBUTE_COLOR_INDEX) {
+                Some(VertexAttributeValues::Uint32(values)) => values.clone(),
+                _ => Vec::new(),
+            },
+        };
+
+        let max_color_index = if mesh.contains_attribute(ATTRIBUTE_BLEND_WEIGHT) {
+            u16::MAX as u32
+        } else {
+            u8::MAX as u32
+        };
+
+        Self {
+            quads: triangles / 2,
+            vertices: mesh.count_vertices(),
+            indices,
+            triangles,
+            bounds,
+            invalid_color_indices: color_indices
+                .iter()
+                .filter(|idx| **idx > max_color_index)
+                .count(),
+        }
+    }
+
+    /// Returns `true` if the mesh has no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.triangles == 0
+    }
+
+    /// Add the counts of `other` to these statistics and extend the bounds to contain its bounds.
+    pub fn merge(&mut self, other: &MeshStats) {
+        self.quads += other.quads;
+        self.vertices += other.vertices;
+        self.indices += other.indices;
+        self.triangles += other.triangles;
+        self.bounds = union(self.bounds, other.bounds);
+        self.invalid_color_indices += other.invalid_color_indices;
+    }
+}
+
+impl<V, VS, S> Chunk<V, VS, S>
+where
+    Self: MeshBuilder,
+{
+    /// Build this chunk's mesh with [`MeshBuilder::build`] and return its statistics.
+    pub fn mesh_stats(&self) -> MeshStats {
+        MeshStats::from_mesh(&self.build())
+    }
+}
+
+/// Statistics of a [`VoxelModel`](crate::VoxelModel) in a scene.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelStats {
+    pub name: Option<String>,
+    pub path: Vec<String>,
+    pub chunks: usize,
+    /// The sum of the model's chunk meshes, with bounds in scene space from [`VoxelModel::bounds`](crate::VoxelModel::bounds).
+    pub mesh: MeshStats,
+    /// The number of voxels whose material is emissive,
+    /// or `None` if the model's voxels weren't kept (see [`LitMesh::voxels`](crate::scene::LitMesh::voxels)).
+    pub emissive_voxels: Option<usize>,
+    pub lights: usize,
+}
+
+/// Statistics of a [`VoxelScene`], returned by [`VoxelScene::stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneStats {
+    pub models: Vec<ModelStats>,
+    /// The sum of every model, with bounds in scene space.
+    pub total: MeshStats,
+    /// The number of emissive voxels in the models whose voxels were kept, or `None` if no model kept its voxels.
+    pub emissive_voxels: Option<usize>,
+    pub lights: usize,
+}
+
+/// A problem found by [`VoxelScene::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A model without any triangles.
+    EmptyModel { model: usize, name: Option<String> },
+    /// A model with vertices whose colour index is outside the palette.
+    InvalidColorIndex {
+        model: usize,
+        name: Option<String>,
+        vertices: usize,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let model = |f: &mut fmt::Formatter<'_>, model: &usize, name: &Option<String>| match name {
+            Some(name) => write!(f, "model {model} ({name})"),
+            None => write!(f, "model {model}"),
+        };
+
+        match self {
+            ValidationIssue::EmptyModel { model: idx, name } => {
+                model(f, idx, name)?;
+                write!(f, " is empty")
+            }
+            ValidationIssue::InvalidColorIndex {
+                model: idx,
+                name,
+                vertices,
+            } => {
+                model(f, idx, name)?;
+                write!(
+                    f,
+                    " has {vertices} vertices with an out of range colour index"
+                )
+            }
+        }
+    }
+}
+
+impl VoxelScene {
+    /// Collect mesh statistics for each model in this scene.
+    pub fn stats(&self) -> SceneStats {
+        let mut stats = SceneStats::default();
+
+        for model in &self.models {
+            let mut model_stats = ModelStats {
+                name: model.name.clone(),
+                path: model.path.clone(),
+                chunks: model.meshes.len(),
+                ..Default::default()
+            };
+
+            for lit_mesh in &model.meshes {
+                model_stats.mesh.merge(&lit_mesh.stats);
+                model_stats.lights += lit_mesh.lights.len();
+
+                if let Some(chunk) = &lit_mesh.voxels {
+                    *model_stats.emissive_voxels.get_or_insert(0) += chunk
+                        .voxels
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, voxel)| {
+                            // Padding voxels belong to neighbouring chunks.
+                            let pos = UVec3::from(chunk.shape.delinearize(*idx as u32));
+                            voxel.idx != 0
+                                && pos.cmpgt(chunk.min).all()
+                                && pos.cmplt(chunk.max).all()
+                                && self.material.emissions[voxel.idx as usize].x > 0.
+                        })
+                        .count();
+                }
+            }
+
+            model_stats.mesh.bounds = model.bounds();
+
+            stats.total.merge(&model_stats.mesh);
+            if let Some(emissive_voxels) = model_stats.emissive_voxels {
+                *stats.emissive_voxels.get_or_insert(0) += emissive_voxels;
+            }
+            stats.lights += model_stats.lights;
+            stats.models.push(model_stats);
+        }
+
+        stats
+    }
+
+    /// Check this scene for degenerate models, returning an empty list if none were found.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        for (idx, model) in self.stats().models.into_iter().enumerate() {
+            if model.mesh.is_empty() {
+                issues.push(ValidationIssue::EmptyModel {
+                    model: idx,
+                    name: model.name.clone(),
+                });
+            }
+            if model.mesh.invalid_color_indices > 0 {
+                issues.push(ValidationIssue::InvalidColorIndex {
+                    model: idx,
+                    name: model.name,
+                    vertices: model.mesh.invalid_color_indices,
+                });
+            }
+        }
+        issues
+    }
+}
+
+fn union(a: Option<(Vec3, Vec3)>, b: Option<(Vec3, Vec3)>) -> Option<(Vec3, Vec3)> {
+    match (a, b) {
+        (Some((min_a, max_a)), Some((min_b, max_b))) => Some((min_a.min(min_b), max_a.max(max_b))),
+        (a, b) => a.or(b),
+    }
+}
@@ -0,0 +1,86 @@
+use std::marker::PhantomData;
+
+use crate::{ATTRIBUTE_TILE_INDEX, BlockRegistry};
+use bevy::{
+    mesh::MeshVertexBufferLayoutRef,
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    render::render_resource::{
+        AsBindGroup, RenderPipelineDescriptor, SpecializedMeshPipelineError,
+    },
+    shader::ShaderRef,
+};
+use uuid::Uuid;
+
+pub const TEXTURED_VOXEL_MATERIAL_SHADER_HANDLE: Handle<Shader> = Handle::Uuid(
+    Uuid::from_bytes([
+        61, 214, 8, 93, 170, 37, 75, 2, 159, 66, 213, 118, 40, 201, 7, 143,
+    ]),
+    PhantomData,
+);
+
+pub struct TexturedVoxelMaterialPlugin;
+
+impl Plugin for TexturedVoxelMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<TexturedVoxelMaterial>::default())
+            .init_resource::<BlockRegistry>()
+            .world_mut()
+            .resource_mut::<Assets<Shader>>()
+            .insert(
+                &TEXTURED_VOXEL_MATERIAL_SHADER_HANDLE,
+                Shader::from_wgsl(
+                    include_str!("textured_voxel_material.wgsl"),
+                    "textured_voxel_material.wgsl",
+                ),
+            )
+            .unwrap();
+    }
+}
+
+/// Material for textured block meshes built with [`Chunk::build_textured`](crate::Chunk::build_textured).
+///
+/// `texture` must be a 2D array texture with one layer per atlas tile
+/// (see [`Image::reinterpret_stacked_2d_as_array`] to convert a vertical strip atlas).
+#[derive(Clone, Debug, AsBindGroup, Asset, TypePath)]
+pub struct TexturedVoxelMaterial {
+    #[texture(0, dimension = "2d_array")]
+    #[sampler(1)]
+    pub texture: Handle<Image>,
+}
+
+impl Material for TexturedVoxelMaterial {
+    fn vertex_shader() -> ShaderRef {
+        ShaderRef::Handle(TEXTURED_VOXEL_MATERIAL_SHADER_HANDLE)
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        ShaderRef::Handle(TEXTURED_VOXEL_MATERIAL_SHADER_HANDLE)
+    }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        ShaderRef::Handle(TEXTURED_VOXEL_MATERIAL_SHADER_HANDLE)
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Opaque
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+            ATTRIBUTE_TILE_INDEX.at_shader_location(3),
+        ])?;
+
+        descriptor.vertex.buffers = vec![vertex_layout];
+
+        Ok(())
+    }
+}
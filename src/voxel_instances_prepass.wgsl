@@ -0,0 +1,60 @@
+#import bevy_pbr::{
+    prepass_io::VertexOutput,
+    view_transformations::position_world_to_clip,
+}
+#import voxy::packed_vertex
+
+// Prepass and shadow vertex shader for `VoxelInstances`, which take their transforms from the instance buffer
+// instead of the mesh bindings.
+
+struct Vertex {
+#ifdef VOXEL_PACKED
+    @location(0) packed: vec2<u32>,
+#else
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+#endif
+    // Rows of the world transform of the instance.
+    @location(3) instance_x: vec4<f32>,
+    @location(4) instance_y: vec4<f32>,
+    @location(5) instance_z: vec4<f32>,
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+#ifdef VOXEL_PACKED
+    let position = packed_vertex::position(vertex.packed);
+    let normal = packed_vertex::normal(vertex.packed);
+#else
+    let position = vertex.position;
+    let normal = vertex.normal;
+#endif
+
+    let world_from_local = transpose(mat4x4<f32>(
+        vertex.instance_x,
+        vertex.instance_y,
+        vertex.instance_z,
+        vec4(0., 0., 0., 1.)
+    ));
+
+    out.world_position = world_from_local * vec4<f32>(position, 1.0);
+    out.position = position_world_to_clip(out.world_position.xyz);
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.unclipped_depth = out.position.z;
+    out.position.z = min(out.position.z, 1.0);
+#endif
+
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+    // Instances assume uniform scale, so normals don't need the inverse transpose.
+    out.world_normal = normalize((world_from_local * vec4(normal, 0.)).xyz);
+#endif
+
+#ifdef MOTION_VECTOR_PREPASS
+    // Previous instance transforms aren't kept, so instances have no motion vectors of their own.
+    out.previous_world_position = out.world_position;
+#endif
+
+    return out;
+}
@@ -1,14 +1,14 @@
 use std::marker::PhantomData;
 
-use crate::ATTRIBUTE_COLOR_INDEX;
+use crate::{ATTRIBUTE_BLEND_WEIGHT, ATTRIBUTE_COLOR_INDEX, ATTRIBUTE_PACKED_VOXEL};
 use bevy::{
-    mesh::MeshVertexBufferLayoutRef,
+    mesh::{MeshVertexBufferLayoutRef, VertexBufferLayout},
     pbr::{MaterialPipeline, MaterialPipelineKey},
     prelude::*,
     render::render_resource::{
         AsBindGroup, RenderPipelineDescriptor, SpecializedMeshPipelineError,
     },
-    shader::ShaderRef,
+    shader::{ShaderDefVal, ShaderRef},
 };
 use uuid::Uuid;
 
@@ -19,18 +19,64 @@ pub const VOXEL_MATERIAL_SHADER_HANDLE: Handle<Shader> = Handle::Uuid(
     PhantomData,
 );
 
+pub const VOXEL_PACKED_PREPASS_SHADER_HANDLE: Handle<Shader> = Handle::Uuid(
+    Uuid::from_bytes([
+        203, 17, 94, 66, 12, 180, 77, 49, 142, 250, 3, 118, 91, 36, 201, 164,
+    ]),
+    PhantomData,
+);
+
+pub(crate) const VOXEL_INSTANCES_PREPASS_SHADER_HANDLE: Handle<Shader> = Handle::Uuid(
+    Uuid::from_bytes([
+        37, 208, 121, 4, 166, 93, 78, 12, 181, 47, 230, 152, 11, 99, 64, 187,
+    ]),
+    PhantomData,
+);
+
+const PACKED_VERTEX_SHADER_HANDLE: Handle<Shader> = Handle::Uuid(
+    Uuid::from_bytes([
+        96, 141, 33, 208, 77, 5, 72, 183, 165, 20, 222, 9, 58, 132, 240, 71,
+    ]),
+    PhantomData,
+);
+
 pub struct VoxelMaterialPlugin;
 
 impl Plugin for VoxelMaterialPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MaterialPlugin::<VoxelMaterial>::default())
-            .world_mut()
-            .resource_mut::<Assets<Shader>>()
+        app.add_plugins(MaterialPlugin::<VoxelMaterial>::default());
+
+        let mut shaders = app.world_mut().resource_mut::<Assets<Shader>>();
+        shaders
             .insert(
                 &VOXEL_MATERIAL_SHADER_HANDLE,
                 Shader::from_wgsl(include_str!("voxel_material.wgsl"), "voxel_material.wgsl"),
             )
             .unwrap();
+        shaders
+            .insert(
+                &VOXEL_PACKED_PREPASS_SHADER_HANDLE,
+                Shader::from_wgsl(
+                    include_str!("voxel_packed_prepass.wgsl"),
+                    "voxel_packed_prepass.wgsl",
+                ),
+            )
+            .unwrap();
+        shaders
+            .insert(
+                &VOXEL_INSTANCES_PREPASS_SHADER_HANDLE,
+                Shader::from_wgsl(
+                    include_str!("voxel_instances_prepass.wgsl"),
+                    "voxel_instances_prepass.wgsl",
+                ),
+            )
+            .unwrap();
+        shaders
+            .insert(
+                &PACKED_VERTEX_SHADER_HANDLE,
+                Shader::from_wgsl(include_str!("packed_vertex.wgsl"), "packed_vertex.wgsl"),
+            )
+            .unwrap();
     }
 }
 
@@ -65,14 +111,47 @@ impl Material for VoxelMaterial {
         layout: &MeshVertexBufferLayoutRef,
         _key: MaterialPipelineKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
-        let vertex_layout = layout.0.get_layout(&[
-            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
-            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
-            ATTRIBUTE_COLOR_INDEX.at_shader_location(2),
-        ])?;
+        let is_prepass = descriptor
+            .vertex
+            .shader_defs
+            .contains(&"PREPASS_PIPELINE".into());
+
+        descriptor.vertex.buffers = vec![voxel_vertex_layout(
+            layout,
+            &mut descriptor.vertex.shader_defs,
+        )?];
 
-        descriptor.vertex.buffers = vec![vertex_layout];
+        // Bevy's default prepass shader expects float positions.
+        if is_prepass && layout.0.contains(ATTRIBUTE_PACKED_VOXEL) {
+            descriptor.vertex.shader = VOXEL_PACKED_PREPASS_SHADER_HANDLE;
+        }
 
         Ok(())
     }
 }
+
+/// Returns the vertex buffer layout read by `voxel_material.wgsl` for a mesh, adding the shader defs it needs.
+pub(crate) fn voxel_vertex_layout(
+    layout: &MeshVertexBufferLayoutRef,
+    shader_defs: &mut Vec<ShaderDefVal>,
+) -> Result<VertexBufferLayout, SpecializedMeshPipelineError> {
+    if layout.0.contains(ATTRIBUTE_PACKED_VOXEL) {
+        shader_defs.push("VOXEL_PACKED".into());
+        return Ok(layout
+            .0
+            .get_layout(&[ATTRIBUTE_PACKED_VOXEL.at_shader_location(0)])?);
+    }
+
+    let mut attributes = vec![
+        Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+        Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+        ATTRIBUTE_COLOR_INDEX.at_shader_location(2),
+    ];
+    // Locations 3 to 6 are used by instances.
+    if layout.0.contains(ATTRIBUTE_BLEND_WEIGHT) {
+        shader_defs.push("VOXEL_BLEND".into());
+        attributes.push(ATTRIBUTE_BLEND_WEIGHT.at_shader_location(7));
+    }
+
+    Ok(layout.0.get_layout(&attributes)?)
+}
@@ -18,14 +18,43 @@
 }
 #endif
 
+#import voxy::packed_vertex
+
+#ifdef VOXEL_INSTANCED
+struct PaletteEntry {
+    color: vec4<f32>,
+    emissive: vec4<f32>
+}
+
+// 256 entries for each palette of a `VoxelInstances` entity.
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<storage, read> palettes: array<PaletteEntry>;
+#else
 @group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> colors: array<vec3<f32>, 256>;
 @group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> emissives: array<vec3<f32>, 256>;
+#endif
 
+#ifdef VOXEL_PACKED
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) packed: vec2<u32>,
+#else
 struct Vertex {
     @builtin(instance_index) instance_index: u32,
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
-    @location(2) color_index: u32
+    @location(2) color_index: u32,
+#endif
+#ifdef VOXEL_INSTANCED
+    // Rows of the world transform of the instance.
+    @location(3) instance_x: vec4<f32>,
+    @location(4) instance_y: vec4<f32>,
+    @location(5) instance_z: vec4<f32>,
+    @location(6) palette: u32,
+#endif
+#ifdef VOXEL_BLEND
+    // Weight of the palette index in bits 8-15 of `color_index`.
+    @location(7) blend: f32,
+#endif
 }
 
 #ifndef PREPASS_PIPELINE
@@ -42,18 +71,57 @@ struct VertexOutput {
 @vertex
 fn vertex(vertex: Vertex) -> VertexOutput {
     var out: VertexOutput;
-    
-    var color = colors[vertex.color_index];
+
+#ifdef VOXEL_PACKED
+    let position = packed_vertex::position(vertex.packed);
+    let normal = packed_vertex::normal(vertex.packed);
+    let color_index = packed_vertex::color_index(vertex.packed);
+#else
+    let position = vertex.position;
+    let normal = vertex.normal;
+#ifdef VOXEL_BLEND
+    let color_index = vertex.color_index & 0xffu;
+    let blend_index = (vertex.color_index >> 8u) & 0xffu;
+#else
+    let color_index = vertex.color_index;
+#endif
+#endif
+
+#ifdef VOXEL_INSTANCED
+    let entry = palettes[vertex.palette * 256u + color_index];
+    out.color = vec4(entry.color.xyz, 1.);
+    out.emissive = entry.emissive.xyz;
+#ifdef VOXEL_BLEND
+    let blend_entry = palettes[vertex.palette * 256u + blend_index];
+    out.color = vec4(mix(out.color.xyz, blend_entry.color.xyz, vertex.blend), 1.);
+    out.emissive = mix(out.emissive, blend_entry.emissive.xyz, vertex.blend);
+#endif
+
+    // Instances assume uniform scale, so normals don't need the inverse transpose.
+    let world_from_local = transpose(mat4x4<f32>(
+        vertex.instance_x,
+        vertex.instance_y,
+        vertex.instance_z,
+        vec4(0., 0., 0., 1.)
+    ));
+    out.world_normal = normalize((world_from_local * vec4(normal, 0.)).xyz);
+#else
+    var color = colors[color_index];
     out.color = vec4(color.x, color.y, color.z, 1.);
-    out.emissive = emissives[vertex.color_index];
+    out.emissive = emissives[color_index];
+#ifdef VOXEL_BLEND
+    out.color = vec4(mix(color, colors[blend_index], vertex.blend), 1.);
+    out.emissive = mix(out.emissive, emissives[blend_index], vertex.blend);
+#endif
  
     var world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
     out.world_normal = mesh_functions::mesh_normal_local_to_world(
-        vertex.normal,
+        normal,
         vertex.instance_index
     );
+#endif
 
-    out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+    out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(position, 1.0));
     out.position = view_transformations::position_world_to_clip(out.world_position.xyz);
 
     out.instance_index = vertex.instance_index;
@@ -106,6 +174,11 @@ fn fragment(@builtin(front_facing) is_front: bool, mesh: VertexOutput) -> Fragme
     var out: FragmentOutput;
     out.color = pbr_functions::apply_pbr_lighting(pbr_input);
     out.color = pbr_functions::main_pass_post_lighting_processing(pbr_input, out.color);
+#ifndef VOXEL_INSTANCED
+    // The linear RGBA8 colour of a `VoxelHighlight`, or 0 if the entity isn't highlighted.
+    let entity_highlight = unpack4x8unorm(mesh_functions::get_tag(mesh.instance_index));
+    out.color = vec4(mix(out.color.rgb, entity_highlight.rgb, entity_highlight.a), out.color.a);
+#endif
 #endif
 
     return out;
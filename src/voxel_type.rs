@@ -0,0 +1,191 @@
+use crate::{
+    AssetVoxel, Chunk, VoxelAttributes, VoxelLight, VoxelMaterial, scene::emissive_lights,
+};
+use bevy::prelude::*;
+use block_mesh::{MergeVoxel, Voxel};
+use ndshape::Shape;
+use std::collections::HashMap;
+
+pub use block_mesh::VoxelVisibility;
+
+/// Identifier of a voxel type in a [`VoxelTypeRegistry`].
+pub type VoxelId = u16;
+
+/// Properties of a kind of voxel (e.g. a block type).
+///
+/// A [`Chunk`] of voxel types can be meshed with [`MeshBuilder::build`] like any other chunk,
+/// so [`VoxelTypeRegistry::resolve_chunk`] turns a chunk of IDs into one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoxelType {
+    /// Whether faces of this voxel are meshed and whether it hides its neighbours' faces.
+    pub visibility: VoxelVisibility,
+    /// Whether this voxel blocks movement.
+    ///
+    /// This isn't used for meshing, but is available to gameplay code through [`VoxelTypeRegistry::is_solid`].
+    pub solid: bool,
+    /// Adjacent faces are only merged into the same quad if they share a merge group and material.
+    pub merge_group: u32,
+    /// The colour or material index written to [`ATTRIBUTE_COLOR_INDEX`](crate::ATTRIBUTE_COLOR_INDEX).
+    pub material: u32,
+    /// The intensity of light emitted by this voxel.
+    pub emission: f32,
+}
+
+impl VoxelType {
+    /// The type of unregistered voxels, which are never meshed.
+    pub const EMPTY: Self = Self {
+        visibility: VoxelVisibility::Empty,
+        solid: false,
+        merge_group: 0,
+        material: 0,
+        emission: 0.,
+    };
+
+    /// An opaque, solid voxel using `material`.
+    pub fn opaque(material: u32) -> Self {
+        Self {
+            visibility: VoxelVisibility::Opaque,
+            solid: true,
+            merge_group: 0,
+            material,
+            emission: 0.,
+        }
+    }
+
+    /// A translucent, non-solid voxel using `material` (e.g. water or glass).
+    pub fn translucent(material: u32) -> Self {
+        Self {
+            visibility: VoxelVisibility::Translucent,
+            solid: false,
+            ..Self::opaque(material)
+        }
+    }
+
+    /// Set the merge group of this voxel.
+    pub fn with_merge_group(mut self, merge_group: u32) -> Self {
+        self.merge_group = merge_group;
+        self
+    }
+
+    /// Set the light emitted by this voxel.
+    pub fn with_emission(mut self, emission: f32) -> Self {
+        self.emission = emission;
+        self
+    }
+}
+
+/// Registry of voxel types keyed by [`VoxelId`].
+///
+/// Unregistered IDs (including `0` by default) are treated as empty.
+/// Insert it as a resource to share the block properties of a game between its systems.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct VoxelTypeRegistry {
+    types: HashMap<VoxelId, VoxelType>,
+}
+
+impl VoxelTypeRegistry {
+    /// Create a registry matching a `.vox` palette, where ID `i + 1` is an opaque voxel using colour `i`.
+    pub fn from_material(material: &VoxelMaterial) -> Self {
+        let mut registry = Self::default();
+        for idx in 0..u8::MAX {
+            let id = idx as VoxelId + 1;
+            registry.insert(
+                id,
+                VoxelType::opaque(idx as u32).with_emission(material.emissions[id as usize].x),
+            );
+        }
+        registry
+    }
+
+    /// Register the properties of voxel `id`, replacing any previous type.
+    pub fn insert(&mut self, id: VoxelId, voxel_type: VoxelType) -> &mut Self {
+        self.types.insert(id, voxel_type);
+        self
+    }
+
+    /// Remove the type registered for `id`.
+    pub fn remove(&mut self, id: VoxelId) -> Option<VoxelType> {
+        self.types.remove(&id)
+    }
+
+    /// Returns the type registered for `id`.
+    pub fn get(&self, id: VoxelId) -> Option<&VoxelType> {
+        self.types.get(&id)
+    }
+
+    /// Returns `true` if voxel `id` is registered and solid.
+    pub fn is_solid(&self, id: VoxelId) -> bool {
+        self.get(id).is_some_and(|voxel_type| voxel_type.solid)
+    }
+
+    /// Returns the light emitted by voxel `id`.
+    pub fn emission(&self, id: VoxelId) -> f32 {
+        self.get(id)
+            .map(|voxel_type| voxel_type.emission)
+            .unwrap_or_default()
+    }
+
+    /// Returns the type registered for `id`, or [`VoxelType::EMPTY`] if there is none.
+    pub fn resolve(&self, id: VoxelId) -> VoxelType {
+        self.get(id).copied().unwrap_or(VoxelType::EMPTY)
+    }
+
+    /// Look up the type of every voxel of `chunk`, including its padding.
+    ///
+    /// Build the returned chunk with [`MeshBuilder::build`] or [`Chunk::build_packed`] to mesh it
+    /// with each voxel's visibility, merge group and material.
+    pub fn resolve_chunk<V, VS, S>(
+        &self,
+        chunk: &Chunk<V, VS, S>,
+    ) -> Chunk<VoxelType, Vec<VoxelType>, S>
+    where
+        VS: AsRef<[V]>,
+        V: Copy + Into<VoxelId>,
+        S: Clone,
+    {
+        let voxels = chunk
+            .voxels
+            .as_ref()
+            .iter()
+            .map(|voxel| self.resolve((*voxel).into()))
+            .collect();
+
+        Chunk::new(voxels, chunk.shape.clone(), chunk.min, chunk.max)
+    }
+
+    /// Returns a light for each voxel inside `chunk` with a positive [`VoxelType::emission`], in chunk space.
+    pub fn lights<V, VS, S>(&self, chunk: &Chunk<V, VS, S>) -> Vec<VoxelLight>
+    where
+        VS: AsRef<[V]>,
+        V: Copy + Into<VoxelId>,
+        S: Shape<3, Coord = u32>,
+    {
+        emissive_lights(chunk, |voxel| self.emission((*voxel).into()))
+    }
+}
+
+impl From<AssetVoxel> for VoxelId {
+    fn from(voxel: AssetVoxel) -> Self {
+        voxel.idx as VoxelId
+    }
+}
+
+impl Voxel for VoxelType {
+    fn get_visibility(&self) -> VoxelVisibility {
+        self.visibility
+    }
+}
+
+impl VoxelAttributes for VoxelType {
+    fn attributes(&self) -> u32 {
+        self.material
+    }
+}
+
+impl MergeVoxel for VoxelType {
+    type MergeValue = (u32, u32);
+
+    fn merge_value(&self) -> Self::MergeValue {
+        (self.merge_group, self.material)
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{AssetVoxel, AssetVoxelChunk, Chunk, QuantizedPalette, VoxelMaterial};
+use bevy::{
+    mesh::{Indices, VertexAttributeValues},
+    prelude::*,
+    render::render_resource::PrimitiveTopology,
+};
+use ndshape::{RuntimeShape, Shape};
+use std::collections::HashMap;
+
+/// Which voxels of a mesh are filled by [`voxelize`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoxelizeMode {
+    /// Fill the inside of closed meshes, coloured like the nearest surface voxel along the X axis.
+    #[default]
+    Solid,
+    /// Only fill voxels intersecting a triangle.
+    Surface,
+}
+
+/// Settings for [`voxelize`].
+#[derive(Clone, Debug)]
+pub struct VoxelizeSettings {
+    /// The number of voxels along the longest side of the mesh's bounding box.
+    pub resolution: u32,
+    pub mode: VoxelizeMode,
+    /// The maximum number of palette colours.
+    pub max_colors: usize,
+    /// Multiplies the texture and vertex colours of the mesh.
+    pub color: Color,
+}
+
+impl Default for VoxelizeSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 32,
+            mode: VoxelizeMode::Solid,
+            max_colors: QuantizedPalette::MAX_COLORS,
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// A voxelized mesh.
+pub struct Voxelized {
+    /// The voxels, with one voxel of padding on each side.
+    pub chunk: AssetVoxelChunk,
+    pub palette: QuantizedPalette,
+    /// The size of a voxel in mesh units.
+    pub voxel_size: f32,
+    /// The position in mesh space of the first voxel's minimum corner, including padding.
+    pub origin: Vec3,
+}
+
+impl Voxelized {
+    /// Returns a material with the voxelized palette.
+    pub fn material(&self) -> VoxelMaterial {
+        self.palette.material()
+    }
+
+    /// Returns the transform that places a mesh built from [`chunk`](Self::chunk) over the original mesh.
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.origin).with_scale(Vec3::splat(self.voxel_size))
+    }
+}
+
+/// Convert a triangle mesh into voxels at `settings.resolution`.
+///
+/// Colours are sampled from `texture` at [`Mesh::ATTRIBUTE_UV_0`] and multiplied by [`Mesh::ATTRIBUTE_COLOR`], if present,
+/// then quantized into a palette with [`QuantizedPalette::from_samples`].
+///
+/// Returns `None` if the mesh isn't a triangle list with positions, or has no triangles.
+pub fn voxelize(
+    mesh: &Mesh,
+    texture: Option<&Image>,
+    settings: &VoxelizeSettings,
+) -> Option<Voxelized> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+        _ => None,
+    };
+    let vertex_colors = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors)) => Some(colors),
+        _ => None,
+    };
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.iter().map(|idx| *idx as usize).collect(),
+        Some(Indices::U32(indices)) => indices.iter().map(|idx| *idx as usize).collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    let (min, max) = positions.iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), position| {
+            (
+                min.min(Vec3::from(*position)),
+                max.max(Vec3::from(*position)),
+            )
+        },
+    );
+    if indices.len() < 3 || !min.is_finite() {
+        return None;
+    }
+
+    let voxel_size =
+        (max - min).max_element().max(f32::EPSILON) / settings.resolution.max(1) as f32;
+    let size = ((max - min) / voxel_size).ceil().as_uvec3().max(UVec3::ONE);
+    let tint = settings.color.to_linear().to_vec3();
+
+    // Sample each triangle at least twice per voxel, averaging the colours of the samples in each voxel.
+    let mut surface: HashMap<UVec3, (Vec3, u32)> = HashMap::new();
+    let step = voxel_size * 0.5;
+    for triangle in indices.chunks_exact(3) {
+        let corners = [0, 1, 2].map(|corner| Vec3::from(positions[triangle[corner]]));
+        let longest = corners[0]
+            .distance(corners[1])
+            .max(corners[1].distance(corners[2]))
+            .max(corners[2].distance(corners[0]));
+        let steps = ((longest / step).ceil() as u32).max(1);
+
+        for i in 0..=steps {
+            for j in 0..=steps - i {
+                let (u, v) = (i as f32 / steps as f32, j as f32 / steps as f32);
+                let weights = Vec3::new(1. - u - v, u, v);
+                let position =
+                    corners[0] * weights.x + corners[1] * weights.y + corners[2] * weights.z;
+                let voxel = ((position - min) / voxel_size)
+                    .floor()
+                    .as_uvec3()
+                    .min(size - UVec3::ONE);
+
+                let mut color = tint;
+                if let (Some(texture), Some(uvs)) = (texture, uvs) {
+                    let uv = triangle
+                        .iter()
+                        .zip(weights.to_array())
+                        .map(|(idx, weight)| Vec2::from(uvs[*idx]) * weight)
+                        .sum();
+                    color *= sample(texture, uv);
+                }
+                if let Some(vertex_colors) = vertex_colors {
+                    color *= triangle
+                        .iter()
+                        .zip(weights.to_array())
+                        .map(|(idx, weight)| Vec4::from(vertex_colors[*idx]).truncate() * weight)
+                        .sum::<Vec3>();
+                }
+
+                let entry = surface.entry(voxel).or_default();
+                entry.0 += color;
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let colors: HashMap<UVec3, Vec3> = surface
+        .into_iter()
+        .map(|(voxel, (sum, count))| (voxel, sum / count as f32))
+        .collect();
+    let samples: Vec<Vec3> = colors.values().copied().collect();
+    let palette = QuantizedPalette::from_samples(&samples, settings.max_colors);
+
+    let shape = RuntimeShape::<u32, 3>::new((size + UVec3::splat(2)).to_array());
+    let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+    for (voxel, color) in &colors {
+        let idx = shape.linearize((*voxel + UVec3::ONE).to_array()) as usize;
+        voxels[idx] = AssetVoxel {
+            idx: palette.nearest(*color) + 1,
+        };
+    }
+
+    if settings.mode == VoxelizeMode::Solid {
+        fill_interior(&mut voxels, &shape);
+    }
+
+    Some(Voxelized {
+        chunk: Chunk::new(voxels, shape, UVec3::ZERO, size + UVec3::ONE),
+        palette,
+        voxel_size,
+        origin: min - Vec3::splat(voxel_size),
+    })
+}
+
+/// Fill the empty voxels that can't be reached from the padding without crossing a voxel.
+fn fill_interior(voxels: &mut [AssetVoxel], shape: &RuntimeShape<u32, 3>) {
+    let size = UVec3::from(shape.as_array());
+    let mut outside = vec![false; voxels.len()];
+    let mut stack = vec![UVec3::ZERO];
+    outside[0] = true;
+
+    while let Some(pos) = stack.pop() {
+        for offset in [
+            IVec3::X,
+            IVec3::NEG_X,
+            IVec3::Y,
+            IVec3::NEG_Y,
+            IVec3::Z,
+            IVec3::NEG_Z,
+        ] {
+            let neighbour = pos.as_ivec3() + offset;
+            if neighbour.cmplt(IVec3::ZERO).any() || neighbour.cmpge(size.as_ivec3()).any() {
+                continue;
+            }
+
+            let neighbour = neighbour.as_uvec3();
+            let idx = shape.linearize(neighbour.to_array()) as usize;
+            if !outside[idx] && voxels[idx].idx == 0 {
+                outside[idx] = true;
+                stack.push(neighbour);
+            }
+        }
+    }
+
+    for z in 1..size.z - 1 {
+        for y in 1..size.y - 1 {
+            let mut last = AssetVoxel::default();
+            for x in 1..size.x - 1 {
+                let idx = shape.linearize([x, y, z]) as usize;
+                if voxels[idx].idx != 0 {
+                    last = voxels[idx];
+                } else if !outside[idx] {
+                    voxels[idx] = last;
+                }
+            }
+        }
+    }
+}
+
+fn sample(texture: &Image, uv: Vec2) -> Vec3 {
+    let size = texture.size();
+    if size.cmpeq(UVec2::ZERO).any() {
+        return Vec3::ONE;
+    }
+    let pixel = (uv.rem_euclid(Vec2::ONE) * size.as_vec2())
+        .as_uvec2()
+        .min(size - UVec2::ONE);
+    texture
+        .get_color_at(pixel.x, pixel.y)
+        .map_or(Vec3::ONE, |color| color.to_linear().to_vec3())
+}
@@ -0,0 +1,173 @@
+use bevy::{mesh::VertexAttributeValues, prelude::*};
+use block_mesh::{MergeVoxel, Voxel, VoxelVisibility};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{ATTRIBUTE_TILE_INDEX, BlockRegistry, BlockTextures, Chunk, VoxelId};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct Block(VoxelId);
+
+impl Voxel for Block {
+    fn get_visibility(&self) -> VoxelVisibility {
+        if self.0 == 0 {
+            VoxelVisibility::Empty
+        } else {
+            VoxelVisibility::Opaque
+        }
+    }
+}
+
+impl MergeVoxel for Block {
+    type MergeValue = VoxelId;
+
+    fn merge_value(&self) -> Self::MergeValue {
+        self.0
+    }
+}
+
+impl From<Block> for VoxelId {
+    fn from(block: Block) -> Self {
+        block.0
+    }
+}
+
+/// A padded chunk of `size` voxels, filled by `block`.
+fn chunk(
+    size: UVec3,
+    block: impl Fn(UVec3) -> Block,
+) -> Chunk<Block, Vec<Block>, RuntimeShape<u32, 3>> {
+    let shape = RuntimeShape::<u32, 3>::new((size + UVec3::splat(2)).to_array());
+    let mut voxels = vec![Block::default(); shape.size() as usize];
+    for (idx, voxel) in voxels.iter_mut().enumerate() {
+        let pos = UVec3::from(shape.delinearize(idx as u32));
+        if pos.cmpge(UVec3::ONE).all() && pos.cmple(size).all() {
+            *voxel = block(pos - UVec3::ONE);
+        }
+    }
+    Chunk::new(voxels, shape, UVec3::ZERO, size + UVec3::ONE)
+}
+
+/// The normal, tile, and UVs of each quad in `mesh`.
+fn quads(mesh: &Mesh) -> Vec<(Vec3, u32, [Vec2; 4])> {
+    let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+    else {
+        panic!("missing normals");
+    };
+    let Some(VertexAttributeValues::Uint32(tiles)) = mesh.attribute(ATTRIBUTE_TILE_INDEX) else {
+        panic!("missing tile indices");
+    };
+    let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        panic!("missing uvs");
+    };
+    assert_eq!(normals.len(), tiles.len());
+    assert_eq!(normals.len(), uvs.len());
+
+    (0..normals.len() / 4)
+        .map(|quad| {
+            let vertices = quad * 4..quad * 4 + 4;
+            // Every vertex of a quad shares its normal and tile.
+            assert!(
+                normals[vertices.clone()]
+                    .iter()
+                    .all(|n| *n == normals[quad * 4])
+            );
+            assert!(
+                tiles[vertices.clone()]
+                    .iter()
+                    .all(|t| *t == tiles[quad * 4])
+            );
+            let uvs: Vec<_> = uvs[vertices].iter().map(|uv| Vec2::from(*uv)).collect();
+            (
+                Vec3::from(normals[quad * 4]),
+                tiles[quad * 4],
+                uvs.try_into().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn tiles_are_selected_per_face() {
+    let mut registry = BlockRegistry::default();
+    registry.insert(1, BlockTextures::top_side_bottom(10, 11, 12));
+
+    let mesh = chunk(UVec3::ONE, |_| Block(1)).build_textured(&registry);
+    let faces = quads(&mesh);
+    assert_eq!(faces.len(), 6);
+    for (normal, tile, _) in faces {
+        let expected = match normal {
+            Vec3::Y => 10,
+            Vec3::NEG_Y => 12,
+            _ => 11,
+        };
+        assert_eq!(tile, expected, "face {normal}");
+    }
+
+    // Each face follows the `RIGHT_HANDED_Y_UP_CONFIG` order.
+    registry.insert(
+        2,
+        BlockTextures {
+            faces: [0, 1, 2, 3, 4, 5],
+        },
+    );
+    let mesh = chunk(UVec3::ONE, |_| Block(2)).build_textured(&registry);
+    let normals = [
+        Vec3::NEG_X,
+        Vec3::NEG_Y,
+        Vec3::NEG_Z,
+        Vec3::X,
+        Vec3::Y,
+        Vec3::Z,
+    ];
+    for (normal, tile, _) in quads(&mesh) {
+        assert_eq!(normals[tile as usize], normal);
+    }
+
+    // Unregistered voxels use tile 0.
+    let mesh = chunk(UVec3::ONE, |_| Block(3)).build_textured(&registry);
+    assert!(quads(&mesh).iter().all(|(_, tile, _)| *tile == 0));
+}
+
+#[test]
+fn uvs_tile_across_merged_quads() {
+    let mut registry = BlockRegistry::default();
+    registry.insert(1, BlockTextures::all(7));
+
+    let mesh = chunk(UVec3::new(4, 1, 2), |_| Block(1)).build_textured(&registry);
+    let faces = quads(&mesh);
+    // The slab is merged into a single quad per face.
+    assert_eq!(faces.len(), 6);
+
+    // UVs are measured in voxels, so the texture repeats once per voxel along each side of the quad.
+    let extent = |uvs: &[Vec2; 4]| {
+        let min = uvs.iter().copied().reduce(Vec2::min).unwrap();
+        let max = uvs.iter().copied().reduce(Vec2::max).unwrap();
+        assert_eq!(min, Vec2::ZERO);
+        max
+    };
+    for (normal, tile, uvs) in &faces {
+        assert_eq!(*tile, 7);
+        let mut extent = extent(uvs).to_array();
+        extent.sort_by(f32::total_cmp);
+        let expected = match *normal {
+            Vec3::X | Vec3::NEG_X => [1., 2.],
+            Vec3::Y | Vec3::NEG_Y => [2., 4.],
+            _ => [1., 4.],
+        };
+        assert_eq!(extent, expected, "face {normal}");
+    }
+
+    // Different voxels aren't merged, so each keeps its own tile.
+    registry.insert(2, BlockTextures::all(8));
+    let mesh =
+        chunk(UVec3::new(2, 1, 1), |pos| Block(1 + pos.x as VoxelId)).build_textured(&registry);
+    let top: Vec<_> = quads(&mesh)
+        .into_iter()
+        .filter(|(normal, _, _)| *normal == Vec3::Y)
+        .map(|(_, tile, uvs)| (tile, extent(&uvs)))
+        .collect();
+    assert_eq!(top.len(), 2);
+    assert!(top.iter().all(|(_, extent)| *extent == Vec2::ONE));
+    let mut tiles: Vec<_> = top.iter().map(|(tile, _)| *tile).collect();
+    tiles.sort();
+    assert_eq!(tiles, [7, 8]);
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use ndshape::Shape;
+use voxy::{
+    AssetVoxel, BrickMap, VoxFileAsset,
+    brickmap::{BRICK_SIZE, brick_translation},
+};
+
+const SOLID: AssetVoxel = AssetVoxel { idx: 1 };
+
+#[test]
+fn get_and_set() {
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    assert_eq!(brickmap.get(IVec3::new(3, -20, 100)), AssetVoxel::default());
+
+    // Setting an empty voxel doesn't allocate a brick.
+    brickmap.set(IVec3::ZERO, AssetVoxel::default());
+    assert_eq!(brickmap.brick_count(), 0);
+
+    // Negative positions belong to the brick below, not brick zero.
+    brickmap.set(IVec3::new(-1, 0, 0), SOLID);
+    brickmap.set(IVec3::new(7, 7, 7), AssetVoxel { idx: 2 });
+    brickmap.set(IVec3::new(8, 0, 0), SOLID);
+    assert_eq!(brickmap.brick_count(), 3);
+    assert!(brickmap.brick(IVec3::new(-1, 0, 0)).is_some());
+    assert!(brickmap.brick(IVec3::ZERO).is_some());
+    assert!(brickmap.brick(IVec3::X).is_some());
+
+    assert_eq!(brickmap.get(IVec3::new(-1, 0, 0)), SOLID);
+    assert_eq!(brickmap.get(IVec3::new(7, 7, 7)), AssetVoxel { idx: 2 });
+    assert_eq!(brickmap.get(IVec3::new(8, 0, 0)), SOLID);
+    assert_eq!(brickmap.get(IVec3::new(0, 0, 0)), AssetVoxel::default());
+}
+
+#[test]
+fn prune_and_bounds() {
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    assert_eq!(brickmap.bounds(), None);
+
+    brickmap.set(IVec3::new(-3, 2, 0), SOLID);
+    brickmap.set(IVec3::new(20, 9, 1), SOLID);
+    assert_eq!(
+        brickmap.bounds(),
+        Some((IVec3::new(-8, 0, 0), IVec3::new(24, 16, 8)))
+    );
+
+    // Erasing leaves the brick allocated until it is pruned.
+    brickmap.set(IVec3::new(20, 9, 1), AssetVoxel::default());
+    assert_eq!(brickmap.brick_count(), 2);
+    brickmap.prune();
+    assert_eq!(brickmap.brick_count(), 1);
+    assert_eq!(
+        brickmap.bounds(),
+        Some((IVec3::new(-8, 0, 0), IVec3::new(0, 8, 8)))
+    );
+    assert_eq!(brickmap.get(IVec3::new(-3, 2, 0)), SOLID);
+}
+
+#[test]
+fn one_mesh_per_occupied_brick() {
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    // A bar crossing three bricks along x, and a single voxel in a distant brick.
+    for x in 4..20 {
+        brickmap.set(IVec3::new(x, 1, 1), SOLID);
+    }
+    brickmap.set(IVec3::new(-30, -30, -30), SOLID);
+    // An allocated brick without any solid voxels doesn't get a mesh.
+    brickmap.set(IVec3::new(0, 40, 0), SOLID);
+    brickmap.set(IVec3::new(0, 40, 0), AssetVoxel::default());
+    assert_eq!(brickmap.brick_count(), 5);
+
+    let mut meshes: Vec<_> = brickmap.build_meshes().collect();
+    meshes.sort_by_key(|(key, _)| key.to_array());
+    let keys: Vec<_> = meshes.iter().map(|(key, _)| *key).collect();
+    assert_eq!(
+        keys,
+        [
+            IVec3::splat(-4),
+            IVec3::new(0, 0, 0),
+            IVec3::new(1, 0, 0),
+            IVec3::new(2, 0, 0),
+        ]
+    );
+
+    // Faces between bricks are culled by the padding, so only the ends of the bar have x faces.
+    let quads: usize = meshes
+        .iter()
+        .map(|(_, mesh)| mesh.indices().unwrap().len() / 6)
+        .sum();
+    assert_eq!(quads, 3 * 4 + 2 + 6);
+
+    // Meshes are positioned by their brick's translation.
+    let chunk = brickmap.brick_chunk(IVec3::X).unwrap();
+    assert_eq!(
+        brick_translation(IVec3::X),
+        Vec3::new(BRICK_SIZE as f32 - 1., -1., -1.)
+    );
+    let [x, y, z] = [1, 2, 2];
+    assert_eq!(
+        chunk.voxels[chunk.shape.linearize([x, y, z]) as usize],
+        brickmap.get(brick_translation(IVec3::X).as_ivec3() + IVec3::new(x as _, y as _, z as _))
+    );
+}
+
+#[test]
+fn file_brickmap_matches_models() {
+    let asset = VoxFileAsset {
+        file: dot_vox::load("assets/character.vox").unwrap(),
+    };
+    let brickmap = asset.brickmap();
+
+    // Every voxel is where the unsplit model meshes put it in scene space.
+    let mut solid = 0;
+    for model in asset.models(None) {
+        let chunk = &model.chunks[0].chunk;
+        let transform = model.transform * model.chunks[0].transform;
+        for idx in 0..chunk.shape.size() {
+            let voxel = chunk.voxels[idx as usize];
+            if voxel.idx == 0 {
+                continue;
+            }
+            let local = UVec3::from(chunk.shape.delinearize(idx)).as_vec3();
+            let pos = transform.transform_point(local + 0.5).floor().as_ivec3();
+            assert_eq!(brickmap.get(pos), voxel);
+            solid += 1;
+        }
+    }
+
+    let stored: usize = brickmap
+        .bricks()
+        .map(|(_, brick)| brick.iter().filter(|voxel| voxel.idx != 0).count())
+        .sum();
+    assert_eq!(stored, solid);
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use ndshape::{RuntimeShape, Shape};
+use voxy::{AssetModel, AssetVoxel, VoxFileAsset};
+
+/// A padded model of `size` voxels where each solid voxel's palette index encodes its x coordinate.
+fn padded_model(
+    size: UVec3,
+    solid: impl Fn(UVec3) -> bool,
+) -> (Vec<AssetVoxel>, RuntimeShape<u32, 3>) {
+    let shape = RuntimeShape::<u32, 3>::new((size + UVec3::splat(2)).to_array());
+    let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+    for (idx, voxel) in voxels.iter_mut().enumerate() {
+        let pos = UVec3::from(shape.delinearize(idx as u32));
+        if pos.cmpge(UVec3::ONE).all() && pos.cmple(size).all() && solid(pos - UVec3::ONE) {
+            voxel.idx = pos.x as u8;
+        }
+    }
+    (voxels, shape)
+}
+
+#[test]
+fn split_into_chunks() {
+    let (voxels, shape) = padded_model(UVec3::new(5, 4, 2), |_| true);
+    let model = AssetModel::from_voxels(voxels, shape, Some(2), Some(String::from("wall")));
+
+    // 3 chunks along x (the last one narrower), 2 along y and 1 along z.
+    assert_eq!(model.chunks.len(), 6);
+    assert_eq!(model.path, ["wall"]);
+    let origins: Vec<_> = model
+        .chunks
+        .iter()
+        .map(|chunk| chunk.transform.translation.as_uvec3())
+        .collect();
+    assert_eq!(
+        origins,
+        [
+            UVec3::new(0, 0, 0),
+            UVec3::new(2, 0, 0),
+            UVec3::new(4, 0, 0),
+            UVec3::new(0, 2, 0),
+            UVec3::new(2, 2, 0),
+            UVec3::new(4, 2, 0),
+        ]
+    );
+    assert_eq!(model.chunks[2].chunk.shape.as_array(), [3, 4, 4]);
+    assert_eq!(model.chunks[2].chunk.max, UVec3::new(2, 3, 3));
+}
+
+#[test]
+fn chunks_are_padded_with_neighbouring_voxels() {
+    let (voxels, shape) = padded_model(UVec3::new(4, 1, 1), |_| true);
+    let model = AssetModel::from_voxels(voxels, shape, Some(2), None);
+    assert_eq!(model.chunks.len(), 2);
+
+    let voxel = |chunk: usize, x: u32| {
+        let chunk = &model.chunks[chunk].chunk;
+        chunk.voxels[chunk.shape.linearize([x, 1, 1]) as usize].idx
+    };
+    // The first chunk's interior holds model x 0 and 1, and its padding on the right holds x 2.
+    assert_eq!(
+        [voxel(0, 0), voxel(0, 1), voxel(0, 2), voxel(0, 3)],
+        [0, 1, 2, 3]
+    );
+    // The second chunk's padding on the left holds model x 1, and the model's padding on the right is empty.
+    assert_eq!(
+        [voxel(1, 0), voxel(1, 1), voxel(1, 2), voxel(1, 3)],
+        [2, 3, 4, 0]
+    );
+
+    // Faces between the chunks are culled by the padding.
+    let quads = |chunks: &[voxy::AssetChunk]| {
+        chunks
+            .iter()
+            .map(|chunk| chunk.chunk.mesh_stats().quads)
+            .sum::<usize>()
+    };
+    let (voxels, shape) = padded_model(UVec3::new(4, 1, 1), |_| true);
+    let whole = AssetModel::from_voxels(voxels, shape, None, None);
+    // Each voxel has its own colour, so none of their 4 sides are merged.
+    assert_eq!(quads(&whole.chunks), 4 * 4 + 2);
+    assert_eq!(quads(&model.chunks), quads(&whole.chunks));
+}
+
+#[test]
+fn empty_chunks_are_skipped() {
+    // Solid at x 0..2 and 4..6, with an empty gap at x 2..4.
+    let (voxels, shape) = padded_model(UVec3::new(6, 2, 2), |pos| !(2..4).contains(&pos.x));
+    let model = AssetModel::from_voxels(voxels, shape, Some(2), None);
+    let origins: Vec<_> = model
+        .chunks
+        .iter()
+        .map(|chunk| chunk.transform.translation.x)
+        .collect();
+    assert_eq!(origins, [0., 4.]);
+
+    // A chunk whose only solid voxels are in its padding is also skipped.
+    let (voxels, shape) = padded_model(UVec3::new(4, 1, 1), |pos| pos.x == 2);
+    let model = AssetModel::from_voxels(voxels, shape, Some(2), None);
+    assert_eq!(model.chunks.len(), 1);
+    assert_eq!(model.chunks[0].transform.translation.x, 2.);
+}
+
+#[test]
+fn file_chunks_match_unsplit_models() {
+    let asset = VoxFileAsset {
+        file: dot_vox::load("assets/character.vox").unwrap(),
+    };
+    let models: Vec<_> = asset.models(None).collect();
+    let chunks: Vec<_> = asset.chunks().collect();
+    assert_eq!(chunks.len(), models.len());
+    for (chunk, model) in chunks.iter().zip(&models) {
+        assert_eq!(chunk.transform, model.transform);
+        assert_eq!(chunk.name, model.name);
+        assert_eq!(chunk.chunk.voxels, model.chunks[0].chunk.voxels);
+    }
+
+    // Splitting keeps every voxel of each model.
+    let solid = |chunks: &[voxy::AssetChunk]| -> usize {
+        chunks
+            .iter()
+            .map(|chunk| {
+                let chunk = &chunk.chunk;
+                (0..chunk.shape.size())
+                    .map(|idx| UVec3::from(chunk.shape.delinearize(idx)))
+                    .filter(|pos| pos.cmpgt(chunk.min).all() && pos.cmplt(chunk.max).all())
+                    .filter(|pos| {
+                        chunk.voxels[chunk.shape.linearize(pos.to_array()) as usize].idx != 0
+                    })
+                    .count()
+            })
+            .sum()
+    };
+    let split: Vec<_> = asset.models(Some(4)).collect();
+    assert!(split.iter().any(|model| model.chunks.len() > 1));
+    for (split, model) in split.iter().zip(&models) {
+        assert_eq!(solid(&split.chunks), solid(&model.chunks));
+    }
+}
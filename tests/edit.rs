@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use ndshape::{RuntimeShape, Shape};
+use voxy::{AssetVoxel, AssetVoxelChunk, BrickMap, Brush, Chunk, EditBounds, VoxelVolume};
+
+const SOLID: AssetVoxel = AssetVoxel { idx: 1 };
+const RED: AssetVoxel = AssetVoxel { idx: 2 };
+
+/// A chunk with `size` editable voxels along each axis, plus padding.
+fn chunk(size: u32) -> AssetVoxelChunk {
+    let shape = RuntimeShape::<u32, 3>::new([size + 2; 3]);
+    Chunk::new(
+        vec![AssetVoxel::default(); shape.size() as usize],
+        shape,
+        UVec3::ZERO,
+        UVec3::splat(size + 1),
+    )
+}
+
+fn count<V: VoxelVolume<Voxel = AssetVoxel>>(volume: &V, voxel: AssetVoxel) -> usize {
+    let Some((min, max)) = volume.occupied() else {
+        return 0;
+    };
+    let mut count = 0;
+    for z in min.z..max.z {
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                if volume.get(IVec3::new(x, y, z)) == voxel {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+#[test]
+fn fill_sphere() {
+    let mut chunk = chunk(8);
+    let bounds = chunk.fill(
+        &Brush::Sphere {
+            center: Vec3::splat(4.5),
+            radius: 1.,
+        },
+        SOLID,
+    );
+
+    // The centre voxel and its six face neighbours.
+    assert_eq!(count(&chunk, SOLID), 7);
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: IVec3::splat(3),
+            max: IVec3::splat(6),
+        })
+    );
+
+    // Filling again changes nothing.
+    let bounds = chunk.fill(
+        &Brush::Sphere {
+            center: Vec3::splat(4.5),
+            radius: 1.,
+        },
+        SOLID,
+    );
+    assert_eq!(bounds, None);
+}
+
+#[test]
+fn fill_is_clipped_to_chunk() {
+    let mut chunk = chunk(4);
+    let bounds = chunk.fill(
+        &Brush::Cuboid {
+            min: IVec3::splat(-10),
+            max: IVec3::splat(10),
+        },
+        SOLID,
+    );
+
+    assert_eq!(count(&chunk, SOLID), 64);
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: IVec3::ONE,
+            max: IVec3::splat(5),
+        })
+    );
+
+    // Padding is left untouched.
+    assert_eq!(chunk.voxels[0], AssetVoxel::default());
+}
+
+#[test]
+fn erase_and_paint() {
+    let mut chunk = chunk(4);
+    chunk.fill(
+        &Brush::Cuboid {
+            min: IVec3::ONE,
+            max: IVec3::splat(5),
+        },
+        SOLID,
+    );
+
+    let bounds = chunk.erase(&Brush::Cuboid {
+        min: IVec3::new(1, 1, 1),
+        max: IVec3::new(3, 5, 5),
+    });
+    assert_eq!(count(&chunk, SOLID), 32);
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: IVec3::ONE,
+            max: IVec3::new(3, 5, 5),
+        })
+    );
+
+    // Painting only recolours existing voxels.
+    let bounds = chunk.paint(
+        &Brush::Cuboid {
+            min: IVec3::ONE,
+            max: IVec3::new(4, 2, 2),
+        },
+        RED,
+    );
+    assert_eq!(count(&chunk, RED), 1);
+    assert_eq!(bounds, Some(EditBounds::voxel(IVec3::new(3, 1, 1))));
+}
+
+#[test]
+fn cylinder_and_line() {
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    brickmap.fill(
+        &Brush::Cylinder {
+            start: Vec3::new(0.5, 0., 0.5),
+            end: Vec3::new(0.5, 4., 0.5),
+            radius: 1.,
+        },
+        SOLID,
+    );
+    // A plus-shaped cross-section, four voxels tall.
+    assert_eq!(count(&brickmap, SOLID), 20);
+
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    let bounds = brickmap.fill(&Brush::line(IVec3::ZERO, IVec3::new(5, 0, 0)), SOLID);
+    assert_eq!(count(&brickmap, SOLID), 6);
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: IVec3::ZERO,
+            max: IVec3::new(6, 1, 1),
+        })
+    );
+
+    // Diagonal lines stay connected.
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    brickmap.fill(&Brush::line(IVec3::ZERO, IVec3::splat(4)), SOLID);
+    for idx in 0..=4 {
+        assert_eq!(brickmap.get(IVec3::splat(idx)), SOLID);
+    }
+}
+
+#[test]
+fn csg() {
+    let mut a = BrickMap::new(AssetVoxel::default());
+    a.fill(
+        &Brush::Cuboid {
+            min: IVec3::ZERO,
+            max: IVec3::splat(4),
+        },
+        SOLID,
+    );
+    let mut b = BrickMap::new(AssetVoxel::default());
+    b.fill(
+        &Brush::Cuboid {
+            min: IVec3::ZERO,
+            max: IVec3::splat(4),
+        },
+        RED,
+    );
+
+    // Overlap a and b by a 2x4x4 slab.
+    let offset = IVec3::new(2, 0, 0);
+
+    let mut union = a.clone();
+    let bounds = union.union(&b, offset);
+    assert_eq!(count(&union, SOLID), 32);
+    assert_eq!(count(&union, RED), 64);
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: offset,
+            max: IVec3::new(6, 4, 4),
+        })
+    );
+
+    let mut subtract = a.clone();
+    let bounds = subtract.subtract(&b, offset);
+    assert_eq!(count(&subtract, SOLID), 32);
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: offset,
+            max: IVec3::splat(4),
+        })
+    );
+
+    let mut intersect = a.clone();
+    let bounds = intersect.intersect(&b, offset);
+    assert_eq!(count(&intersect, SOLID), 32);
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: IVec3::ZERO,
+            max: IVec3::new(2, 4, 4),
+        })
+    );
+
+    // Volumes of different storage can be combined.
+    let mut chunk = chunk(8);
+    chunk.union(&b, IVec3::ONE);
+    assert_eq!(count(&chunk, RED), 64);
+}
+
+#[test]
+fn edit_bounds_chunks() {
+    let bounds = EditBounds::voxel(IVec3::new(8, 3, 3));
+    let mut chunks: Vec<_> = bounds.chunks(UVec3::splat(8)).collect();
+    chunks.sort_by_key(|chunk| chunk.to_array());
+
+    // The voxel is in chunk (1, 0, 0) and the padding of chunk (0, 0, 0).
+    assert_eq!(chunks, vec![IVec3::ZERO, IVec3::X]);
+}
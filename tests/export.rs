@@ -0,0 +1,307 @@
+use bevy::prelude::*;
+use ndshape::{RuntimeShape, Shape};
+use std::collections::HashMap;
+use voxy::{
+    AssetVoxel, AssetVoxelChunk, Chunk, ExportMesh, VoxFileAsset, VoxelScene,
+    export::{write_gltf, write_obj, write_ply},
+};
+
+struct Obj {
+    positions: Vec<Vec3>,
+    colors: Vec<Vec3>,
+    normals: usize,
+    faces: Vec<[usize; 3]>,
+    /// The material of each face.
+    face_materials: Vec<String>,
+}
+
+fn parse_obj(obj: &str) -> Obj {
+    let mut parsed = Obj {
+        positions: Vec::new(),
+        colors: Vec::new(),
+        normals: 0,
+        faces: Vec::new(),
+        face_materials: Vec::new(),
+    };
+    let mut material = String::new();
+
+    for line in obj.lines() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => {
+                let values: Vec<f32> = words.map(|word| word.parse().unwrap()).collect();
+                assert_eq!(values.len(), 6);
+                parsed.positions.push(Vec3::from_slice(&values[..3]));
+                parsed.colors.push(Vec3::from_slice(&values[3..]));
+            }
+            Some("vn") => parsed.normals += 1,
+            Some("usemtl") => material = words.next().unwrap().to_owned(),
+            Some("f") => {
+                let face: Vec<usize> = words
+                    .map(|word| word.split("//").next().unwrap().parse().unwrap())
+                    .collect();
+                parsed.faces.push([face[0], face[1], face[2]]);
+                parsed.face_materials.push(material.clone());
+            }
+            _ => {}
+        }
+    }
+    parsed
+}
+
+fn parse_mtl(mtl: &str) -> HashMap<String, Vec3> {
+    let mut materials = HashMap::new();
+    let mut name = String::new();
+    for line in mtl.lines() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("newmtl") => name = words.next().unwrap().to_owned(),
+            Some("Kd") => {
+                let values: Vec<f32> = words.map(|word| word.parse().unwrap()).collect();
+                materials.insert(name.clone(), Vec3::from_slice(&values));
+            }
+            _ => {}
+        }
+    }
+    materials
+}
+
+struct Ply {
+    positions: Vec<Vec3>,
+    colors: Vec<[u8; 3]>,
+    faces: usize,
+}
+
+fn parse_ply(ply: &str) -> Ply {
+    let mut lines = ply.lines();
+    assert_eq!(lines.next(), Some("ply"));
+
+    let mut vertices = 0;
+    let mut faces = 0;
+    for line in lines.by_ref() {
+        if let Some(count) = line.strip_prefix("element vertex ") {
+            vertices = count.parse().unwrap();
+        } else if let Some(count) = line.strip_prefix("element face ") {
+            faces = count.parse().unwrap();
+        } else if line == "end_header" {
+            break;
+        }
+    }
+
+    let mut parsed = Ply {
+        positions: Vec::new(),
+        colors: Vec::new(),
+        faces: 0,
+    };
+    for line in lines.by_ref().take(vertices) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        assert_eq!(words.len(), 9);
+        let position: Vec<f32> = words[..3]
+            .iter()
+            .map(|word| word.parse().unwrap())
+            .collect();
+        parsed.positions.push(Vec3::from_slice(&position));
+        parsed
+            .colors
+            .push([0, 1, 2].map(|idx| words[6 + idx].parse().unwrap()));
+    }
+    for line in lines {
+        assert!(line.starts_with("3 "));
+        parsed.faces += 1;
+    }
+    assert_eq!(parsed.positions.len(), vertices);
+    assert_eq!(parsed.faces, faces);
+    parsed
+}
+
+fn material() -> voxy::VoxelMaterial {
+    let mut material = voxy::QuantizedPalette::default().material();
+    material.colors[0] = Vec3::new(1., 0., 0.);
+    material.colors[1] = Vec3::new(0., 0.2, 1.);
+    material
+}
+
+/// Two voxels side by side, coloured with palette indices 0 and 1.
+fn chunk() -> AssetVoxelChunk {
+    let shape = RuntimeShape::<u32, 3>::new([4, 3, 3]);
+    let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+    voxels[shape.linearize([1, 1, 1]) as usize] = AssetVoxel { idx: 1 };
+    voxels[shape.linearize([2, 1, 1]) as usize] = AssetVoxel { idx: 2 };
+    Chunk::new(voxels, shape, UVec3::ZERO, UVec3::new(3, 2, 2))
+}
+
+fn obj(meshes: &[ExportMesh], material: &voxy::VoxelMaterial) -> (String, String) {
+    let mut obj = Vec::new();
+    let mut mtl = Vec::new();
+    write_obj(meshes, material, "chunk.mtl", &mut obj, &mut mtl).unwrap();
+    (
+        String::from_utf8(obj).unwrap(),
+        String::from_utf8(mtl).unwrap(),
+    )
+}
+
+fn ply(meshes: &[ExportMesh]) -> String {
+    let mut ply = Vec::new();
+    write_ply(meshes, &mut ply).unwrap();
+    String::from_utf8(ply).unwrap()
+}
+
+#[test]
+fn chunk_obj() {
+    let material = material();
+    let mesh = chunk().build();
+    let meshes = [ExportMesh::from_mesh("chunk", &mesh, &material).unwrap()];
+    let (obj, mtl) = obj(&meshes, &material);
+    assert!(obj.contains("mtllib chunk.mtl"));
+
+    let parsed = parse_obj(&obj);
+    assert_eq!(parsed.positions.len(), mesh.count_vertices());
+    assert_eq!(parsed.normals, mesh.count_vertices());
+    assert_eq!(parsed.faces.len(), mesh.indices().unwrap().len() / 3);
+    assert!(
+        parsed
+            .faces
+            .iter()
+            .flatten()
+            .all(|idx| (1..=parsed.positions.len()).contains(idx))
+    );
+
+    // Each face uses the palette colour of its voxel, and its vertices have the same colour.
+    let materials = parse_mtl(&mtl);
+    assert_eq!(materials.len(), 2);
+    for (face, name) in parsed.faces.iter().zip(&parsed.face_materials) {
+        let kd = materials[name];
+        assert!(kd == material.colors[0] || kd == material.colors[1]);
+        for idx in face {
+            assert_eq!(parsed.colors[idx - 1], kd);
+        }
+    }
+}
+
+#[test]
+fn chunk_ply() {
+    let material = material();
+    let mesh = chunk().build();
+    let meshes = [ExportMesh::from_mesh("chunk", &mesh, &material).unwrap()];
+    let parsed = parse_ply(&ply(&meshes));
+    assert_eq!(parsed.positions.len(), mesh.count_vertices());
+    assert_eq!(parsed.faces, mesh.indices().unwrap().len() / 3);
+
+    let red = [255, 0, 0];
+    let blue = Color::linear_rgb(0., 0.2, 1.).to_srgba().to_u8_array();
+    let blue = [blue[0], blue[1], blue[2]];
+    for (position, color) in parsed.positions.iter().zip(&parsed.colors) {
+        // The left voxel spans x = 1..2 and the right x = 2..3.
+        if position.x < 2. {
+            assert_eq!(*color, red);
+        } else if position.x > 2. {
+            assert_eq!(*color, blue);
+        } else {
+            assert!(*color == red || *color == blue);
+        }
+    }
+    assert!(parsed.colors.contains(&red) && parsed.colors.contains(&blue));
+}
+
+#[test]
+fn packed_meshes_match() {
+    let material = material();
+    let chunk = chunk();
+    let float = ExportMesh::from_mesh("float", &chunk.build(), &material).unwrap();
+    let packed = ExportMesh::from_mesh("packed", &chunk.build_packed(), &material).unwrap();
+
+    let mut float_positions: Vec<_> = float.positions.iter().map(|p| p.to_array()).collect();
+    let mut packed_positions: Vec<_> = packed.positions.iter().map(|p| p.to_array()).collect();
+    float_positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    packed_positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(float_positions, packed_positions);
+
+    let mut float_colors: Vec<_> = float.colors.iter().map(|c| c.to_array()).collect();
+    let mut packed_colors: Vec<_> = packed.colors.iter().map(|c| c.to_array()).collect();
+    float_colors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    packed_colors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(float_colors, packed_colors);
+}
+
+#[test]
+fn scene_export() {
+    let file = dot_vox::load("assets/character.vox").unwrap();
+    let asset = VoxFileAsset { file };
+    let scene = VoxelScene::from_models(asset.models(Some(16)), asset.material(), false);
+    let stats = scene.stats();
+    let meshes = ExportMesh::from_scene(&scene);
+
+    let (obj, mtl) = obj(&meshes, &scene.material);
+    let parsed = parse_obj(&obj);
+    assert_eq!(parsed.positions.len(), stats.total.vertices);
+    assert_eq!(parsed.faces.len(), stats.total.triangles);
+    assert_eq!(
+        obj.lines().filter(|line| line.starts_with("o ")).count(),
+        meshes.len()
+    );
+    let materials = parse_mtl(&mtl);
+    assert!(
+        parsed
+            .face_materials
+            .iter()
+            .all(|name| materials.contains_key(name))
+    );
+
+    // Positions are in scene space.
+    let (min, max) = stats.total.bounds.unwrap();
+    for position in &parsed.positions {
+        assert!(position.cmpge(min - 1e-3).all() && position.cmple(max + 1e-3).all());
+    }
+
+    let parsed = parse_ply(&ply(&meshes));
+    assert_eq!(parsed.positions.len(), stats.total.vertices);
+    assert_eq!(parsed.faces, stats.total.triangles);
+}
+
+#[test]
+fn scene_gltf() {
+    let file = dot_vox::load("assets/character.vox").unwrap();
+    let asset = VoxFileAsset { file };
+    let scene = VoxelScene::from_models(asset.models(Some(16)), asset.material(), false);
+    let stats = scene.stats();
+    let meshes = ExportMesh::from_scene(&scene);
+
+    let mut glb = Vec::new();
+    write_gltf(&meshes, true, &mut glb).unwrap();
+    assert_eq!(&glb[0..4], b"glTF");
+    let u32_at = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+    assert_eq!(u32_at(8) as usize, glb.len());
+    assert_eq!(&glb[16..20], b"JSON");
+    let json_len = u32_at(12) as usize;
+    let json: serde_json::Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+    assert_eq!(&glb[24 + json_len..28 + json_len], b"BIN\0");
+    assert_eq!(
+        json["buffers"][0]["byteLength"].as_u64().unwrap() as usize,
+        u32_at(20 + json_len) as usize
+    );
+
+    assert_eq!(json["nodes"].as_array().unwrap().len(), meshes.len());
+    // Sum the counts of the accessor at `pointer` in each mesh's primitive.
+    let accessor_count = |pointer: &str| -> usize {
+        json["meshes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|mesh| {
+                let accessor = mesh["primitives"][0].pointer(pointer).unwrap();
+                json["accessors"][accessor.as_u64().unwrap() as usize]["count"]
+                    .as_u64()
+                    .unwrap() as usize
+            })
+            .sum()
+    };
+    assert_eq!(accessor_count("/attributes/POSITION"), stats.total.vertices);
+    assert_eq!(accessor_count("/indices"), stats.total.indices);
+
+    // The text variant embeds the same buffer as a data URI.
+    let mut gltf = Vec::new();
+    write_gltf(&meshes, false, &mut gltf).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&gltf).unwrap();
+    let uri = json["buffers"][0]["uri"].as_str().unwrap();
+    assert!(uri.starts_with("data:application/octet-stream;base64,"));
+}
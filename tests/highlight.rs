@@ -0,0 +1,191 @@
+use bevy::{asset::AssetPlugin, ecs::system::SystemState, mesh::MeshTag, prelude::*};
+use voxy::{
+    HighlightPlugin, PaletteAnimation, PaletteAnimationPlugin, PalettePlugin, ScenePlugin,
+    VoxelHighlight, VoxelMaterial, VoxelModel, VoxelScene,
+    scene::{LitMesh, VoxelSceneHandle, VoxelSceneModels},
+};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        ScenePlugin,
+        PalettePlugin,
+        PaletteAnimationPlugin,
+        HighlightPlugin,
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<VoxelMaterial>();
+    app
+}
+
+fn scene(translation: f32) -> VoxelScene {
+    let models = ["left", "right"]
+        .into_iter()
+        .map(|name| VoxelModel {
+            meshes: vec![
+                LitMesh::new(Cuboid::default().into(), Transform::default()),
+                LitMesh::new(Cuboid::default().into(), Transform::from_xyz(0., 1., 0.)),
+            ],
+            name: Some(name.to_string()),
+            path: vec![name.to_string()],
+            transform: Transform::from_xyz(translation, 0., 0.),
+        })
+        .collect();
+
+    VoxelScene {
+        models,
+        material: VoxelMaterial {
+            colors: [Vec3::ONE; 256],
+            emissions: [Vec3::ZERO; 256],
+        },
+        material_handle: None,
+    }
+}
+
+fn update(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn spawn_scene(app: &mut App) -> (Handle<VoxelScene>, Entity) {
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene(0.));
+    let root = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    update(app);
+    (handle, root)
+}
+
+fn model(app: &App, root: Entity, name: &str) -> Entity {
+    app.world().get::<VoxelSceneModels>(root).unwrap().entities[name]
+}
+
+/// The mesh tags and materials of the chunk meshes below `entity`.
+fn chunks(app: &mut App, entity: Entity) -> Vec<(Option<u32>, AssetId<VoxelMaterial>)> {
+    let mut state = SystemState::<(
+        Query<&Children>,
+        Query<(Option<&MeshTag>, &MeshMaterial3d<VoxelMaterial>)>,
+    )>::new(app.world_mut());
+    let (children_query, mesh_query) = state.get(app.world());
+    children_query
+        .iter_descendants(entity)
+        .filter_map(|descendant| mesh_query.get(descendant).ok())
+        .map(|(tag, material)| (tag.map(|tag| tag.0), material.id()))
+        .collect()
+}
+
+fn tags(app: &mut App, entity: Entity) -> Vec<Option<u32>> {
+    chunks(app, entity)
+        .into_iter()
+        .map(|(tag, _)| tag)
+        .collect()
+}
+
+#[test]
+fn tag_packs_linear_color() {
+    assert_eq!(VoxelHighlight::new(LinearRgba::NONE).tag(), MeshTag(0));
+    assert_eq!(
+        VoxelHighlight::new(LinearRgba::new(1., 0., 0., 0.5)).tag(),
+        MeshTag(u32::from_le_bytes([255, 0, 0, 128]))
+    );
+}
+
+#[test]
+fn nearest_highlight_wins() {
+    let mut app = app();
+    let (_, root) = spawn_scene(&mut app);
+    let left = model(&app, root, "left");
+    let right = model(&app, root, "right");
+    assert_eq!(tags(&mut app, root), [None; 4]);
+
+    let red = VoxelHighlight::new(LinearRgba::RED);
+    let blue = VoxelHighlight::new(LinearRgba::BLUE);
+    app.world_mut().entity_mut(root).insert(red);
+    app.world_mut().entity_mut(left).insert(blue);
+    update(&mut app);
+    assert_eq!(tags(&mut app, left), [Some(blue.tag().0); 2]);
+    assert_eq!(tags(&mut app, right), [Some(red.tag().0); 2]);
+
+    // Removing the model's highlight falls back to the scene's.
+    app.world_mut().entity_mut(left).remove::<VoxelHighlight>();
+    update(&mut app);
+    assert_eq!(tags(&mut app, root), [Some(red.tag().0); 4]);
+
+    // Changing the scene's highlight updates every chunk.
+    let green = VoxelHighlight::new(LinearRgba::GREEN);
+    app.world_mut().entity_mut(root).insert(green);
+    update(&mut app);
+    assert_eq!(tags(&mut app, root), [Some(green.tag().0); 4]);
+
+    app.world_mut().entity_mut(root).remove::<VoxelHighlight>();
+    update(&mut app);
+    assert_eq!(tags(&mut app, root), [None; 4]);
+}
+
+#[test]
+fn highlights_keep_the_material() {
+    let mut app = app();
+    let (_, root) = spawn_scene(&mut app);
+    let left = model(&app, root, "left");
+    let materials = app.world().resource::<Assets<VoxelMaterial>>().len();
+
+    // A highlighted model inside an animated scene.
+    app.world_mut()
+        .entity_mut(root)
+        .insert(PaletteAnimation::default().with_pulse(1, 0., 1., 1.));
+    app.world_mut()
+        .entity_mut(left)
+        .insert(VoxelHighlight::new(LinearRgba::RED));
+    update(&mut app);
+
+    // The scene gets one animated copy of its material, shared by every chunk.
+    let chunks = chunks(&mut app, root);
+    assert!(chunks.iter().all(|(_, material)| *material == chunks[0].1));
+    assert_eq!(
+        app.world().resource::<Assets<VoxelMaterial>>().len(),
+        materials + 1
+    );
+    assert_eq!(
+        tags(&mut app, left),
+        [Some(VoxelHighlight::new(LinearRgba::RED).tag().0); 2]
+    );
+
+    // No materials are created while the animation and highlight run.
+    for _ in 0..10 {
+        update(&mut app);
+    }
+    assert_eq!(
+        app.world().resource::<Assets<VoxelMaterial>>().len(),
+        materials + 1
+    );
+    assert_eq!(self::chunks(&mut app, root), chunks);
+}
+
+#[test]
+fn reloaded_chunks_are_highlighted() {
+    let mut app = app();
+    let (handle, root) = spawn_scene(&mut app);
+    let right = model(&app, root, "right");
+    let highlight = VoxelHighlight::new(LinearRgba::BLUE);
+    app.world_mut().entity_mut(right).insert(highlight);
+    update(&mut app);
+    let old_chunks = app.world().get::<Children>(right).unwrap().to_vec();
+
+    app.world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .insert(&handle, scene(2.))
+        .unwrap();
+    update(&mut app);
+
+    // The model keeps its entity and highlight, and its new chunks are tagged.
+    assert_eq!(model(&app, root, "right"), right);
+    let new_chunks = app.world().get::<Children>(right).unwrap().to_vec();
+    assert!(new_chunks.iter().all(|chunk| !old_chunks.contains(chunk)));
+    assert_eq!(tags(&mut app, right), [Some(highlight.tag().0); 2]);
+    let left = model(&app, root, "left");
+    assert_eq!(tags(&mut app, left), [None; 2]);
+}
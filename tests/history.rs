@@ -0,0 +1,302 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{
+    AssetModel, AssetVoxel, AssetVoxelChunk, BrickMap, Brush, Chunk, EditBounds, EditHistory,
+    ScenePlugin, VoxelEdit, VoxelMaterial, VoxelScene, VoxelSceneChunk, VoxelVolume,
+    scene::VoxelSceneHandle,
+};
+
+const SOLID: AssetVoxel = AssetVoxel { idx: 1 };
+const RED: AssetVoxel = AssetVoxel { idx: 2 };
+
+fn chunk(size: u32) -> AssetVoxelChunk {
+    let shape = RuntimeShape::<u32, 3>::new([size + 2; 3]);
+    Chunk::new(
+        vec![AssetVoxel::default(); shape.size() as usize],
+        shape,
+        UVec3::ZERO,
+        UVec3::splat(size + 1),
+    )
+}
+
+fn cube(min: i32, max: i32) -> Brush {
+    Brush::Cuboid {
+        min: IVec3::splat(min),
+        max: IVec3::splat(max),
+    }
+}
+
+#[test]
+fn undo_redo() {
+    let mut chunk = chunk(8);
+    let mut history = EditHistory::default();
+
+    let bounds = history.edit(&mut chunk, |chunk| {
+        chunk.fill(&cube(1, 5), SOLID);
+        chunk.paint(&cube(1, 3), RED);
+    });
+    assert_eq!(
+        bounds,
+        Some(EditBounds {
+            min: IVec3::ONE,
+            max: IVec3::splat(5),
+        })
+    );
+    let edited = chunk.voxels.clone();
+
+    history.edit(&mut chunk, |chunk| {
+        chunk.erase(&cube(1, 9));
+    });
+    assert!(
+        chunk
+            .voxels
+            .iter()
+            .all(|voxel| *voxel == AssetVoxel::default())
+    );
+
+    history.undo(&mut chunk);
+    assert_eq!(chunk.voxels, edited);
+    history.undo(&mut chunk);
+    assert!(
+        chunk
+            .voxels
+            .iter()
+            .all(|voxel| *voxel == AssetVoxel::default())
+    );
+    assert_eq!(history.undo(&mut chunk), None);
+
+    history.redo(&mut chunk);
+    assert_eq!(chunk.voxels, edited);
+    assert_eq!((history.undo_len(), history.redo_len()), (1, 1));
+
+    // A new edit clears the redo stack.
+    history.edit(&mut chunk, |chunk| {
+        chunk.fill(&cube(6, 7), SOLID);
+    });
+    assert_eq!(history.redo(&mut chunk), None);
+}
+
+#[test]
+fn unchanged_edits_are_not_recorded() {
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    let mut history = EditHistory::default();
+
+    let bounds = history.edit(&mut brickmap, |brickmap| {
+        brickmap.fill(&cube(0, 4), SOLID);
+        brickmap.erase(&cube(0, 4));
+    });
+    assert_eq!(bounds, None);
+    assert_eq!(history.undo_len(), 0);
+}
+
+#[test]
+fn edits_are_compressed() {
+    let changes = (0..1000).map(|x| (IVec3::new(x, 0, 0), AssetVoxel::default(), SOLID));
+    let edit = VoxelEdit::from_changes(changes).unwrap();
+    assert_eq!(edit.len(), 1000);
+    assert!(edit.memory_usage() < 128);
+
+    let changes: Vec<_> = edit.changes().collect();
+    assert_eq!(changes.len(), 1000);
+    assert_eq!(
+        changes[999],
+        (IVec3::new(999, 0, 0), AssetVoxel::default(), SOLID)
+    );
+}
+
+#[test]
+fn memory_limit_drops_oldest_edits() {
+    let mut brickmap = BrickMap::new(AssetVoxel::default());
+    let mut history = EditHistory::new(0);
+
+    for idx in 0..4 {
+        history.edit(&mut brickmap, |brickmap| {
+            brickmap.fill(&Brush::line(IVec3::ZERO, IVec3::X * 4), AssetVoxel { idx });
+        });
+    }
+
+    // Only the latest edit is kept.
+    assert_eq!(history.undo_len(), 1);
+    history.undo(&mut brickmap);
+    assert_eq!(brickmap.get(IVec3::ZERO), AssetVoxel { idx: 2 });
+
+    history.set_memory_limit(usize::MAX);
+    history.redo(&mut brickmap);
+    history.edit(&mut brickmap, |brickmap| {
+        brickmap.erase(&cube(0, 5));
+    });
+    assert_eq!(history.undo_len(), 2);
+}
+
+#[test]
+fn editing_scene_chunks_remeshes() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<VoxelMaterial>();
+
+    let mut voxels = chunk(4);
+    voxels.fill(&cube(1, 5), SOLID);
+    let mesh = app
+        .world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(voxels.build());
+    let entity = app
+        .world_mut()
+        .spawn((
+            VoxelSceneChunk {
+                voxels: Some(voxels.into()),
+                origin: IVec3::ZERO,
+            },
+            Mesh3d(mesh.clone()),
+            EditHistory::<AssetVoxel>::default(),
+        ))
+        .id();
+    app.update();
+    assert_eq!(app.world().get::<Mesh3d>(entity).unwrap().0, mesh);
+
+    let mut entity_mut = app.world_mut().entity_mut(entity);
+    let mut history = entity_mut.take::<EditHistory<AssetVoxel>>().unwrap();
+    let mut chunk = entity_mut.get_mut::<VoxelSceneChunk>().unwrap();
+    history.edit(chunk.voxels_mut().unwrap(), |voxels| {
+        voxels.erase(&cube(1, 3));
+    });
+    entity_mut.insert(history);
+    app.update();
+
+    let edited = app.world().get::<Mesh3d>(entity).unwrap().0.clone();
+    assert_ne!(edited, mesh);
+
+    // Undoing remeshes again.
+    let mut entity_mut = app.world_mut().entity_mut(entity);
+    let mut history = entity_mut.take::<EditHistory<AssetVoxel>>().unwrap();
+    let mut chunk = entity_mut.get_mut::<VoxelSceneChunk>().unwrap();
+    history.undo(chunk.voxels_mut().unwrap());
+    app.update();
+
+    let undone = app.world().get::<Mesh3d>(entity).unwrap().0.clone();
+    assert_ne!(undone, edited);
+    let meshes = app.world().resource::<Assets<Mesh>>();
+    assert_eq!(
+        meshes.get(&undone).unwrap().count_vertices(),
+        meshes.get(&mesh).unwrap().count_vertices()
+    );
+}
+
+/// Edit the voxels of `entity` with its history, and update the app.
+fn edit_chunk(
+    app: &mut App,
+    entity: Entity,
+    f: impl FnOnce(&mut EditHistory<AssetVoxel>, &mut AssetVoxelChunk),
+) {
+    let mut entity_mut = app.world_mut().entity_mut(entity);
+    let mut history = entity_mut.take::<EditHistory<AssetVoxel>>().unwrap();
+    let mut chunk = entity_mut.get_mut::<VoxelSceneChunk>().unwrap();
+    f(&mut history, chunk.voxels_mut().unwrap());
+    entity_mut.insert(history);
+    app.update();
+}
+
+#[test]
+fn editing_chunk_borders_updates_neighbours() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<VoxelMaterial>();
+
+    // A solid model of 4x2x2 voxels, split into two chunks along x.
+    let shape = RuntimeShape::<u32, 3>::new([6, 4, 4]);
+    let voxels = (0..shape.size())
+        .map(|idx| {
+            let pos = UVec3::from(shape.delinearize(idx));
+            let interior = pos.cmpgt(UVec3::ZERO).all() && pos.cmplt(UVec3::new(5, 3, 3)).all();
+            if interior {
+                SOLID
+            } else {
+                AssetVoxel::default()
+            }
+        })
+        .collect();
+    let model = AssetModel::from_voxels(voxels, shape, Some(2), None);
+    let mut emissions = [Vec3::ZERO; 256];
+    emissions[RED.idx as usize] = Vec3::X;
+    let material = VoxelMaterial {
+        colors: [Vec3::ONE; 256],
+        emissions,
+    };
+    let scene = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(VoxelScene::from_models([model], material, false));
+    app.world_mut().spawn(VoxelSceneHandle(scene));
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let mut chunks = app
+        .world_mut()
+        .query::<(Entity, &VoxelSceneChunk)>()
+        .iter(app.world())
+        .map(|(entity, chunk)| (chunk.origin.x, entity))
+        .collect::<Vec<_>>();
+    chunks.sort();
+    let [(_, left), (_, right)] = chunks[..] else {
+        panic!("expected two chunks, got {chunks:?}");
+    };
+    for entity in [left, right] {
+        app.world_mut()
+            .entity_mut(entity)
+            .insert(EditHistory::<AssetVoxel>::default());
+    }
+
+    let padding = |app: &App| {
+        // Edits can't reach padding voxels, so read them directly.
+        let chunk = app.world().get::<VoxelSceneChunk>(right).unwrap();
+        let voxels = chunk.voxels.as_ref().unwrap();
+        voxels.voxels[voxels.shape.linearize([0, 1, 1]) as usize]
+    };
+    let vertices = |app: &App| {
+        let mesh = &app.world().get::<Mesh3d>(right).unwrap().0;
+        let meshes = app.world().resource::<Assets<Mesh>>();
+        meshes.get(mesh).unwrap().count_vertices()
+    };
+    let lights = |app: &mut App, entity: Entity| {
+        app.world_mut()
+            .query_filtered::<&ChildOf, With<PointLight>>()
+            .iter(app.world())
+            .filter(|child_of| child_of.parent() == entity)
+            .count()
+    };
+    let solid_vertices = vertices(&app);
+    assert_eq!(padding(&app), SOLID);
+
+    // Erasing a voxel on the left chunk's border uncovers a face of the right chunk.
+    edit_chunk(&mut app, left, |history, voxels| {
+        history.edit(voxels, |voxels| {
+            voxels.set(IVec3::new(2, 1, 1), AssetVoxel::default());
+        });
+    });
+    assert_eq!(padding(&app), AssetVoxel::default());
+    assert!(vertices(&app) > solid_vertices);
+
+    edit_chunk(&mut app, left, |history, voxels| {
+        history.undo(voxels);
+    });
+    assert_eq!(padding(&app), SOLID);
+    assert_eq!(vertices(&app), solid_vertices);
+
+    // Lights follow emissive voxels through edits and undo.
+    assert_eq!(lights(&mut app, left), 0);
+    edit_chunk(&mut app, left, |history, voxels| {
+        history.edit(voxels, |voxels| {
+            voxels.set(IVec3::new(1, 1, 1), RED);
+        });
+    });
+    assert_eq!(lights(&mut app, left), 1);
+    assert_eq!(lights(&mut app, right), 0);
+
+    edit_chunk(&mut app, left, |history, voxels| {
+        history.undo(voxels);
+    });
+    assert_eq!(lights(&mut app, left), 0);
+}
@@ -0,0 +1,200 @@
+use bevy::{
+    asset::{LoadedFolder, RenderAssetUsages},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use voxy::{HeightmapSettings, SliceChannel, SliceSettings, VoxelScene};
+
+fn image(width: u32, height: u32, pixels: &[[u8; 4]]) -> Image {
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels.concat(),
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Returns the palette indices of the scene's single model by position, without padding.
+fn voxels(scene: &VoxelScene) -> Vec<(UVec3, u8)> {
+    assert_eq!(scene.models.len(), 1);
+    let lit_mesh = &scene.models[0].meshes[0];
+    let chunk = lit_mesh.voxels.as_ref().unwrap();
+    let size = chunk.max - UVec3::ONE;
+
+    let mut voxels = Vec::new();
+    for z in 0..size.z {
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let pos = UVec3::new(x, y, z);
+                let idx = ndshape::Shape::linearize(&chunk.shape, (pos + UVec3::ONE).to_array());
+                let voxel = chunk.voxels[idx as usize];
+                if voxel.idx != 0 {
+                    voxels.push((pos, voxel.idx));
+                }
+            }
+        }
+    }
+    voxels
+}
+
+#[test]
+fn heightmap_columns() {
+    // Heights of 0, 1, 2 and 4 voxels.
+    let heightmap = image(
+        2,
+        2,
+        &[
+            [0, 0, 0, 255],
+            [64, 64, 64, 255],
+            [128, 128, 128, 255],
+            [255, 255, 255, 255],
+        ],
+    );
+    let settings = HeightmapSettings {
+        max_height: 4,
+        gradient: vec![(0., Color::BLACK), (1., Color::WHITE)],
+        chunk_size: None,
+        ..default()
+    };
+    let scene = VoxelScene::from_heightmap(&heightmap, None, &settings);
+    let voxels = voxels(&scene);
+
+    let height = |x, z| {
+        voxels
+            .iter()
+            .filter(|(pos, _)| pos.x == x && pos.z == z)
+            .count()
+    };
+    assert_eq!(
+        [height(0, 0), height(1, 0), height(0, 1), height(1, 1)],
+        [0, 1, 2, 4]
+    );
+
+    // Each height level gets its own colour from the gradient, brightest at the top.
+    let top = voxels
+        .iter()
+        .find(|(pos, _)| *pos == UVec3::new(1, 3, 1))
+        .unwrap()
+        .1;
+    let bottom = voxels
+        .iter()
+        .find(|(pos, _)| *pos == UVec3::new(1, 0, 1))
+        .unwrap()
+        .1;
+    let colors = scene.material.colors;
+    assert!(colors[top as usize - 1].x > colors[bottom as usize - 1].x);
+
+    // Chunked heightmaps skip empty chunks.
+    let scene = VoxelScene::from_heightmap(
+        &heightmap,
+        None,
+        &HeightmapSettings {
+            chunk_size: Some(1),
+            ..settings
+        },
+    );
+    assert_eq!(scene.models[0].meshes.len(), 7);
+}
+
+#[test]
+fn heightmap_color_map() {
+    let heightmap = image(2, 1, &[[255, 255, 255, 255], [255, 255, 255, 255]]);
+    let color_map = image(2, 1, &[[255, 0, 0, 255], [0, 0, 255, 255]]);
+    let scene = VoxelScene::from_heightmap(
+        &heightmap,
+        Some(&color_map),
+        &HeightmapSettings {
+            max_height: 2,
+            chunk_size: None,
+            ..default()
+        },
+    );
+
+    let voxels = voxels(&scene);
+    assert_eq!(voxels.len(), 4);
+    for (pos, idx) in voxels {
+        let color = scene.material.colors[idx as usize - 1];
+        let expected = if pos.x == 0 { Vec3::X } else { Vec3::Z };
+        assert!(color.distance(expected) < 0.01);
+    }
+}
+
+#[test]
+fn slice_stack() {
+    let empty = [0, 0, 0, 0];
+    let red = [255, 0, 0, 255];
+    let grey = [100, 100, 100, 255];
+    let bottom = image(2, 2, &[red, empty, empty, red]);
+    let top = image(2, 2, &[empty, grey, empty, empty]);
+
+    let scene = VoxelScene::from_slices(
+        &[&bottom, &top],
+        &SliceSettings {
+            chunk_size: None,
+            ..default()
+        },
+    );
+    let positions: Vec<_> = voxels(&scene).into_iter().map(|(pos, _)| pos).collect();
+    assert_eq!(
+        positions,
+        [
+            UVec3::new(0, 0, 0),
+            UVec3::new(1, 1, 0),
+            UVec3::new(1, 0, 1)
+        ]
+    );
+
+    // Thresholding by luminance drops the darker red voxels.
+    let scene = VoxelScene::from_slices(
+        &[&bottom, &top],
+        &SliceSettings {
+            channel: SliceChannel::Luminance,
+            threshold: 0.3,
+            chunk_size: None,
+            ..default()
+        },
+    );
+    assert_eq!(voxels(&scene).len(), 1);
+}
+
+#[test]
+fn slice_folder() {
+    let empty = [0, 0, 0, 0];
+    let red = [255, 0, 0, 255];
+    let mut images = Assets::<Image>::default();
+    let mut meshes = Assets::<Mesh>::default();
+    let bottom = images.add(image(2, 2, &[red, empty, empty, red]));
+    let top = images.add(image(2, 2, &[empty, red, empty, empty]));
+
+    // Assets that aren't images are ignored.
+    let mut folder = LoadedFolder {
+        handles: vec![
+            bottom.untyped(),
+            meshes.add(Cuboid::default()).untyped(),
+            top.untyped(),
+        ],
+    };
+    let settings = SliceSettings {
+        chunk_size: None,
+        ..default()
+    };
+    let scene = VoxelScene::from_slice_folder(&folder, &images, &settings).unwrap();
+    let positions: Vec<_> = voxels(&scene).into_iter().map(|(pos, _)| pos).collect();
+    assert_eq!(
+        positions,
+        [
+            UVec3::new(0, 0, 0),
+            UVec3::new(1, 1, 0),
+            UVec3::new(1, 0, 1)
+        ]
+    );
+
+    // Folders with images that haven't loaded yet can't be built.
+    folder.handles.push(Handle::<Image>::default().untyped());
+    assert!(VoxelScene::from_slice_folder(&folder, &images, &settings).is_none());
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use voxy::{VoxelInstanceData, VoxelInstances, VoxelMaterial, VoxelPaletteEntry};
+
+fn material(color: Vec3, emission: Vec3) -> VoxelMaterial {
+    VoxelMaterial {
+        colors: [color; 256],
+        emissions: [emission; 256],
+    }
+}
+
+#[test]
+fn instance_data_is_in_world_space() {
+    let mut materials = Assets::<VoxelMaterial>::default();
+    let mut instances = VoxelInstances::new(materials.add(material(Vec3::ONE, Vec3::ZERO)));
+    instances.add_palette(materials.add(material(Vec3::ONE, Vec3::ZERO)));
+    instances.push(Transform::from_xyz(1., 2., 3.), 1);
+    // Palette indices past the last palette are clamped.
+    instances.push(Transform::from_scale(Vec3::splat(3.)), 5);
+
+    let global_transform =
+        GlobalTransform::from(Transform::from_xyz(10., 0., 0.).with_scale(Vec3::splat(2.)));
+    assert_eq!(
+        instances.instance_data(&global_transform),
+        [
+            VoxelInstanceData {
+                rows: [[2., 0., 0., 12.], [0., 2., 0., 4.], [0., 0., 2., 6.]],
+                palette: 1,
+            },
+            VoxelInstanceData {
+                rows: [[6., 0., 0., 10.], [0., 6., 0., 0.], [0., 0., 6., 0.]],
+                palette: 1,
+            },
+        ]
+    );
+
+    // The instance buffer layout is three rows followed by the palette index.
+    assert_eq!(size_of::<VoxelInstanceData>(), 3 * 16 + 4);
+}
+
+#[test]
+fn palette_data_waits_for_every_palette() {
+    let mut materials = Assets::<VoxelMaterial>::default();
+    let red = materials.add(material(Vec3::X, Vec3::ZERO));
+    let blue = materials.add(material(Vec3::Z, Vec3::new(2., 0.5, 0.)));
+
+    let mut instances = VoxelInstances::default();
+    assert_eq!(instances.palette_data(&materials), None);
+
+    instances.palettes = vec![red.clone(), blue.clone()];
+    let palettes = instances.palette_data(&materials).unwrap();
+    assert_eq!(palettes.len(), 512);
+    assert_eq!(
+        palettes[3],
+        VoxelPaletteEntry {
+            color: Vec4::new(1., 0., 0., 1.),
+            emissive: Vec4::ZERO,
+        }
+    );
+    assert_eq!(
+        palettes[256 + 3],
+        VoxelPaletteEntry {
+            color: Vec4::new(0., 0., 1., 1.),
+            emissive: Vec4::new(2., 0.5, 0., 0.),
+        }
+    );
+
+    // A palette that isn't loaded holds back the whole buffer.
+    instances.add_palette(Handle::default());
+    assert_eq!(instances.palette_data(&materials), None);
+}
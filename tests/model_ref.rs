@@ -0,0 +1,221 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use voxy::{
+    ScenePlugin, VoxelMaterial, VoxelModel, VoxelScene,
+    scene::{LitMesh, VoxelSceneHandle, VoxelSceneModel, VoxelSceneModelRef, VoxelSceneModels},
+};
+
+#[derive(Component)]
+struct Marker;
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<VoxelMaterial>();
+    app
+}
+
+/// A unit cube model named after the last segment of its `/`-separated path.
+fn model(path: &str, translation: Vec3) -> VoxelModel {
+    let path: Vec<_> = path.split('/').map(String::from).collect();
+    VoxelModel {
+        meshes: vec![LitMesh::new(Cuboid::default().into(), Transform::default())],
+        name: path.last().cloned(),
+        path,
+        transform: Transform::from_translation(translation),
+    }
+}
+
+fn scene(models: Vec<VoxelModel>) -> VoxelScene {
+    VoxelScene {
+        models,
+        material: VoxelMaterial {
+            colors: [Vec3::ONE; 256],
+            emissions: [Vec3::ZERO; 256],
+        },
+        material_handle: None,
+    }
+}
+
+fn forest() -> VoxelScene {
+    scene(vec![
+        model("tree_03/trunk", Vec3::ZERO),
+        model("tree_03/leaves", Vec3::new(0., 4., 0.)),
+        model("rock", Vec3::new(10., 0., 0.)),
+    ])
+}
+
+fn update(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// The names and translations of the models spawned under `root`, sorted by name.
+fn spawned(app: &App, root: Entity) -> Vec<(String, Vec3)> {
+    let mut models: Vec<_> = app
+        .world()
+        .get::<Children>(root)
+        .map(|children| children.to_vec())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|child| {
+            let model = app.world().get::<VoxelSceneModel>(child)?;
+            let transform = app.world().get::<Transform>(child)?;
+            Some((model.name.clone()?, transform.translation))
+        })
+        .collect();
+    models.sort_by(|(a, _), (b, _)| a.cmp(b));
+    models
+}
+
+#[test]
+fn matches_names_and_group_paths() {
+    let trunk = model("forest/tree_03/trunk", Vec3::ZERO);
+    for path in [
+        "trunk",
+        "tree_03",
+        "forest",
+        "tree_03/trunk",
+        "forest/tree_03",
+        "/tree_03/trunk/",
+    ] {
+        assert!(trunk.matches(path), "{path}");
+    }
+    for path in [
+        "",
+        "/",
+        "tree",
+        "trunk/tree_03",
+        "forest/trunk",
+        "tree_03/leaves",
+        "forest/tree_03/trunk/bark",
+    ] {
+        assert!(!trunk.matches(path), "{path}");
+    }
+}
+
+#[test]
+fn only_matching_models_are_spawned() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(forest());
+    let tree = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle.clone(), "tree_03"))
+        .id();
+    let leaves = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle.clone(), "tree_03/leaves"))
+        .id();
+    let recentered = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle.clone(), "tree_03").recentered())
+        .id();
+    let missing = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle, "tree_04"))
+        .id();
+    update(&mut app);
+
+    assert_eq!(
+        spawned(&app, tree),
+        [
+            (String::from("leaves"), Vec3::new(0., 4., 0.)),
+            (String::from("trunk"), Vec3::ZERO),
+        ]
+    );
+    assert_eq!(
+        spawned(&app, leaves),
+        [(String::from("leaves"), Vec3::new(0., 4., 0.))]
+    );
+
+    // The tree spans y -0.5 to 4.5, so re-centering moves it down by 2.
+    assert_eq!(
+        spawned(&app, recentered),
+        [
+            (String::from("leaves"), Vec3::new(0., 2., 0.)),
+            (String::from("trunk"), Vec3::new(0., -2., 0.)),
+        ]
+    );
+
+    assert!(spawned(&app, missing).is_empty());
+    assert!(
+        app.world()
+            .get::<VoxelSceneModels>(missing)
+            .unwrap()
+            .entities
+            .is_empty()
+    );
+}
+
+#[test]
+fn reload_updates_model_refs_in_place() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(forest());
+    let root = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle.clone(), "tree_03"))
+        .id();
+    update(&mut app);
+
+    let models = &app.world().get::<VoxelSceneModels>(root).unwrap().entities;
+    let trunk = models["trunk"];
+    let leaves = models["leaves"];
+    app.world_mut().entity_mut(trunk).insert(Marker);
+
+    app.world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .insert(
+            &handle,
+            scene(vec![
+                model("tree_03/trunk", Vec3::new(0., 1., 0.)),
+                model("tree_03/branch", Vec3::new(1., 3., 0.)),
+                model("rock", Vec3::new(12., 0., 0.)),
+            ]),
+        )
+        .unwrap();
+    update(&mut app);
+
+    // The trunk keeps its entity and components, the leaves are removed, and the new branch is spawned.
+    assert_eq!(
+        spawned(&app, root),
+        [
+            (String::from("branch"), Vec3::new(1., 3., 0.)),
+            (String::from("trunk"), Vec3::new(0., 1., 0.)),
+        ]
+    );
+    let models = &app.world().get::<VoxelSceneModels>(root).unwrap().entities;
+    assert_eq!(models["trunk"], trunk);
+    assert!(app.world().get::<Marker>(trunk).is_some());
+    assert!(app.world().get_entity(leaves).is_err());
+}
+
+#[test]
+fn scene_handle_takes_precedence_over_model_ref() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(forest());
+    let root = app
+        .world_mut()
+        .spawn((
+            VoxelSceneHandle(handle.clone()),
+            VoxelSceneModelRef::new(handle, "rock"),
+        ))
+        .id();
+    update(&mut app);
+
+    // The whole scene is spawned once.
+    let names: Vec<_> = spawned(&app, root)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, ["leaves", "rock", "trunk"]);
+}
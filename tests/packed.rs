@@ -0,0 +1,109 @@
+use bevy::{
+    camera::primitives::MeshAabb,
+    mesh::{Indices, VertexAttributeValues},
+    prelude::*,
+};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{
+    ATTRIBUTE_COLOR_INDEX, ATTRIBUTE_PACKED_VOXEL, AssetVoxel, Chunk, FACE_NORMALS,
+    PACKED_POSITION_MAX, VoxFileAsset, unpack_face, unpack_position,
+};
+
+fn asset() -> VoxFileAsset {
+    VoxFileAsset {
+        file: dot_vox::load("assets/character.vox").unwrap(),
+    }
+}
+
+#[test]
+fn packed_matches_unpacked() {
+    for asset_chunk in asset().chunks() {
+        let mesh = asset_chunk.chunk.build();
+        let packed_mesh = asset_chunk.chunk.build_packed();
+
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("missing positions");
+        };
+        let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        else {
+            panic!("missing normals");
+        };
+        let Some(VertexAttributeValues::Uint32(color_indices)) =
+            mesh.attribute(ATTRIBUTE_COLOR_INDEX)
+        else {
+            panic!("missing color indices");
+        };
+        let Some(VertexAttributeValues::Uint32x2(packed)) =
+            packed_mesh.attribute(ATTRIBUTE_PACKED_VOXEL)
+        else {
+            panic!("missing packed vertices");
+        };
+
+        assert!(!packed.is_empty());
+        assert_eq!(positions.len(), packed.len());
+        assert!(packed_mesh.attribute(Mesh::ATTRIBUTE_POSITION).is_none());
+
+        for (idx, packed) in packed.iter().enumerate() {
+            assert_eq!(unpack_position(*packed), Vec3::from(positions[idx]));
+            assert_eq!(
+                FACE_NORMALS[unpack_face(*packed) as usize],
+                Vec3::from(normals[idx])
+            );
+            assert_eq!(packed[1], color_indices[idx]);
+        }
+
+        let indices: Vec<_> = mesh.indices().unwrap().iter().collect();
+        let packed_indices: Vec<_> = packed_mesh.indices().unwrap().iter().collect();
+        assert_eq!(indices, packed_indices);
+        assert!(matches!(packed_mesh.indices(), Some(Indices::U16(_))));
+    }
+}
+
+#[test]
+fn packed_aabb_matches_positions() {
+    for asset_chunk in asset().chunks() {
+        let mesh = asset_chunk.chunk.build();
+        let packed_mesh = asset_chunk.chunk.build_packed();
+
+        assert_eq!(voxy::packed_aabb(&packed_mesh), mesh.compute_aabb());
+    }
+}
+
+#[test]
+fn oversized_chunk_falls_back_to_unpacked() {
+    // A row of voxels along x, with one voxel of padding around it.
+    let row = |len: u32| {
+        let shape = RuntimeShape::<u32, 3>::new([len + 2, 3, 3]);
+        let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+        for x in 1..=len {
+            voxels[shape.linearize([x, 1, 1]) as usize] = AssetVoxel { idx: 1 };
+        }
+        Chunk::new(voxels, shape, UVec3::ZERO, UVec3::new(len + 1, 2, 2))
+    };
+
+    let fits = row(PACKED_POSITION_MAX - 1).build_packed();
+    assert!(fits.attribute(ATTRIBUTE_PACKED_VOXEL).is_some());
+    assert_eq!(
+        voxy::packed_aabb(&fits).unwrap().max().x,
+        PACKED_POSITION_MAX as f32
+    );
+
+    let chunk = row(PACKED_POSITION_MAX);
+    let mesh = chunk.build_packed();
+    assert!(mesh.attribute(ATTRIBUTE_PACKED_VOXEL).is_none());
+    assert_eq!(
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().len(),
+        chunk
+            .build()
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .unwrap()
+            .len()
+    );
+    assert_eq!(
+        mesh.compute_aabb().unwrap().max().x,
+        PACKED_POSITION_MAX as f32 + 1.
+    );
+}
@@ -0,0 +1,247 @@
+use bevy::{asset::AssetPlugin, ecs::system::SystemState, prelude::*};
+use voxy::{
+    PalettePlugin, PaletteVariants, ScenePlugin, VoxelMaterial, VoxelModel, VoxelPaletteOverride,
+    VoxelScene,
+    scene::{LitMesh, VoxelSceneHandle, VoxelSceneModelRef},
+};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        ScenePlugin,
+        PalettePlugin,
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<VoxelMaterial>();
+    app
+}
+
+fn material() -> VoxelMaterial {
+    let mut material = VoxelMaterial {
+        colors: [Vec3::ONE; 256],
+        emissions: [Vec3::ZERO; 256],
+    };
+    material.colors[1] = Vec3::new(1., 0., 0.);
+    material.colors[2] = Vec3::new(0., 1., 0.);
+    material.emissions[2] = Vec3::splat(3.);
+    material
+}
+
+fn scene() -> VoxelScene {
+    let models = (0..2)
+        .map(|idx| VoxelModel {
+            meshes: vec![LitMesh::new(Cuboid::default().into(), Transform::default())],
+            name: Some(format!("model-{idx}")),
+            path: vec![format!("model-{idx}")],
+            transform: Transform::from_xyz(idx as f32, 0., 0.),
+        })
+        .collect();
+
+    VoxelScene {
+        models,
+        material: material(),
+        material_handle: None,
+    }
+}
+
+fn update(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// The materials of the chunk meshes spawned under `entity`.
+fn chunk_materials(app: &mut App, entity: Entity) -> Vec<AssetId<VoxelMaterial>> {
+    let mut state = SystemState::<(Query<&Children>, Query<&MeshMaterial3d<VoxelMaterial>>)>::new(
+        app.world_mut(),
+    );
+    let (children_query, material_query) = state.get(app.world());
+    children_query
+        .iter_descendants(entity)
+        .filter_map(|descendant| material_query.get(descendant).ok())
+        .map(|material| material.id())
+        .collect()
+}
+
+fn variant_count(app: &App) -> usize {
+    app.world().resource::<PaletteVariants>().len()
+}
+
+#[test]
+fn apply_remaps_colors_and_tints() {
+    let palette_override = VoxelPaletteOverride::tinted(LinearRgba::rgb(0.5, 0.5, 0.5))
+        .with_remap(1, 2)
+        .with_color(3, LinearRgba::rgb(0., 0., 1.));
+    let variant = palette_override.apply(&material());
+
+    // Remapped indices take both the colour and the emission of their target.
+    assert_eq!(variant.colors[1], Vec3::new(0., 0.5, 0.));
+    assert_eq!(variant.emissions[1], Vec3::splat(3.));
+    assert_eq!(variant.colors[2], Vec3::new(0., 0.5, 0.));
+    // Replaced colours are tinted too, and keep their emission.
+    assert_eq!(variant.colors[3], Vec3::new(0., 0., 0.5));
+    assert_eq!(variant.emissions[3], Vec3::ZERO);
+    assert_eq!(variant.colors[4], Vec3::splat(0.5));
+
+    // The default override leaves the palette unchanged.
+    let unchanged = VoxelPaletteOverride::default().apply(&material());
+    assert_eq!(unchanged.colors, material().colors);
+    assert_eq!(unchanged.emissions, material().emissions);
+}
+
+#[test]
+fn identical_overrides_share_a_variant() {
+    let mut app = app();
+    update(&mut app);
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene());
+    let red = VoxelPaletteOverride::tinted(LinearRgba::RED);
+    let first = app
+        .world_mut()
+        .spawn((VoxelSceneHandle(handle.clone()), red.clone()))
+        .id();
+    let second = app
+        .world_mut()
+        .spawn((VoxelSceneHandle(handle.clone()), red))
+        .id();
+    let plain = app.world_mut().spawn(VoxelSceneHandle(handle)).id();
+    update(&mut app);
+
+    let base = chunk_materials(&mut app, plain);
+    let variant = chunk_materials(&mut app, first);
+    assert_eq!(base.len(), 2);
+    assert_eq!(variant.len(), 2);
+    assert_ne!(base[0], variant[0]);
+    assert!(base.iter().all(|id| *id == base[0]));
+    assert!(variant.iter().all(|id| *id == variant[0]));
+    assert_eq!(chunk_materials(&mut app, second), variant);
+    assert_eq!(variant_count(&app), 1);
+
+    let materials = app.world().resource::<Assets<VoxelMaterial>>();
+    assert_eq!(materials.len(), 2);
+    assert_eq!(
+        materials.get(variant[0]).unwrap().colors[1],
+        Vec3::new(1., 0., 0.)
+    );
+    assert_eq!(materials.get(variant[0]).unwrap().colors[2], Vec3::ZERO);
+
+    // Changing an override switches to a different variant.
+    app.world_mut()
+        .entity_mut(second)
+        .insert(VoxelPaletteOverride::default().with_remap(1, 2));
+    update(&mut app);
+    let remapped = chunk_materials(&mut app, second);
+    assert_ne!(remapped[0], variant[0]);
+    assert_ne!(remapped[0], base[0]);
+    assert_eq!(chunk_materials(&mut app, first), variant);
+    assert_eq!(variant_count(&app), 2);
+}
+
+#[test]
+fn unused_variants_are_removed() {
+    let mut app = app();
+    update(&mut app);
+    let baseline = app.world().resource::<Assets<VoxelMaterial>>().len();
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene());
+    let tinted = app
+        .world_mut()
+        .spawn((
+            VoxelSceneHandle(handle.clone()),
+            VoxelPaletteOverride::tinted(LinearRgba::BLUE),
+        ))
+        .id();
+    let plain = app.world_mut().spawn(VoxelSceneHandle(handle)).id();
+    update(&mut app);
+    assert_eq!(variant_count(&app), 1);
+    assert_eq!(
+        app.world().resource::<Assets<VoxelMaterial>>().len(),
+        baseline + 2
+    );
+
+    // Removing the override restores the shared material and releases the variant.
+    app.world_mut()
+        .entity_mut(tinted)
+        .remove::<VoxelPaletteOverride>();
+    update(&mut app);
+    assert_eq!(
+        chunk_materials(&mut app, tinted),
+        chunk_materials(&mut app, plain)
+    );
+    assert_eq!(variant_count(&app), 0);
+    assert_eq!(
+        app.world().resource::<Assets<VoxelMaterial>>().len(),
+        baseline + 1
+    );
+
+    // Despawning the last instance using a variant also releases it.
+    app.world_mut()
+        .entity_mut(tinted)
+        .insert(VoxelPaletteOverride::tinted(LinearRgba::BLUE));
+    update(&mut app);
+    assert_eq!(variant_count(&app), 1);
+
+    // Changing the only override using a variant releases it.
+    app.world_mut()
+        .entity_mut(tinted)
+        .insert(VoxelPaletteOverride::tinted(LinearRgba::RED));
+    update(&mut app);
+    assert_eq!(variant_count(&app), 1);
+    assert_eq!(
+        app.world().resource::<Assets<VoxelMaterial>>().len(),
+        baseline + 2
+    );
+
+    app.world_mut().entity_mut(tinted).despawn();
+    update(&mut app);
+    assert_eq!(variant_count(&app), 0);
+    assert_eq!(
+        app.world().resource::<Assets<VoxelMaterial>>().len(),
+        baseline + 1
+    );
+}
+
+#[test]
+fn overrides_apply_to_model_refs() {
+    let mut app = app();
+    update(&mut app);
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene());
+    let plain = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle.clone(), "model-1"))
+        .id();
+    let tinted = app
+        .world_mut()
+        .spawn((
+            VoxelSceneModelRef::new(handle, "model-1"),
+            VoxelPaletteOverride::tinted(LinearRgba::GREEN),
+        ))
+        .id();
+    update(&mut app);
+
+    let base = chunk_materials(&mut app, plain);
+    let variant = chunk_materials(&mut app, tinted);
+    assert_eq!(base.len(), 1);
+    assert_eq!(variant.len(), 1);
+    assert_ne!(base, variant);
+    assert_eq!(variant_count(&app), 1);
+
+    app.world_mut()
+        .entity_mut(tinted)
+        .remove::<VoxelPaletteOverride>();
+    update(&mut app);
+    assert_eq!(chunk_materials(&mut app, tinted), base);
+    assert_eq!(variant_count(&app), 0);
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use voxy::{ColorKeyframes, PaletteAnimation, QuantizedPalette, VoxelMaterial};
+
+fn material() -> VoxelMaterial {
+    let mut material = QuantizedPalette::default().material();
+    for idx in 0..256 {
+        material.colors[idx] = Vec3::splat(idx as f32 / 255.);
+        material.emissions[idx] = Vec3::new(idx as f32, 0., 0.);
+    }
+    material
+}
+
+#[test]
+fn color_cycle_wraps() {
+    let base = material();
+    let mut animated = base.clone();
+    let animation = PaletteAnimation::default().with_cycle(10..=13, 1.);
+
+    animation.apply(&base, 0., &mut animated);
+    assert_eq!(animated.colors, base.colors);
+
+    // After one step each index shows the next one, and the last wraps to the first.
+    animation.apply(&base, 1.5, &mut animated);
+    assert_eq!(animated.colors[10], base.colors[11]);
+    assert_eq!(animated.colors[12], base.colors[13]);
+    assert_eq!(animated.colors[13], base.colors[10]);
+    assert_eq!(animated.emissions[13], base.emissions[10]);
+    assert_eq!(animated.colors[9], base.colors[9]);
+    assert_eq!(animated.colors[14], base.colors[14]);
+
+    // A full cycle restores the palette, including for negative times.
+    animation.apply(&base, 4., &mut animated);
+    assert_eq!(animated.colors, base.colors);
+    animation.apply(&base, -1., &mut animated);
+    assert_eq!(animated.colors[10], base.colors[13]);
+}
+
+#[test]
+fn keyframes_interpolate_and_loop() {
+    let keyframes = ColorKeyframes {
+        index: 1,
+        keyframes: vec![
+            (0., Color::linear_rgb(0., 0., 0.)),
+            (1., Color::linear_rgb(1., 0., 0.)),
+            (2., Color::linear_rgb(1., 1., 0.)),
+        ],
+    };
+    let sample = |time| keyframes.sample(time).unwrap().to_linear().to_vec3();
+
+    assert!(sample(0.).distance(Vec3::ZERO) < 1e-5);
+    assert!(sample(0.5).distance(Vec3::new(0.5, 0., 0.)) < 1e-5);
+    assert!(sample(1.5).distance(Vec3::new(1., 0.5, 0.)) < 1e-5);
+    // The animation loops after the last keyframe.
+    assert!(sample(2.5).distance(Vec3::new(0.5, 0., 0.)) < 1e-5);
+
+    assert!(
+        ColorKeyframes {
+            index: 0,
+            keyframes: Vec::new()
+        }
+        .sample(1.)
+        .is_none()
+    );
+
+    let base = material();
+    let mut animated = base.clone();
+    PaletteAnimation::default()
+        .with_keyframes(1, keyframes.keyframes.clone())
+        .apply(&base, 0.5, &mut animated);
+    assert!(animated.colors[1].distance(Vec3::new(0.5, 0., 0.)) < 1e-5);
+    assert_eq!(animated.colors[2], base.colors[2]);
+}
+
+#[test]
+fn emission_pulse_stays_in_range() {
+    let base = material();
+    let mut animated = base.clone();
+    let animation = PaletteAnimation::default().with_pulse(5, 0.5, 2., 2.);
+
+    let mut lowest = f32::INFINITY;
+    let mut highest = f32::NEG_INFINITY;
+    for step in 0..100 {
+        animation.apply(&base, step as f32 / 100., &mut animated);
+        let emission = animated.emissions[5].x;
+        assert!((0.5 - 1e-4..=2. + 1e-4).contains(&emission));
+        lowest = lowest.min(emission);
+        highest = highest.max(emission);
+    }
+    assert!(lowest < 0.51 && highest > 1.99);
+
+    // The wave starts halfway between `min` and `max`.
+    animation.apply(&base, 0., &mut animated);
+    assert!((animated.emissions[5].x - 1.25).abs() < 1e-4);
+    assert_eq!(animated.emissions[6], base.emissions[6]);
+}
@@ -0,0 +1,105 @@
+use bevy::{mesh::VertexAttributeValues, prelude::*};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{AssetVoxel, Chunk, PalettedVoxels};
+
+#[test]
+fn grows_from_1_to_16_bits() {
+    const LEN: usize = 1 << 17;
+    let mut voxels = PalettedVoxels::new(LEN, 0u32);
+    assert_eq!(voxels.bits_per_index(), 1);
+    assert_eq!(voxels.get(LEN - 1), 0);
+
+    // Each new palette entry past a power of two adds a bit to every index.
+    for value in 1..1u32 << 16 {
+        let idx = value as usize * 2;
+        assert_eq!(voxels.set(idx, value), 0);
+
+        let expected_bits = (u32::BITS - value.leading_zeros()).max(1);
+        assert_eq!(voxels.bits_per_index(), expected_bits, "value {value}");
+    }
+    assert_eq!(voxels.bits_per_index(), PalettedVoxels::<u32>::MAX_BITS);
+    assert_eq!(voxels.palette().len(), 1 << 16);
+
+    // Repacking keeps every voxel that was set at a smaller width.
+    for value in 0..1u32 << 16 {
+        let idx = value as usize * 2;
+        assert_eq!(voxels.get(idx), value);
+        assert_eq!(voxels.get(idx + 1), 0);
+    }
+}
+
+#[test]
+#[should_panic(expected = "palette exceeds")]
+fn palette_limit() {
+    let mut voxels = PalettedVoxels::new((1 << 16) + 1, 0u32);
+    for value in 0..=1u32 << 16 {
+        voxels.set(value as usize, value);
+    }
+}
+
+#[test]
+fn set_returns_previous_voxel() {
+    let mut voxels = PalettedVoxels::new(10, 'a');
+    assert_eq!(voxels.set(3, 'b'), 'a');
+    assert_eq!(voxels.set(3, 'c'), 'b');
+    assert_eq!(voxels.set(3, 'a'), 'c');
+    assert_eq!(voxels.get(3), 'a');
+    assert_eq!(voxels.palette(), ['a', 'b', 'c']);
+    assert_eq!(voxels.bits_per_index(), 2);
+}
+
+#[test]
+fn expand_round_trips() {
+    // Lengths that don't fill the last word, at widths that don't divide 64.
+    for (len, values) in [(0, 1), (1, 1), (63, 2), (100, 5), (1000, 37), (4097, 300)] {
+        let dense: Vec<u16> = (0..len).map(|idx| ((idx * 7) % values) as u16).collect();
+        let voxels = PalettedVoxels::from_slice(&dense);
+        assert_eq!(voxels.len(), len);
+        assert_eq!(voxels.is_empty(), len == 0);
+        assert_eq!(voxels.to_vec(), dense);
+
+        let mut scratch = vec![u16::MAX; 3];
+        voxels.expand_into(&mut scratch);
+        assert_eq!(scratch, dense);
+        for (idx, voxel) in dense.iter().enumerate() {
+            assert_eq!(voxels.get(idx), *voxel);
+        }
+    }
+}
+
+#[test]
+fn paletted_chunk_meshes_like_dense_chunk() {
+    let shape = RuntimeShape::<u32, 3>::new([10; 3]);
+    let mut dense = vec![AssetVoxel::default(); shape.size() as usize];
+    for (idx, voxel) in dense.iter_mut().enumerate() {
+        let [x, y, z] = shape.delinearize(idx as u32);
+        if (1..9).contains(&x) && (1..9).contains(&z) && y > 0 && y < 2 + (x + z) % 5 {
+            voxel.idx = 1 + ((x * 3 + z) % 4) as u8;
+        }
+    }
+
+    let max = UVec3::splat(9);
+    let paletted = Chunk::new(
+        PalettedVoxels::from_slice(&dense),
+        shape.clone(),
+        UVec3::ZERO,
+        max,
+    );
+    let dense = Chunk::new(dense, shape, UVec3::ZERO, max);
+
+    let positions = |mesh: &Mesh| match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => positions.clone(),
+        _ => panic!("missing positions"),
+    };
+    let expected = positions(&dense.build());
+    assert!(!expected.is_empty());
+    assert_eq!(positions(&paletted.build()), expected);
+
+    let mut scratch = Vec::new();
+    for _ in 0..2 {
+        assert_eq!(
+            positions(&paletted.build_with_scratch(&mut scratch)),
+            expected
+        );
+    }
+}
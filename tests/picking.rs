@@ -0,0 +1,114 @@
+#![cfg(feature = "picking")]
+
+use bevy::{picking::backend::HitData, prelude::*};
+use ndshape::{RuntimeShape, Shape};
+use std::sync::Arc;
+use voxy::{AssetVoxel, AssetVoxelChunk, Chunk, VoxelHit, VoxelSceneChunk, cast_ray};
+
+/// A chunk with 4 voxels along each axis plus padding, with `voxels` set at padded coordinates.
+fn chunk(voxels: &[(UVec3, u8)]) -> AssetVoxelChunk {
+    let shape = RuntimeShape::<u32, 3>::new([6; 3]);
+    let mut data = vec![AssetVoxel::default(); shape.size() as usize];
+    for (pos, idx) in voxels {
+        data[shape.linearize(pos.to_array()) as usize] = AssetVoxel { idx: *idx };
+    }
+    Chunk::new(data, shape, UVec3::ZERO, UVec3::splat(5))
+}
+
+#[test]
+fn ray_hits_entry_face() {
+    let chunk = chunk(&[(UVec3::splat(2), 3)]);
+
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(-5., 2.5, 2.5), Vec3::X),
+        Some((UVec3::splat(2), IVec3::NEG_X, 7.))
+    );
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(2.5, 10., 2.5), Vec3::NEG_Y),
+        Some((UVec3::splat(2), IVec3::Y, 7.))
+    );
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(2.5, 2.5, -1.), Vec3::Z * 2.),
+        Some((UVec3::splat(2), IVec3::NEG_Z, 1.5))
+    );
+
+    // A ray that starts inside a voxel reports the face facing the ray.
+    assert_eq!(
+        cast_ray(&chunk, Vec3::splat(2.5), Vec3::X),
+        Some((UVec3::splat(2), IVec3::NEG_X, 0.))
+    );
+}
+
+#[test]
+fn ray_finds_first_voxel() {
+    let chunk = chunk(&[(UVec3::new(4, 3, 1), 1), (UVec3::new(2, 3, 1), 2)]);
+
+    // Walking from inside the chunk, the nearer voxel is hit from either side.
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(1.5, 3.5, 1.5), Vec3::X),
+        Some((UVec3::new(2, 3, 1), IVec3::NEG_X, 0.5))
+    );
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(3.5, 3.5, 1.5), Vec3::NEG_X),
+        Some((UVec3::new(2, 3, 1), IVec3::X, 0.5))
+    );
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(10., 3.5, 1.5), Vec3::NEG_X),
+        Some((UVec3::new(4, 3, 1), IVec3::X, 5.))
+    );
+
+    // A diagonal ray steps past the nearer voxel on its way up.
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(1.5, 1.5, 1.5), Vec3::new(1., 1., 0.)),
+        Some((UVec3::new(4, 3, 1), IVec3::NEG_X, 2.5))
+    );
+}
+
+#[test]
+fn ray_misses() {
+    let chunk = chunk(&[(UVec3::splat(2), 1), (UVec3::new(0, 4, 4), 1)]);
+
+    // Pointing away from the voxel.
+    assert_eq!(
+        cast_ray(&chunk, Vec3::new(-5., 2.5, 2.5), Vec3::NEG_X),
+        None
+    );
+    // Passing beside it.
+    assert_eq!(cast_ray(&chunk, Vec3::new(-5., 3.5, 2.5), Vec3::X), None);
+    // Passing outside the chunk.
+    assert_eq!(cast_ray(&chunk, Vec3::new(-5., 10., 2.5), Vec3::X), None);
+    // Padding voxels belong to neighbouring chunks.
+    assert_eq!(cast_ray(&chunk, Vec3::new(-5., 4.5, 4.5), Vec3::X), None);
+}
+
+#[test]
+fn hit_data_finds_voxel() {
+    let chunk = VoxelSceneChunk {
+        voxels: Some(Arc::new(chunk(&[(UVec3::splat(2), 3)]))),
+        origin: IVec3::new(9, -1, -1),
+    };
+    let entity = Entity::from_raw_u32(1).unwrap();
+    let transform =
+        GlobalTransform::from(Transform::from_xyz(100., 0., 0.).with_scale(Vec3::splat(2.)));
+
+    // The -X face of the voxel in world space.
+    let hit = HitData::new(entity, 7., Some(Vec3::new(104., 5., 5.)), Some(Vec3::NEG_X));
+    assert_eq!(
+        VoxelHit::from_hit(entity, &chunk, &transform, &hit),
+        Some(VoxelHit {
+            entity,
+            voxel: IVec3::new(11, 1, 1),
+            normal: IVec3::NEG_X,
+            palette_index: 2,
+            position: Vec3::new(104., 5., 5.),
+            distance: 7.,
+        })
+    );
+
+    // The opposite side of the face is empty.
+    let hit = HitData::new(entity, 7., Some(Vec3::new(104., 5., 5.)), Some(Vec3::X));
+    assert_eq!(VoxelHit::from_hit(entity, &chunk, &transform, &hit), None);
+
+    let hit = HitData::new(entity, 7., None, Some(Vec3::NEG_X));
+    assert_eq!(VoxelHit::from_hit(entity, &chunk, &transform, &hit), None);
+}
@@ -0,0 +1,122 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use voxy::{
+    ScenePlugin, VoxelMaterial, VoxelModel, VoxelScene,
+    scene::{LitMesh, LoadedAssets, VoxelSceneHandle},
+};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<VoxelMaterial>();
+    app
+}
+
+fn scene() -> VoxelScene {
+    let models = (0..2)
+        .map(|idx| VoxelModel {
+            meshes: vec![LitMesh::new(Cuboid::default().into(), Transform::default())],
+            name: Some(format!("model-{idx}")),
+            path: vec![format!("model-{idx}")],
+            transform: Transform::from_xyz(idx as f32, 0., 0.),
+        })
+        .collect();
+
+    VoxelScene {
+        models,
+        material: VoxelMaterial {
+            colors: [Vec3::ONE; 256],
+            emissions: [Vec3::ZERO; 256],
+        },
+        material_handle: None,
+    }
+}
+
+fn update(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn counts(app: &App) -> (usize, usize) {
+    (
+        app.world().resource::<Assets<Mesh>>().len(),
+        app.world().resource::<Assets<VoxelMaterial>>().len(),
+    )
+}
+
+#[test]
+fn despawning_scenes_releases_meshes() {
+    let mut app = app();
+    update(&mut app);
+    let baseline = counts(&app);
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene());
+    let id = handle.id();
+    let first = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    let second = app.world_mut().spawn(VoxelSceneHandle(handle)).id();
+    update(&mut app);
+
+    // Both instances share one material and a mesh per chunk.
+    assert_eq!(counts(&app), (baseline.0 + 2, baseline.1 + 1));
+    assert!(
+        app.world()
+            .resource::<LoadedAssets>()
+            .material(id)
+            .is_some()
+    );
+
+    app.world_mut().entity_mut(first).despawn();
+    update(&mut app);
+    assert_eq!(counts(&app), (baseline.0 + 2, baseline.1 + 1));
+
+    app.world_mut().entity_mut(second).despawn();
+    update(&mut app);
+    assert_eq!(counts(&app), baseline);
+    assert!(
+        app.world()
+            .resource::<LoadedAssets>()
+            .material(id)
+            .is_none()
+    );
+    assert!(
+        app.world()
+            .resource::<Assets<VoxelScene>>()
+            .get(id)
+            .is_none()
+    );
+}
+
+#[test]
+fn removing_scene_asset_releases_meshes() {
+    let mut app = app();
+    update(&mut app);
+    let baseline = counts(&app);
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene());
+    let entity = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    update(&mut app);
+    assert_eq!(counts(&app), (baseline.0 + 2, baseline.1 + 1));
+
+    // Removing the asset releases the cache, and despawning the instance releases the rest.
+    app.world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .remove(&handle);
+    update(&mut app);
+    assert!(
+        app.world()
+            .resource::<LoadedAssets>()
+            .material(handle.id())
+            .is_none()
+    );
+
+    app.world_mut().entity_mut(entity).despawn();
+    update(&mut app);
+    assert_eq!(counts(&app), baseline);
+}
@@ -0,0 +1,170 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use std::time::Duration;
+use voxy::{
+    ScenePlugin, VoxelMaterial, VoxelModel, VoxelScene,
+    scene::{
+        LitMesh, VoxelSceneHandle, VoxelSceneLoadFailed, VoxelSceneModelRef, VoxelSceneReady,
+        VoxelSceneReloaded,
+    },
+};
+
+/// The entities each scene event was triggered for, in order.
+#[derive(Default, Resource)]
+struct SceneEvents {
+    ready: Vec<Entity>,
+    reloaded: Vec<Entity>,
+    failed: Vec<Entity>,
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<VoxelMaterial>()
+        .init_resource::<SceneEvents>()
+        .add_observer(
+            |ready: On<VoxelSceneReady>, mut events: ResMut<SceneEvents>| {
+                events.ready.push(ready.entity);
+            },
+        )
+        .add_observer(
+            |reloaded: On<VoxelSceneReloaded>, mut events: ResMut<SceneEvents>| {
+                events.reloaded.push(reloaded.entity);
+            },
+        )
+        .add_observer(
+            |failed: On<VoxelSceneLoadFailed>, mut events: ResMut<SceneEvents>| {
+                events.failed.push(failed.entity);
+            },
+        );
+    app
+}
+
+fn scene() -> VoxelScene {
+    let models = (0..2)
+        .map(|idx| VoxelModel {
+            meshes: vec![LitMesh::new(Cuboid::default().into(), Transform::default())],
+            name: Some(format!("model-{idx}")),
+            path: vec![format!("model-{idx}")],
+            transform: Transform::from_xyz(idx as f32, 0., 0.),
+        })
+        .collect();
+
+    VoxelScene {
+        models,
+        material: VoxelMaterial {
+            colors: [Vec3::ONE; 256],
+            emissions: [Vec3::ZERO; 256],
+        },
+        material_handle: None,
+    }
+}
+
+fn update(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn events(app: &App) -> &SceneEvents {
+    app.world().resource::<SceneEvents>()
+}
+
+#[test]
+fn ready_fires_once_per_entity() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene());
+    let scene_entity = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    let ref_entity = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle, "model-1"))
+        .id();
+    update(&mut app);
+    update(&mut app);
+
+    let mut ready = events(&app).ready.clone();
+    ready.sort();
+    let mut expected = vec![scene_entity, ref_entity];
+    expected.sort();
+    assert_eq!(ready, expected);
+    assert!(events(&app).reloaded.is_empty());
+    assert!(events(&app).failed.is_empty());
+}
+
+#[test]
+fn reloaded_fires_once_per_modification() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene());
+    let scene_entity = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    let ref_entity = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle.clone(), "model-1"))
+        .id();
+    update(&mut app);
+    assert!(events(&app).reloaded.is_empty());
+
+    // Replacing the asset reloads every spawned instance once.
+    app.world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .insert(&handle, scene())
+        .unwrap();
+    update(&mut app);
+    let mut reloaded = events(&app).reloaded.clone();
+    reloaded.sort();
+    let mut expected = vec![scene_entity, ref_entity];
+    expected.sort();
+    assert_eq!(reloaded, expected);
+
+    // Later frames without changes don't reload again.
+    update(&mut app);
+    assert_eq!(events(&app).reloaded.len(), 2);
+    assert_eq!(events(&app).ready.len(), 2);
+}
+
+#[test]
+fn load_failed_fires_once() {
+    let mut app = app();
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load::<VoxelScene>("missing.vox");
+    let scene_entity = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    let ref_entity = app
+        .world_mut()
+        .spawn(VoxelSceneModelRef::new(handle.clone(), "model-1"))
+        .id();
+
+    for _ in 0..500 {
+        app.update();
+        if app
+            .world()
+            .resource::<AssetServer>()
+            .load_state(&handle)
+            .is_failed()
+        {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(
+        app.world()
+            .resource::<AssetServer>()
+            .load_state(&handle)
+            .is_failed()
+    );
+    update(&mut app);
+    update(&mut app);
+
+    let mut failed = events(&app).failed.clone();
+    failed.sort();
+    let mut expected = vec![scene_entity, ref_entity];
+    expected.sort();
+    assert_eq!(failed, expected);
+    assert!(events(&app).ready.is_empty());
+}
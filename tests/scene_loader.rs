@@ -0,0 +1,142 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use std::time::Duration;
+use voxy::{
+    SceneLoaderSettings, ScenePlugin, VoxelMaterial, VoxelModel, VoxelScene, scene::LitMesh,
+};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<VoxelMaterial>();
+    app
+}
+
+/// Update `app` until `handle` and its dependencies are loaded.
+fn load<A: Asset>(app: &mut App, handle: &Handle<A>) {
+    for _ in 0..1000 {
+        app.update();
+        let asset_server = app.world().resource::<AssetServer>();
+        if asset_server.is_loaded_with_dependencies(handle) {
+            return;
+        }
+        assert!(
+            !asset_server.load_state(handle).is_failed(),
+            "{:?}",
+            asset_server.load_state(handle)
+        );
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("timed out loading {:?}", handle.path());
+}
+
+fn model(name: Option<&str>) -> VoxelModel {
+    VoxelModel {
+        meshes: vec![LitMesh::new(Cuboid::default().into(), Transform::default())],
+        name: name.map(String::from),
+        path: name.into_iter().map(String::from).collect(),
+        transform: Transform::default(),
+    }
+}
+
+#[test]
+fn labeled_sub_assets_resolve() {
+    let mut app = app();
+    let asset_server = app.world().resource::<AssetServer>().clone();
+    let scene = asset_server.load::<VoxelScene>("character.vox");
+    load(&mut app, &scene);
+
+    let material = asset_server.load::<VoxelMaterial>("character.vox#Material");
+    let right_arm = asset_server.load::<Mesh>("character.vox#Model/right_arm");
+    load(&mut app, &material);
+    load(&mut app, &right_arm);
+
+    let scenes = app.world().resource::<Assets<VoxelScene>>();
+    let scene = scenes.get(&scene).unwrap();
+    assert_eq!(scene.material_handle.as_ref(), Some(&material));
+    let materials = app.world().resource::<Assets<VoxelMaterial>>();
+    assert_eq!(
+        materials.get(&material).unwrap().colors,
+        scene.material.colors
+    );
+
+    // Each model's mesh is moved into its sub-asset.
+    let labels = scene.model_labels();
+    let idx = labels
+        .iter()
+        .position(|label| label == "right_arm")
+        .unwrap();
+    let lit_mesh = &scene.models[idx].meshes[0];
+    assert_eq!(lit_mesh.handle.as_ref(), Some(&right_arm));
+    assert!(lit_mesh.mesh.is_none());
+    let meshes = app.world().resource::<Assets<Mesh>>();
+    let mesh = meshes.get(&right_arm).unwrap();
+    assert_eq!(mesh.count_vertices(), lit_mesh.stats.vertices);
+    assert!(lit_mesh.stats.vertices > 0);
+    assert!(scene.models.iter().all(|model| model.bounds().is_some()));
+}
+
+#[test]
+fn model_labels_are_unique() {
+    let material = VoxelMaterial {
+        colors: [Vec3::ONE; 256],
+        emissions: [Vec3::ZERO; 256],
+    };
+    let scene = VoxelScene {
+        models: vec![
+            model(None),
+            model(Some("0")),
+            model(Some("barrel")),
+            model(Some("barrel")),
+            model(Some("3")),
+            model(None),
+        ],
+        material,
+        material_handle: None,
+    };
+
+    // Named models keep their name, and the others fall back to their index.
+    assert_eq!(scene.model_labels(), ["0_", "0", "barrel", "3_", "3", "5"]);
+}
+
+#[test]
+fn voxels_are_kept_on_request() {
+    let mut app = app();
+    let asset_server = app.world().resource::<AssetServer>().clone();
+    let dropped = asset_server
+        .load_with_settings::<VoxelScene, SceneLoaderSettings>("character.vox", |settings| {
+            settings.keep_voxels = false
+        });
+    let kept = asset_server
+        .load_with_settings::<VoxelScene, SceneLoaderSettings>("example.vox", |settings| {
+            settings.keep_voxels = true
+        });
+    load(&mut app, &dropped);
+    load(&mut app, &kept);
+
+    let scenes = app.world().resource::<Assets<VoxelScene>>();
+    let lit_meshes = |handle| {
+        scenes
+            .get(handle)
+            .unwrap()
+            .models
+            .iter()
+            .flat_map(|model| &model.meshes)
+            .collect::<Vec<_>>()
+    };
+    assert!(
+        lit_meshes(&dropped)
+            .iter()
+            .all(|lit_mesh| lit_mesh.voxels.is_none())
+    );
+    assert!(
+        lit_meshes(&kept)
+            .iter()
+            .all(|lit_mesh| lit_mesh.voxels.is_some())
+    );
+
+    // Statistics that need the voxels are missing without them.
+    let stats = scenes.get(&dropped).unwrap().stats();
+    assert_eq!(stats.emissive_voxels, None);
+    assert!(stats.total.vertices > 0);
+}
@@ -0,0 +1,228 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use voxy::{
+    ScenePlugin, VoxelMaterial, VoxelModel, VoxelScene,
+    scene::{
+        LitMesh, LoadedAssets, VoxelSceneChunk, VoxelSceneHandle, VoxelSceneModel,
+        VoxelSceneModels, VoxelSceneReloadSettings,
+    },
+};
+
+#[derive(Component)]
+struct Marker;
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<VoxelMaterial>();
+    app
+}
+
+/// A scene with a single-chunk model for each name and translation. Empty names are unnamed models.
+fn scene(models: &[(&str, Vec3)]) -> VoxelScene {
+    let models = models
+        .iter()
+        .map(|(name, translation)| VoxelModel {
+            meshes: vec![LitMesh::new(Cuboid::default().into(), Transform::default())],
+            name: (!name.is_empty()).then(|| name.to_string()),
+            path: vec![name.to_string()],
+            transform: Transform::from_translation(*translation),
+        })
+        .collect();
+
+    VoxelScene {
+        models,
+        material: VoxelMaterial {
+            colors: [Vec3::ONE; 256],
+            emissions: [Vec3::ZERO; 256],
+        },
+        material_handle: None,
+    }
+}
+
+fn update(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn model_entity(app: &App, root: Entity, name: &str) -> Entity {
+    app.world().get::<VoxelSceneModels>(root).unwrap().entities[name]
+}
+
+/// The model entities spawned under `root`.
+fn models(app: &App, root: Entity) -> Vec<Entity> {
+    let children = app.world().get::<Children>(root).unwrap().to_vec();
+    children
+        .into_iter()
+        .filter(|child| app.world().get::<VoxelSceneModel>(*child).is_some())
+        .collect()
+}
+
+/// The chunk entities spawned under `model`.
+fn chunks(app: &App, model: Entity) -> Vec<Entity> {
+    app.world()
+        .get::<Children>(model)
+        .unwrap()
+        .iter()
+        .filter(|child| app.world().get::<VoxelSceneChunk>(*child).is_some())
+        .collect()
+}
+
+fn translation(app: &App, entity: Entity) -> Vec3 {
+    app.world().get::<Transform>(entity).unwrap().translation
+}
+
+fn replace(app: &mut App, handle: &Handle<VoxelScene>, scene: VoxelScene) {
+    app.world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .insert(handle, scene)
+        .unwrap();
+    update(app);
+}
+
+#[test]
+fn named_models_keep_their_entity_and_components() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene(&[
+            ("kept", Vec3::ZERO),
+            ("removed", Vec3::X),
+            ("", Vec3::Y),
+        ]));
+    let root = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    update(&mut app);
+    assert_eq!(models(&app, root).len(), 3);
+
+    let kept = model_entity(&app, root, "kept");
+    let removed = model_entity(&app, root, "removed");
+    let kept_chunks = chunks(&app, kept);
+    app.world_mut().entity_mut(kept).insert(Marker);
+
+    replace(
+        &mut app,
+        &handle,
+        scene(&[("kept", Vec3::Z), ("added", Vec3::X), ("", Vec3::Y)]),
+    );
+
+    // The kept model is updated in place, with new chunks and its new authored transform.
+    assert_eq!(model_entity(&app, root, "kept"), kept);
+    assert!(app.world().get::<Marker>(kept).is_some());
+    assert_eq!(translation(&app, kept), Vec3::Z);
+    let new_chunks = chunks(&app, kept);
+    assert_eq!(new_chunks.len(), 1);
+    assert!(
+        kept_chunks
+            .iter()
+            .all(|chunk| app.world().get_entity(*chunk).is_err())
+    );
+
+    // Removed models are despawned and added models are spawned.
+    assert!(app.world().get_entity(removed).is_err());
+    let added = model_entity(&app, root, "added");
+    assert_eq!(translation(&app, added), Vec3::X);
+    let mut names: Vec<_> = app
+        .world()
+        .get::<VoxelSceneModels>(root)
+        .unwrap()
+        .entities
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    assert_eq!(names, ["added", "kept"]);
+
+    // The unnamed model is replaced, so there are still 3 models.
+    assert_eq!(models(&app, root).len(), 3);
+}
+
+#[test]
+fn moved_models_keep_their_transform() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene(&[("moved", Vec3::ZERO), ("still", Vec3::X)]));
+    let preserved = app.world_mut().spawn(VoxelSceneHandle(handle.clone())).id();
+    let reset = app
+        .world_mut()
+        .spawn((
+            VoxelSceneHandle(handle.clone()),
+            VoxelSceneReloadSettings {
+                preserve_transforms: false,
+            },
+        ))
+        .id();
+    update(&mut app);
+
+    for root in [preserved, reset] {
+        let moved = model_entity(&app, root, "moved");
+        app.world_mut()
+            .get_mut::<Transform>(moved)
+            .unwrap()
+            .translation = Vec3::splat(5.);
+    }
+
+    replace(
+        &mut app,
+        &handle,
+        scene(&[("moved", Vec3::Y), ("still", Vec3::Z)]),
+    );
+
+    // Models moved by the user keep their transform, the others follow the asset.
+    let moved = model_entity(&app, preserved, "moved");
+    assert_eq!(translation(&app, moved), Vec3::splat(5.));
+    assert_eq!(
+        app.world()
+            .get::<VoxelSceneModel>(moved)
+            .unwrap()
+            .authored_transform
+            .translation,
+        Vec3::Y
+    );
+    assert_eq!(
+        translation(&app, model_entity(&app, preserved, "still")),
+        Vec3::Z
+    );
+
+    // Without `preserve_transforms`, every model is reset to the asset.
+    assert_eq!(
+        translation(&app, model_entity(&app, reset, "moved")),
+        Vec3::Y
+    );
+    assert_eq!(
+        translation(&app, model_entity(&app, reset, "still")),
+        Vec3::Z
+    );
+}
+
+#[test]
+fn unspawned_scenes_are_not_meshed_on_modification() {
+    let mut app = app();
+    let spawned = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene(&[("a", Vec3::ZERO)]));
+    let unspawned = app
+        .world_mut()
+        .resource_mut::<Assets<VoxelScene>>()
+        .add(scene(&[("b", Vec3::ZERO)]));
+    app.world_mut().spawn(VoxelSceneHandle(spawned.clone()));
+    update(&mut app);
+    let meshes = app.world().resource::<Assets<Mesh>>().len();
+
+    replace(&mut app, &unspawned, scene(&[("b", Vec3::X)]));
+    assert_eq!(app.world().resource::<Assets<Mesh>>().len(), meshes);
+    assert!(
+        app.world()
+            .resource::<LoadedAssets>()
+            .material(unspawned.id())
+            .is_none()
+    );
+
+    // Spawned scenes are still rebuilt.
+    replace(&mut app, &spawned, scene(&[("a", Vec3::X), ("c", Vec3::Y)]));
+    assert_eq!(app.world().resource::<Assets<Mesh>>().len(), meshes + 1);
+}
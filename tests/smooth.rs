@@ -0,0 +1,117 @@
+use bevy::{
+    mesh::{Indices, VertexAttributeValues},
+    prelude::*,
+};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{ATTRIBUTE_BLEND_WEIGHT, ATTRIBUTE_COLOR_INDEX, Chunk, SdfVoxel};
+
+fn sdf(
+    size: u32,
+    f: impl Fn(Vec3) -> SdfVoxel,
+) -> Chunk<SdfVoxel, Vec<SdfVoxel>, RuntimeShape<u32, 3>> {
+    let shape = RuntimeShape::<u32, 3>::new([size; 3]);
+    let voxels = (0..shape.size())
+        .map(|idx| f(UVec3::from(shape.delinearize(idx)).as_vec3()))
+        .collect();
+    Chunk::new(voxels, shape, UVec3::ZERO, UVec3::splat(size - 1))
+}
+
+fn attributes(mesh: &Mesh) -> (&[[f32; 3]], &[[f32; 3]], Vec<u32>) {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("missing positions");
+    };
+    let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+    else {
+        panic!("missing normals");
+    };
+    let Some(Indices::U32(indices)) = mesh.indices() else {
+        panic!("missing indices");
+    };
+    (positions, normals, indices.clone())
+}
+
+#[test]
+fn sphere_surface() {
+    let center = Vec3::splat(8.);
+    let chunk = sdf(17, |pos| SdfVoxel {
+        distance: pos.distance(center) - 5.,
+        palette_index: 3,
+    });
+    let mesh = chunk.build_smooth();
+    let (positions, normals, indices) = attributes(&mesh);
+    assert!(!indices.is_empty());
+
+    for (position, normal) in positions.iter().zip(normals) {
+        let offset = Vec3::from(*position) - center;
+        assert!((offset.length() - 5.).abs() < 0.25, "{position:?}");
+        assert!(Vec3::from(*normal).dot(offset.normalize()) > 0.9);
+    }
+
+    // Triangles wind counter-clockwise when seen from outside.
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|idx| Vec3::from(positions[triangle[idx] as usize]));
+        let face_normal = (b - a).cross(c - a);
+        assert!(face_normal.dot((a + b + c) / 3. - center) > 0.);
+    }
+
+    let Some(VertexAttributeValues::Uint32(color_indices)) = mesh.attribute(ATTRIBUTE_COLOR_INDEX)
+    else {
+        panic!("missing color indices");
+    };
+    assert!(color_indices.iter().all(|idx| *idx == 3 | 3 << 8));
+}
+
+#[test]
+fn materials_blend() {
+    // A slab split into two materials along X.
+    let chunk = sdf(10, |pos| SdfVoxel {
+        distance: pos.y - 4.5,
+        palette_index: if pos.x < 5. { 1 } else { 2 },
+    });
+    let mesh = chunk.build_smooth();
+    let Some(VertexAttributeValues::Float32(weights)) = mesh.attribute(ATTRIBUTE_BLEND_WEIGHT)
+    else {
+        panic!("missing blend weights");
+    };
+    let Some(VertexAttributeValues::Uint32(color_indices)) = mesh.attribute(ATTRIBUTE_COLOR_INDEX)
+    else {
+        panic!("missing color indices");
+    };
+
+    let (positions, normals, _) = attributes(&mesh);
+    assert!(
+        normals
+            .iter()
+            .all(|normal| Vec3::from(*normal).abs_diff_eq(Vec3::Y, 1e-4))
+    );
+    assert!(
+        positions
+            .iter()
+            .all(|position| (position[1] - 4.5).abs() < 1e-4)
+    );
+
+    for ((position, weight), idx) in positions.iter().zip(weights).zip(color_indices) {
+        if position[0] < 4. {
+            assert_eq!((*idx, *weight), (1 | 1 << 8, 0.));
+        } else if position[0] > 5. {
+            assert_eq!((*idx, *weight), (2 | 2 << 8, 0.));
+        } else {
+            assert!(*weight > 0. && *weight <= 0.5);
+            assert!(*idx == 1 | 2 << 8 || *idx == 2 | 1 << 8);
+        }
+    }
+}
+
+#[test]
+fn empty_and_solid_fields_have_no_surface() {
+    for distance in [-1., 1.] {
+        let chunk = sdf(6, |_| SdfVoxel {
+            distance,
+            palette_index: 1,
+        });
+        let mesh = chunk.build_smooth();
+        assert_eq!(mesh.count_vertices(), 0);
+    }
+}
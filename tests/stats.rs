@@ -0,0 +1,154 @@
+use bevy::{
+    asset::RenderAssetUsages, mesh::VertexAttributeValues, prelude::*,
+    render::render_resource::PrimitiveTopology,
+};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{
+    ATTRIBUTE_COLOR_INDEX, AssetModel, AssetVoxel, AssetVoxelChunk, Chunk, MeshStats,
+    QuantizedPalette, SdfVoxel, ValidationIssue, VoxFileAsset, VoxelScene,
+    scene::{LitMesh, VoxelModel},
+};
+
+fn chunk(size: u32, solid: &[UVec3]) -> AssetVoxelChunk {
+    let shape = RuntimeShape::<u32, 3>::new([size + 2; 3]);
+    let mut voxels = vec![AssetVoxel::default(); shape.size() as usize];
+    for pos in solid {
+        voxels[shape.linearize((*pos + UVec3::ONE).to_array()) as usize] = AssetVoxel { idx: 1 };
+    }
+    Chunk::new(voxels, shape, UVec3::ZERO, UVec3::splat(size + 1))
+}
+
+#[test]
+fn chunk_stats() {
+    let chunk = chunk(4, &[UVec3::ZERO]);
+    let stats = chunk.mesh_stats();
+    assert_eq!(
+        stats,
+        MeshStats {
+            quads: 6,
+            vertices: 24,
+            indices: 36,
+            triangles: 12,
+            bounds: Some((Vec3::ONE, Vec3::splat(2.))),
+            invalid_color_indices: 0,
+        }
+    );
+
+    // Packed meshes have the same geometry.
+    assert_eq!(MeshStats::from_mesh(&chunk.build_packed()), stats);
+}
+
+#[test]
+fn scene_stats() {
+    let file = dot_vox::load("assets/example.vox").unwrap();
+    let asset = VoxFileAsset { file };
+    let scene = VoxelScene::from_models(asset.models(Some(16)), asset.material(), false);
+
+    let stats = scene.stats();
+    assert_eq!(stats.models.len(), scene.models.len());
+    assert!(stats.total.quads > 0);
+    assert_eq!(stats.total.vertices, stats.total.quads * 4);
+    assert_eq!(stats.total.indices, stats.total.triangles * 3);
+    assert_eq!(
+        stats.total.triangles,
+        stats
+            .models
+            .iter()
+            .map(|model| model.mesh.triangles)
+            .sum::<usize>()
+    );
+    assert_eq!(stats.emissive_voxels, Some(stats.lights));
+
+    for (model, model_stats) in scene.models.iter().zip(&stats.models) {
+        assert_eq!(model_stats.chunks, model.meshes.len());
+        assert_eq!(model_stats.mesh.bounds, model.bounds());
+    }
+    assert!(scene.validate().is_empty());
+}
+
+#[test]
+fn validation() {
+    let empty = AssetModel::from_voxels(
+        chunk(2, &[]).voxels,
+        RuntimeShape::<u32, 3>::new([4; 3]),
+        None,
+        Some(String::from("empty")),
+    );
+    let solid = AssetModel::from_voxels(
+        chunk(2, &[UVec3::ONE]).voxels,
+        RuntimeShape::<u32, 3>::new([4; 3]),
+        None,
+        None,
+    );
+    let mut scene = VoxelScene::from_models(
+        [empty, solid],
+        QuantizedPalette::default().material(),
+        false,
+    );
+
+    // A custom mesh whose colour indices were computed as `0 - 1` and past the end of the palette.
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+    )
+    .with_inserted_attribute(
+        ATTRIBUTE_COLOR_INDEX,
+        VertexAttributeValues::Uint32(vec![u32::MAX, 256, 255]),
+    );
+    scene.models.push(VoxelModel {
+        meshes: vec![LitMesh::new(mesh, Transform::IDENTITY)],
+        name: Some(String::from("custom")),
+        path: Vec::new(),
+        transform: Transform::IDENTITY,
+    });
+
+    let issues = scene.validate();
+    assert_eq!(
+        issues,
+        [
+            ValidationIssue::EmptyModel {
+                model: 0,
+                name: Some(String::from("empty")),
+            },
+            ValidationIssue::InvalidColorIndex {
+                model: 2,
+                name: Some(String::from("custom")),
+                vertices: 2,
+            },
+        ]
+    );
+    assert_eq!(issues[0].to_string(), "model 0 (empty) is empty");
+    assert_eq!(
+        issues[1].to_string(),
+        "model 2 (custom) has 2 vertices with an out of range colour index"
+    );
+
+    // Without voxels, emissive voxels can't be counted.
+    let stats = scene.stats();
+    assert_eq!(stats.models[2].emissive_voxels, None);
+    assert_eq!(stats.models[1].emissive_voxels, Some(0));
+    assert_eq!(stats.emissive_voxels, Some(0));
+}
+
+#[test]
+fn smooth_meshes_have_valid_color_indices() {
+    let shape = RuntimeShape::<u32, 3>::new([8; 3]);
+    let voxels: Vec<_> = (0..shape.size())
+        .map(|idx| {
+            let pos = Vec3::from(shape.delinearize(idx).map(|c| c as f32));
+            SdfVoxel {
+                distance: pos.distance(Vec3::splat(3.5)) - 2.,
+                palette_index: if pos.x < 3.5 { 200 } else { 255 },
+            }
+        })
+        .collect();
+    let chunk = Chunk::new(voxels, shape, UVec3::ZERO, UVec3::splat(7));
+
+    let stats = MeshStats::from_mesh(&chunk.build_smooth());
+    assert!(stats.triangles > 0);
+    assert_eq!(stats.invalid_color_indices, 0);
+}
@@ -0,0 +1,138 @@
+use dot_vox::{
+    Color, DEFAULT_PALETTE, Dict, DotVoxData, Frame, Layer, Material, Model, SceneNode, ShapeModel,
+    Size, Voxel,
+};
+use voxy::VoxFileAsset;
+
+fn model(voxels: &[(u8, u8)]) -> Model {
+    Model {
+        size: Size { x: 4, y: 4, z: 4 },
+        voxels: voxels
+            .iter()
+            .map(|(x, i)| Voxel {
+                x: *x,
+                y: 0,
+                z: 0,
+                i: *i,
+            })
+            .collect(),
+    }
+}
+
+fn shape(model_id: u32) -> SceneNode {
+    SceneNode::Shape {
+        attributes: Dict::new(),
+        models: vec![ShapeModel {
+            model_id,
+            attributes: Dict::new(),
+        }],
+    }
+}
+
+fn transform(child: u32) -> SceneNode {
+    SceneNode::Transform {
+        attributes: Dict::new(),
+        frames: vec![Frame::new(Dict::new())],
+        child,
+        layer_id: 0,
+    }
+}
+
+/// A file with three models in a group, where the first is empty.
+fn file() -> VoxFileAsset {
+    let mut palette: Vec<Color> = DEFAULT_PALETTE.to_vec();
+    palette[7] = palette[3];
+
+    VoxFileAsset {
+        file: DotVoxData {
+            version: 150,
+            models: vec![model(&[]), model(&[(0, 3), (1, 7)]), model(&[(2, 7)])],
+            palette,
+            materials: Vec::new(),
+            scenes: vec![
+                transform(1),
+                SceneNode::Group {
+                    attributes: Dict::new(),
+                    children: vec![2, 4, 6],
+                },
+                transform(3),
+                shape(0),
+                transform(5),
+                shape(1),
+                transform(7),
+                shape(2),
+            ],
+            layers: Vec::new(),
+        },
+    }
+}
+
+#[test]
+fn strip_empty_models() {
+    let mut asset = file();
+    assert_eq!(asset.models(None).count(), 3);
+
+    assert_eq!(asset.strip_empty_models(), 1);
+    assert_eq!(asset.file.models.len(), 2);
+    assert!(matches!(
+        &asset.file.scenes[3],
+        SceneNode::Shape { models, .. } if models.is_empty()
+    ));
+    assert!(matches!(
+        &asset.file.scenes[7],
+        SceneNode::Shape { models, .. } if models[0].model_id == 1
+    ));
+
+    let models: Vec<_> = asset.models(None).collect();
+    assert_eq!(models.len(), 2);
+    assert_eq!(asset.strip_empty_models(), 0);
+
+    // The stripped file can be written and read back.
+    let mut bytes = Vec::new();
+    asset.write_vox(&mut bytes).unwrap();
+    let file = dot_vox::load_bytes(&bytes).unwrap();
+    assert_eq!(file.models, asset.file.models);
+}
+
+#[test]
+fn dedup_palette() {
+    let mut asset = file();
+    assert!(asset.dedup_palette() >= 1);
+
+    let indices: Vec<_> = asset
+        .file
+        .models
+        .iter()
+        .flat_map(|model| &model.voxels)
+        .map(|voxel| voxel.i)
+        .collect();
+    assert_eq!(indices, [3, 3, 3]);
+}
+
+#[test]
+fn write_keeps_materials_and_layers() {
+    let mut asset = file();
+    asset.file.materials = vec![Material {
+        id: 4,
+        properties: Dict::from([
+            (String::from("_type"), String::from("_emit")),
+            (String::from("_emit"), String::from("0.5")),
+        ]),
+    }];
+    asset.file.layers = vec![
+        Layer {
+            attributes: Dict::from([(String::from("_name"), String::from("ground"))]),
+        },
+        Layer {
+            attributes: Dict::from([(String::from("_hidden"), String::from("1"))]),
+        },
+    ];
+
+    let mut bytes = Vec::new();
+    asset.write_vox(&mut bytes).unwrap();
+    let file = dot_vox::load_bytes(&bytes).unwrap();
+    assert_eq!(file.models, asset.file.models);
+    assert_eq!(file.palette, asset.file.palette);
+    assert_eq!(file.materials, asset.file.materials);
+    assert_eq!(file.layers, asset.file.layers);
+}
@@ -0,0 +1,87 @@
+use bevy::{mesh::VertexAttributeValues, prelude::*};
+use block_mesh::{MergeVoxel, Voxel, VoxelVisibility};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{ATTRIBUTE_COLOR_INDEX, Chunk, VoxelAttributes};
+
+#[derive(Clone, Copy, Default)]
+struct MaterialVoxel {
+    material: Option<u16>,
+}
+
+impl Voxel for MaterialVoxel {
+    fn get_visibility(&self) -> VoxelVisibility {
+        if self.material.is_some() {
+            VoxelVisibility::Opaque
+        } else {
+            VoxelVisibility::Empty
+        }
+    }
+}
+
+impl MergeVoxel for MaterialVoxel {
+    type MergeValue = Option<u16>;
+
+    fn merge_value(&self) -> Self::MergeValue {
+        self.material
+    }
+}
+
+impl VoxelAttributes for MaterialVoxel {
+    fn attributes(&self) -> u32 {
+        self.material.unwrap_or_default() as u32
+    }
+}
+
+fn color_indices(mesh: &Mesh) -> &[u32] {
+    match mesh.attribute(ATTRIBUTE_COLOR_INDEX) {
+        Some(VertexAttributeValues::Uint32(values)) => values,
+        _ => panic!("missing color index attribute"),
+    }
+}
+
+#[test]
+fn meshes_1024_materials() {
+    const MATERIALS: u32 = 1024;
+
+    // A 32x32 layer of voxels, each with its own material so no quads are merged.
+    let shape = RuntimeShape::<u32, 3>::new([34, 3, 34]);
+    let mut voxels = vec![MaterialVoxel::default(); shape.size() as usize];
+    for z in 0..32 {
+        for x in 0..32 {
+            voxels[shape.linearize([x + 1, 1, z + 1]) as usize] = MaterialVoxel {
+                material: Some((x + z * 32) as u16),
+            };
+        }
+    }
+
+    let mesh = Chunk::new(voxels, shape, UVec3::ZERO, UVec3::new(33, 2, 33)).build();
+    let indices = color_indices(&mesh);
+
+    // Every vertex of a quad shares its voxel's material.
+    for quad in indices.chunks(4) {
+        assert!(quad.iter().all(|idx| *idx == quad[0]));
+    }
+
+    let mut seen = vec![false; MATERIALS as usize];
+    for idx in indices {
+        assert!(*idx < MATERIALS);
+        seen[*idx as usize] = true;
+    }
+    assert!(seen.into_iter().all(|seen| seen));
+
+    // Top and bottom faces for every voxel, plus the outer walls.
+    assert_eq!(indices.len(), (MATERIALS as usize * 2 + 32 * 4) * 4);
+}
+
+#[test]
+fn material_zero_does_not_underflow() {
+    let shape = RuntimeShape::<u32, 3>::new([3, 3, 3]);
+    let mut voxels = vec![MaterialVoxel::default(); shape.size() as usize];
+    voxels[shape.linearize([1, 1, 1]) as usize] = MaterialVoxel { material: Some(0) };
+
+    let mesh = Chunk::new(voxels, shape, UVec3::ZERO, UVec3::splat(2)).build();
+    let indices = color_indices(&mesh);
+
+    assert_eq!(indices.len(), 6 * 4);
+    assert!(indices.iter().all(|idx| *idx == 0));
+}
@@ -0,0 +1,101 @@
+use bevy::{mesh::VertexAttributeValues, prelude::*};
+use ndshape::{RuntimeShape, Shape};
+use voxy::{ATTRIBUTE_COLOR_INDEX, Chunk, VoxelId, VoxelType, VoxelTypeRegistry};
+
+/// A padded chunk of two voxels side by side along X.
+fn pair(left: VoxelId, right: VoxelId) -> Chunk<VoxelId, Vec<VoxelId>, RuntimeShape<u32, 3>> {
+    let shape = RuntimeShape::<u32, 3>::new([4, 3, 3]);
+    let mut voxels = vec![0; shape.size() as usize];
+    voxels[shape.linearize([1, 1, 1]) as usize] = left;
+    voxels[shape.linearize([2, 1, 1]) as usize] = right;
+    Chunk::new(voxels, shape, UVec3::ZERO, UVec3::new(3, 2, 2))
+}
+
+fn quads(
+    registry: &VoxelTypeRegistry,
+    chunk: &Chunk<VoxelId, Vec<VoxelId>, RuntimeShape<u32, 3>>,
+) -> usize {
+    registry.resolve_chunk(chunk).build().count_vertices() / 4
+}
+
+fn color_indices(mesh: &Mesh) -> &[u32] {
+    match mesh.attribute(ATTRIBUTE_COLOR_INDEX) {
+        Some(VertexAttributeValues::Uint32(values)) => values,
+        _ => panic!("missing color index attribute"),
+    }
+}
+
+#[test]
+fn unregistered_voxels_are_empty() {
+    let mut registry = VoxelTypeRegistry::default();
+    registry.insert(1, VoxelType::opaque(4));
+
+    assert_eq!(registry.resolve(0), VoxelType::EMPTY);
+    assert_eq!(registry.resolve(9), VoxelType::EMPTY);
+    assert_eq!(registry.resolve(1), VoxelType::opaque(4));
+
+    // A single voxel next to an unregistered one.
+    assert_eq!(quads(&registry, &pair(1, 9)), 6);
+    assert_eq!(quads(&registry, &pair(9, 9)), 0);
+}
+
+#[test]
+fn visibility_hides_faces() {
+    let mut registry = VoxelTypeRegistry::default();
+    registry
+        .insert(1, VoxelType::opaque(1))
+        .insert(2, VoxelType::opaque(2))
+        .insert(3, VoxelType::translucent(3))
+        .insert(4, VoxelType::translucent(4));
+
+    // Opaque neighbours hide the faces between them.
+    assert_eq!(quads(&registry, &pair(1, 2)), 10);
+    // An opaque voxel shows its face to a translucent neighbour, but not the other way round.
+    assert_eq!(quads(&registry, &pair(1, 3)), 11);
+    // Translucent neighbours hide the faces between them.
+    assert_eq!(quads(&registry, &pair(3, 4)), 10);
+
+    assert!(registry.is_solid(1));
+    assert!(!registry.is_solid(3));
+    assert!(!registry.is_solid(9));
+}
+
+#[test]
+fn merge_groups_split_quads() {
+    let mut registry = VoxelTypeRegistry::default();
+    registry
+        .insert(1, VoxelType::opaque(7))
+        .insert(2, VoxelType::opaque(7))
+        .insert(3, VoxelType::opaque(7).with_merge_group(1))
+        .insert(4, VoxelType::opaque(8));
+
+    // Different IDs with the same merge group and material are merged into shared quads.
+    assert_eq!(quads(&registry, &pair(1, 2)), 6);
+    // A different merge group or material keeps them apart.
+    assert_eq!(quads(&registry, &pair(1, 3)), 10);
+    assert_eq!(quads(&registry, &pair(1, 4)), 10);
+
+    let mesh = registry.resolve_chunk(&pair(1, 4)).build();
+    let mut materials = color_indices(&mesh).to_vec();
+    materials.sort();
+    materials.dedup();
+    assert_eq!(materials, [7, 8]);
+}
+
+#[test]
+fn emissive_voxels_have_lights() {
+    let mut registry = VoxelTypeRegistry::default();
+    registry
+        .insert(1, VoxelType::opaque(1))
+        .insert(2, VoxelType::opaque(2).with_emission(3.));
+
+    let mut chunk = pair(1, 2);
+    // Padding voxels belong to neighbouring chunks.
+    let padding = chunk.shape.linearize([0, 1, 1]) as usize;
+    chunk.voxels[padding] = 2;
+
+    let lights = registry.lights(&chunk);
+    assert_eq!(lights.len(), 1);
+    assert_eq!(lights[0].origin, Vec3::new(2., 1., 1.));
+    assert_eq!(lights[0].intensity, 3.);
+}
@@ -0,0 +1,140 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use voxy::{QuantizedPalette, VoxelizeMode, VoxelizeSettings, voxelize};
+
+fn count_solid(voxelized: &voxy::Voxelized) -> usize {
+    voxelized
+        .chunk
+        .voxels
+        .iter()
+        .filter(|voxel| voxel.idx != 0)
+        .count()
+}
+
+#[test]
+fn voxelize_cuboid() {
+    let mesh = Cuboid::default().mesh().build();
+
+    let solid = voxelize(
+        &mesh,
+        None,
+        &VoxelizeSettings {
+            resolution: 8,
+            ..default()
+        },
+    )
+    .unwrap();
+    assert_eq!(solid.voxel_size, 0.125);
+    assert_eq!(count_solid(&solid), 8 * 8 * 8);
+    assert_eq!(solid.origin, Vec3::splat(-0.625));
+
+    let surface = voxelize(
+        &mesh,
+        None,
+        &VoxelizeSettings {
+            resolution: 8,
+            mode: VoxelizeMode::Surface,
+            ..default()
+        },
+    )
+    .unwrap();
+    assert_eq!(count_solid(&surface), 8 * 8 * 8 - 6 * 6 * 6);
+
+    // Padding is left empty.
+    assert_eq!(surface.chunk.voxels[0].idx, 0);
+}
+
+#[test]
+fn voxelize_sphere_is_filled() {
+    let mesh = Sphere::new(1.).mesh().ico(4).unwrap();
+    let voxelized = voxelize(
+        &mesh,
+        None,
+        &VoxelizeSettings {
+            resolution: 16,
+            ..default()
+        },
+    )
+    .unwrap();
+
+    // Between the volumes of spheres with radii of 8 and 9 voxels, since voxels touching the surface are filled.
+    let volume = |radius: f32| 4. / 3. * std::f32::consts::PI * radius.powi(3);
+    let count = count_solid(&voxelized) as f32;
+    assert!(count > volume(8.) && count < volume(9.), "{count}");
+}
+
+#[test]
+fn voxelize_samples_texture() {
+    // Left half red, right half blue.
+    let mut data = Vec::new();
+    for _ in 0..2 {
+        data.extend_from_slice(&[255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+    let texture = Image::new(
+        Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+
+    let mesh = Plane3d::default().mesh().size(1., 1.).build();
+    let voxelized = voxelize(
+        &mesh,
+        Some(&texture),
+        &VoxelizeSettings {
+            resolution: 8,
+            mode: VoxelizeMode::Surface,
+            ..default()
+        },
+    )
+    .unwrap();
+
+    // Voxels on the border between the halves may mix both colours.
+    let palette = &voxelized.palette.colors;
+    assert!(palette.len() <= 3);
+    for color in [Vec3::X, Vec3::Z] {
+        assert!(palette.iter().any(|entry| entry.distance(color) < 0.01));
+    }
+}
+
+#[test]
+fn quantize_palette() {
+    let colors = [Vec3::X, Vec3::Y, Vec3::Z, Vec3::X];
+    let palette = QuantizedPalette::from_samples(&colors, 256);
+    assert_eq!(palette.colors.len(), 3);
+    for color in [Vec3::X, Vec3::Y, Vec3::Z] {
+        let idx = palette.nearest(color) as usize;
+        assert!(palette.colors[idx].distance(color) < 1e-4);
+    }
+
+    let gradient: Vec<_> = (0..1000)
+        .map(|idx| Vec3::splat(idx as f32 / 1000.))
+        .collect();
+    let palette = QuantizedPalette::from_samples(&gradient, 4);
+    assert_eq!(palette.colors.len(), 4);
+    assert_eq!(palette.material().colors[4], Vec3::ONE);
+}
+
+#[test]
+fn quantize_ignores_non_finite_samples() {
+    let colors = [
+        Vec3::X,
+        Vec3::NAN,
+        Vec3::new(0., f32::INFINITY, 0.),
+        Vec3::Z,
+    ];
+    let palette = QuantizedPalette::from_samples(&colors, 256);
+    assert_eq!(palette.colors.len(), 2);
+    assert!(palette.colors.iter().all(|color| color.is_finite()));
+
+    let palette = QuantizedPalette::from_samples(&[Vec3::NAN], 4);
+    assert!(palette.colors.is_empty());
+}